@@ -0,0 +1,546 @@
+use crate::error::{Error, Result};
+use crate::report::{BacktestReport, EquityPoint};
+use crate::venue::{SimulatedExchange, SimulatedVenue, VenueEvent};
+use chrono::{DateTime, TimeDelta, Utc};
+use quant_exchange::TradingVenue;
+use quant_order::OrderRouter;
+use quant_schema::{
+    Bar, BookUpdate, Decimal, Exchange, Fill, Interval, MarketEvent, OrderId, Price, ReceiveTime,
+    Trade,
+};
+use quant_storage::MarketDataStore;
+use quant_strategy::{Strategy, StrategyRuntime};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// 回放的数据
+#[derive(Debug, Clone)]
+enum Feed {
+    Candles {
+        exchange: Exchange,
+        symbol: String,
+        interval: Interval,
+    },
+    Trades {
+        exchange: Exchange,
+        symbol: String,
+    },
+    Books {
+        exchange: Exchange,
+        symbol: String,
+    },
+}
+
+/// 按时间回放的行情
+#[derive(Debug, Clone)]
+enum Event {
+    Book(BookUpdate),
+    Trade(Trade),
+    Bar(Bar),
+}
+
+impl Event {
+    fn time(&self) -> DateTime<Utc> {
+        match self {
+            Event::Book(update) => update.timestamp,
+            Event::Trade(trade) => trade.timestamp,
+            Event::Bar(bar) => bar.close_time,
+        }
+    }
+
+    /// 同一时间的事件先更新深度，再处理成交，最后是收盘的 K 线
+    fn rank(&self) -> u8 {
+        match self {
+            Event::Book(_) => 0,
+            Event::Trade(_) => 1,
+            Event::Bar(_) => 2,
+        }
+    }
+
+    fn exchange(&self) -> &Exchange {
+        match self {
+            Event::Book(update) => &update.exchange,
+            Event::Trade(trade) => &trade.exchange,
+            Event::Bar(bar) => &bar.exchange,
+        }
+    }
+
+    fn symbol(&self) -> &str {
+        match self {
+            Event::Book(update) => &update.symbol,
+            Event::Trade(trade) => &trade.symbol,
+            Event::Bar(bar) => &bar.symbol,
+        }
+    }
+}
+
+/// 事件驱动的回测引擎：从 quant-storage 读取 K 线、成交和深度，按时间顺序回放给与实盘相同的 `Strategy`
+///
+/// 时钟取回放到的事件时间，策略的订单通过 `OrderRouter` 发送到 [`SimulatedExchange`] 撮合，
+/// 成交和订单状态变化再回到 `StrategyRuntime`，同样的输入总是得到同样的结果。
+/// K 线在收盘时间回放，只回放收盘时间在 `(start, end]` 内的已完结 K 线；成交和深度回放时间在 `[start, end)` 内的。
+/// 全部数据在开始时读入内存。
+///
+/// 权益以 `quote_asset` 计：初始余额加上已实现和未实现盈亏，减去手续费；未实现盈亏按最新成交价、
+/// K 线收盘价或深度中间价计算，合约按 U 本位面值换算。
+pub struct Backtest<'a> {
+    store: &'a dyn MarketDataStore,
+    range: Range<DateTime<Utc>>,
+    feeds: Vec<Feed>,
+    runtime: StrategyRuntime<'a>,
+    venues: BTreeMap<Exchange, Arc<Mutex<SimulatedExchange>>>,
+    quote_asset: String,
+    timer_interval: Option<TimeDelta>,
+    equity_interval: TimeDelta,
+}
+
+impl<'a> Backtest<'a> {
+    pub fn new(store: &'a dyn MarketDataStore, range: Range<DateTime<Utc>>) -> Self {
+        Self {
+            store,
+            range,
+            feeds: Vec::new(),
+            runtime: StrategyRuntime::new(OrderRouter::new()),
+            venues: BTreeMap::new(),
+            quote_asset: "USDT".to_string(),
+            timer_interval: None,
+            equity_interval: TimeDelta::hours(1),
+        }
+    }
+
+    /// 回放 K 线，策略在 `on_bar` 中收到
+    pub fn candles(
+        mut self,
+        exchange: Exchange,
+        symbol: impl Into<String>,
+        interval: Interval,
+    ) -> Self {
+        self.feeds.push(Feed::Candles {
+            exchange,
+            symbol: symbol.into(),
+            interval,
+        });
+        self
+    }
+
+    /// 回放逐笔成交，策略在 `on_trade` 中收到
+    pub fn trades(mut self, exchange: Exchange, symbol: impl Into<String>) -> Self {
+        self.feeds.push(Feed::Trades {
+            exchange,
+            symbol: symbol.into(),
+        });
+        self
+    }
+
+    /// 回放深度变化，只用于撮合
+    pub fn books(mut self, exchange: Exchange, symbol: impl Into<String>) -> Self {
+        self.feeds.push(Feed::Books {
+            exchange,
+            symbol: symbol.into(),
+        });
+        self
+    }
+
+    /// 注册模拟交易所，没有注册的交易所使用没有余额和手续费的默认配置
+    pub fn venue(mut self, simulator: SimulatedExchange) -> Self {
+        for symbol in simulator.symbols() {
+            self.runtime.portfolio_mut().set_symbol(symbol.clone());
+        }
+        let venue = SimulatedVenue::new(simulator);
+        self.venues.insert(venue.exchange(), venue.shared());
+        self.runtime.router_mut().add_venue(venue);
+        self
+    }
+
+    /// 权益的计价币种，默认 USDT
+    pub fn quote_asset(mut self, asset: impl Into<String>) -> Self {
+        self.quote_asset = asset.into();
+        self
+    }
+
+    /// 按回放时间调用 `on_timer` 的间隔，默认不调用
+    pub fn timer_interval(mut self, interval: TimeDelta) -> Self {
+        self.timer_interval = Some(interval);
+        self
+    }
+
+    /// 权益曲线的采样间隔，也是夏普比率的收益率周期，默认 1 小时
+    pub fn equity_interval(mut self, interval: TimeDelta) -> Self {
+        self.equity_interval = interval;
+        self
+    }
+
+    pub fn add_strategy(&mut self, strategy: impl Strategy + 'a) {
+        self.runtime.add_strategy(strategy);
+    }
+
+    /// 运行回测，结束时调用策略的 `on_exit`
+    pub async fn run(mut self) -> Result<BacktestReport> {
+        let Range { start, end } = self.range;
+        if start >= end {
+            return Err(Error::EmptyRange { start, end });
+        }
+        for interval in self.timer_interval.iter().chain([&self.equity_interval]) {
+            if *interval <= TimeDelta::zero() {
+                return Err(Error::InvalidInterval(*interval));
+            }
+        }
+        let events = self.load()?;
+        for event in &events {
+            if !self.venues.contains_key(event.exchange()) {
+                self = self.venue(SimulatedExchange::new(event.exchange().clone()));
+            }
+        }
+
+        let mut replay = Replay {
+            quote_asset: self.quote_asset.clone(),
+            venues: self.venues.clone(),
+            marks: HashMap::new(),
+            traded: BTreeSet::new(),
+            fills: Vec::new(),
+            turnover: Decimal::ZERO,
+            sequence: 0,
+        };
+        let runtime = &mut self.runtime;
+        runtime.sync_portfolio().await?;
+        runtime.init().await?;
+        replay.deliver(runtime).await?;
+
+        let mut curve = vec![EquityPoint {
+            time: start,
+            equity: replay.equity(runtime),
+        }];
+        let mut next_sample = start + self.equity_interval;
+        let mut next_timer = self.timer_interval.map(|interval| start + interval);
+        for event in events {
+            let time = event.time();
+            while let Some(at) = next_timer.filter(|at| *at <= time) {
+                runtime.on_timer(at).await?;
+                replay.deliver(runtime).await?;
+                next_timer = self.timer_interval.map(|interval| at + interval);
+            }
+            // 采样点的权益包含同一时间的事件
+            while next_sample < time {
+                curve.push(EquityPoint {
+                    time: next_sample,
+                    equity: replay.equity(runtime),
+                });
+                next_sample += self.equity_interval;
+            }
+            replay.replay(runtime, event).await?;
+        }
+        while let Some(at) = next_timer.filter(|at| *at < end) {
+            runtime.on_timer(at).await?;
+            replay.deliver(runtime).await?;
+            next_timer = self.timer_interval.map(|interval| at + interval);
+        }
+        while next_sample < end {
+            curve.push(EquityPoint {
+                time: next_sample,
+                equity: replay.equity(runtime),
+            });
+            next_sample += self.equity_interval;
+        }
+        runtime.shutdown().await?;
+        replay.deliver(runtime).await?;
+        curve.push(EquityPoint {
+            time: end,
+            equity: replay.equity(runtime),
+        });
+
+        Ok(BacktestReport::new(
+            replay.fills,
+            curve,
+            self.equity_interval,
+            replay.turnover,
+        ))
+    }
+
+    /// 读取全部数据并按时间排序，同一时间按事件类型和数据源的注册顺序排列
+    fn load(&self) -> Result<Vec<Event>> {
+        let Range { start, end } = self.range;
+        let mut events = Vec::new();
+        for feed in &self.feeds {
+            match feed {
+                Feed::Candles {
+                    exchange,
+                    symbol,
+                    interval,
+                } => {
+                    let candles = self.store.candles(
+                        exchange,
+                        symbol,
+                        *interval,
+                        start - interval.duration()..end,
+                    )?;
+                    events.extend(
+                        candles
+                            .iter()
+                            .filter(|candle| candle.closed)
+                            .map(Bar::from)
+                            .filter(|bar| bar.close_time > start && bar.close_time <= end)
+                            .map(Event::Bar),
+                    );
+                }
+                Feed::Trades { exchange, symbol } => {
+                    let trades = self.store.trades(exchange, symbol, start..end)?;
+                    events.extend(trades.into_iter().map(Event::Trade));
+                }
+                Feed::Books { exchange, symbol } => {
+                    let updates = self.store.book_updates(exchange, symbol, start..end)?;
+                    events.extend(updates.into_iter().map(Event::Book));
+                }
+            }
+        }
+        events.sort_by_key(|event| (event.time(), event.rank()));
+        Ok(events)
+    }
+}
+
+/// 回放过程中的状态
+struct Replay {
+    quote_asset: String,
+    venues: BTreeMap<Exchange, Arc<Mutex<SimulatedExchange>>>,
+    /// 计算未实现盈亏的价格
+    marks: HashMap<(Exchange, String), Price>,
+    /// 有过成交的产品，用于汇总已实现盈亏
+    traded: BTreeSet<(Exchange, String)>,
+    fills: Vec<Fill>,
+    turnover: Decimal,
+    sequence: u64,
+}
+
+impl Replay {
+    fn simulator(&self, exchange: &Exchange) -> MutexGuard<'_, SimulatedExchange> {
+        self.venues[exchange]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 先用行情撮合之前的订单并通知策略成交，再把行情交给策略
+    async fn replay(&mut self, runtime: &mut StrategyRuntime<'_>, event: Event) -> Result<()> {
+        let key = (event.exchange().clone(), event.symbol().to_string());
+        let mark = {
+            let mut simulator = self.simulator(&key.0);
+            simulator.set_time(event.time());
+            match &event {
+                Event::Book(update) => {
+                    simulator.on_book_update(update);
+                    simulator
+                        .book(&update.symbol)
+                        .and_then(|book| book.mid_price())
+                }
+                Event::Trade(trade) => {
+                    simulator.on_trade(trade);
+                    Some(trade.price)
+                }
+                Event::Bar(bar) => {
+                    simulator.on_bar(bar);
+                    Some(bar.close)
+                }
+            }
+        };
+        if let Some(mark) = mark {
+            self.marks.insert(key.clone(), mark);
+        }
+        self.deliver(runtime).await?;
+
+        match event {
+            Event::Book(_) => {}
+            Event::Trade(trade) => {
+                let received = ReceiveTime {
+                    wall: trade.timestamp,
+                    monotonic: trade
+                        .timestamp
+                        .timestamp_nanos_opt()
+                        .unwrap_or_default()
+                        .max(0) as u64,
+                };
+                self.sequence += 1;
+                let event = MarketEvent::new(key.0, trade, received, self.sequence);
+                runtime.on_trade(&event).await?;
+            }
+            Event::Bar(bar) => runtime.on_bar(&bar).await?,
+        }
+        self.deliver(runtime).await
+    }
+
+    /// 把模拟交易所产生的成交和订单状态变化交给策略，直到没有新的事件
+    async fn deliver(&mut self, runtime: &mut StrategyRuntime<'_>) -> Result<()> {
+        loop {
+            let mut events = Vec::new();
+            for (exchange, simulator) in &self.venues {
+                let mut simulator = simulator.lock().unwrap_or_else(PoisonError::into_inner);
+                events.extend(
+                    simulator
+                        .drain_events()
+                        .into_iter()
+                        .map(|event| (exchange.clone(), event)),
+                );
+            }
+            if events.is_empty() {
+                return Ok(());
+            }
+            for (exchange, event) in events {
+                match event {
+                    VenueEvent::Fill(fill) => {
+                        let multiplier = self.simulator(&exchange).multiplier(&fill.symbol);
+                        self.turnover += fill.price * fill.quantity * multiplier;
+                        self.traded.insert((exchange.clone(), fill.symbol.clone()));
+                        runtime.on_fill(&fill).await?;
+                        self.fills.push(fill);
+                    }
+                    VenueEvent::Status {
+                        order_id,
+                        status,
+                        at,
+                    } => {
+                        runtime
+                            .on_order_status(&exchange, &OrderId::Exchange(order_id), status, at)
+                            .await?;
+                    }
+                }
+            }
+        }
+    }
+
+    fn equity(&self, runtime: &StrategyRuntime<'_>) -> Decimal {
+        let mut equity: Decimal = self
+            .venues
+            .values()
+            .map(|simulator| {
+                simulator
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .initial_balance(&self.quote_asset)
+            })
+            .sum();
+
+        // 已实现盈亏已经扣除了能换算的手续费，其余以计价币种收取的单独扣除
+        let portfolio = runtime.portfolio();
+        for exchange in self.venues.keys() {
+            equity -= portfolio.fee(exchange, &self.quote_asset);
+        }
+        for key in &self.traded {
+            let (exchange, symbol) = key;
+            let multiplier = portfolio.multiplier(exchange, symbol);
+            equity += portfolio.realized_pnl(exchange, symbol);
+            if let Some(position) = portfolio.position(exchange, symbol)
+                && let Some(mark) = self.marks.get(key)
+            {
+                let quantity = portfolio.net_quantity(exchange, symbol);
+                equity += (*mark - position.entry_price) * quantity * multiplier;
+            }
+        }
+        equity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::num;
+    use async_trait::async_trait;
+    use quant_schema::{Candle, OrderRequest, Quantity, Side};
+    use quant_storage::MemoryStore;
+    use quant_strategy::Context;
+
+    /// 第 1 根 K 线收盘买入，第 3 根收盘卖出
+    struct RoundTrip;
+
+    #[async_trait]
+    impl Strategy for RoundTrip {
+        fn name(&self) -> &str {
+            "round-trip"
+        }
+
+        async fn on_bar(
+            &mut self,
+            ctx: &mut Context<'_>,
+            bar: &Bar,
+        ) -> quant_strategy::error::Result<()> {
+            let count = ctx
+                .market()
+                .bars(&bar.exchange, &bar.symbol, bar.bar_type, 10)
+                .len();
+            let side = match count {
+                1 => Side::Buy,
+                3 => Side::Sell,
+                _ => return Ok(()),
+            };
+            let request = OrderRequest::market(&bar.symbol, side, Quantity::new(Decimal::ONE));
+            ctx.submit(bar.exchange.clone(), request);
+            Ok(())
+        }
+    }
+
+    fn candle(minute: i64, open: &str, close: &str) -> Candle {
+        Candle {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            interval: Interval::minutes(1),
+            open_time: DateTime::UNIX_EPOCH + TimeDelta::minutes(minute),
+            open: num(open),
+            high: num(open.max(close)),
+            low: num(open.min(close)),
+            close: num(close),
+            volume: num("1"),
+            quote_volume: num(close),
+            closed: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_candles() {
+        let mut store = MemoryStore::new();
+        store
+            .write_candles(&[
+                candle(0, "99", "100"),
+                candle(1, "101", "105"),
+                candle(2, "106", "110"),
+                candle(3, "108", "108"),
+            ])
+            .unwrap();
+        let start = DateTime::UNIX_EPOCH;
+        let mut backtest = Backtest::new(&store, start..start + TimeDelta::minutes(4))
+            .candles(Exchange::Okex, "BTC-USDT", Interval::minutes(1))
+            .venue(
+                SimulatedExchange::new(Exchange::Okex)
+                    .balance("USDT", num("1000"))
+                    .fees(Decimal::ZERO, num("0.001")),
+            )
+            .equity_interval(TimeDelta::minutes(1));
+        backtest.add_strategy(RoundTrip);
+        let report = backtest.run().await.unwrap();
+
+        // 下一根 K 线的开盘价成交
+        let fills: Vec<_> = report
+            .fills
+            .iter()
+            .map(|fill| (fill.side, fill.price.to_string()))
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (Side::Buy, "101".to_string()),
+                (Side::Sell, "108".to_string())
+            ]
+        );
+        let equity: Vec<_> = report
+            .equity_curve
+            .iter()
+            .map(|point| point.equity.normalize().to_string())
+            .collect();
+        assert_eq!(
+            equity,
+            vec!["1000", "1000", "1003.899", "1008.899", "1006.791"]
+        );
+        assert_eq!(report.summary.fees, num("0.209"));
+        assert_eq!(report.summary.turnover, num("209"));
+        assert!(matches!(
+            report.equity_curve.last(),
+            Some(point) if point.time == start + TimeDelta::minutes(4)
+        ));
+    }
+}
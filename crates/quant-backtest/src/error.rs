@@ -0,0 +1,18 @@
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Storage(#[from] quant_storage::error::Error),
+    #[error(transparent)]
+    Strategy(#[from] quant_strategy::error::Error),
+    #[error("Backtest range is empty: {start} >= {end}")]
+    EmptyRange {
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    },
+    #[error("Interval must be positive: {0}")]
+    InvalidInterval(chrono::TimeDelta),
+}
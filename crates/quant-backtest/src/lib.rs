@@ -0,0 +1,12 @@
+pub mod engine;
+pub mod error;
+pub mod matching;
+pub mod report;
+#[cfg(test)]
+mod test_util;
+pub mod venue;
+
+pub use engine::Backtest;
+pub use matching::{MatchEvent, MatchingEngine, SimOrder};
+pub use report::{BacktestReport, EquityPoint, Summary};
+pub use venue::{SimulatedExchange, SimulatedVenue, VenueEvent};
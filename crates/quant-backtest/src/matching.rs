@@ -0,0 +1,598 @@
+use quant_exchange::schema::okex::order::OrderType;
+use quant_marketdata::book::OrderBook;
+use quant_schema::{Bar, BookUpdate, Exchange, OrderStatus, Price, Quantity, Side, Trade};
+
+/// 撮合结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchEvent {
+    Fill {
+        order_id: String,
+        price: Price,
+        quantity: Quantity,
+        is_maker: bool,
+    },
+    /// 订单剩余部分不再撮合：会主动成交的只做 Maker 单为 `Rejected`，
+    /// 市价单、IOC 和 FOK 不能成交的部分为 `Expired`
+    Done {
+        order_id: String,
+        status: OrderStatus,
+    },
+}
+
+/// 撮合引擎中的订单
+#[derive(Debug, Clone)]
+pub struct SimOrder {
+    pub order_id: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    /// 限价，市价单和 `optimal_limit_ioc` 忽略
+    pub price: Option<Price>,
+    pub quantity: Quantity,
+    pub filled: Quantity,
+    /// 估计的同价位排在前面的数量，全部成交或撤销后才轮到本订单
+    pub queue_ahead: Quantity,
+    /// 上次观察到的挂单价位的数量，用于估计撤单对排队位置的影响
+    level_size: Quantity,
+    /// 到达时没有深度，等待下一条成交或 K 线撮合
+    awaiting_quote: bool,
+    /// 没有深度时挂单，排队位置未知，收到深度后视为排在同价位的最后
+    queue_unknown: bool,
+}
+
+impl SimOrder {
+    pub fn new(
+        order_id: impl Into<String>,
+        side: Side,
+        order_type: OrderType,
+        price: Option<Price>,
+        quantity: Quantity,
+    ) -> Self {
+        Self {
+            order_id: order_id.into(),
+            side,
+            order_type,
+            price,
+            quantity,
+            filled: Quantity::ZERO,
+            queue_ahead: Quantity::ZERO,
+            level_size: Quantity::ZERO,
+            awaiting_quote: false,
+            queue_unknown: false,
+        }
+    }
+
+    pub fn remaining(&self) -> Quantity {
+        (self.quantity - self.filled).max(Quantity::ZERO)
+    }
+
+    /// 市价单没有限价
+    fn limit(&self) -> Option<Price> {
+        match self.order_type {
+            OrderType::Market | OrderType::OptimalLimitIoc => None,
+            _ => self.price,
+        }
+    }
+
+    fn is_post_only(&self) -> bool {
+        matches!(
+            self.order_type,
+            OrderType::PostOnly | OrderType::MmpAndPostOnly
+        )
+    }
+
+    /// 未成交的部分是否挂在订单簿上
+    fn rests(&self) -> bool {
+        matches!(
+            self.order_type,
+            OrderType::Limit
+                | OrderType::PostOnly
+                | OrderType::MarketMakerProtection
+                | OrderType::MmpAndPostOnly
+        )
+    }
+}
+
+/// 以 `side` 方向成交时 `price` 是否不差于 `limit`
+fn acceptable(side: Side, price: Price, limit: Price) -> bool {
+    match side {
+        Side::Buy => price <= limit,
+        Side::Sell => price >= limit,
+    }
+}
+
+/// 单个产品的撮合引擎，支持 OKX 的全部订单类型
+///
+/// 有深度时，订单到达后按档位吃掉对手方的深度，可以部分成交，吃掉的数量从本地订单簿中扣除，
+/// 直到下一次深度推送覆盖：
+/// - `market`、`optimal_limit_ioc` 吃到订单簿耗尽为止，剩余部分过期
+/// - `limit`、`mmp` 吃掉限价以内的深度后，剩余部分挂单
+/// - `post_only`、`mmp_and_post_only` 会主动成交时被拒绝
+/// - `ioc` 吃掉限价以内的深度后剩余部分过期，`fok` 限价以内的深度不足时整单过期
+///
+/// 挂单的排队位置取到达时同价位的数量。成交价穿过挂单价，或对手方主动成交的成交价等于挂单价时，
+/// 成交数量先消耗前面的数量，余下的与挂单成交；深度推送中对手价穿过挂单价时，穿过的深度同样先消耗前面的数量。
+/// 超出成交数量的部分继续挂单。深度推送中同价位数量减少时，前面的数量按比例减少。
+///
+/// 没有深度的产品在下一条成交或 K 线上撮合：主动成交按成交价或开盘价全部成交；
+/// 挂单的排队位置未知，成交价穿过挂单价时按成交数量成交，等于挂单价时不成交，K 线最高最低价穿过挂单价时全部成交。
+///
+/// `OrderType::Other` 是无法识别的订单类型，直接拒绝。
+#[derive(Debug, Clone)]
+pub struct MatchingEngine {
+    book: OrderBook,
+    /// 按到达顺序撮合
+    orders: Vec<SimOrder>,
+}
+
+impl MatchingEngine {
+    pub fn new(exchange: Exchange, symbol: impl Into<String>) -> Self {
+        Self {
+            book: OrderBook::new(exchange, symbol),
+            orders: Vec::new(),
+        }
+    }
+
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    /// 挂在订单簿上或等待行情的订单
+    pub fn orders(&self) -> &[SimOrder] {
+        &self.orders
+    }
+
+    pub fn order(&self, order_id: &str) -> Option<&SimOrder> {
+        self.orders.iter().find(|order| order.order_id == order_id)
+    }
+
+    /// 订单到达交易所
+    pub fn submit(&mut self, mut order: SimOrder) -> Vec<MatchEvent> {
+        let mut events = Vec::new();
+        if order.order_type == OrderType::Other
+            || order.limit().is_none()
+                && !matches!(
+                    order.order_type,
+                    OrderType::Market | OrderType::OptimalLimitIoc
+                )
+        {
+            events.push(MatchEvent::Done {
+                order_id: order.order_id,
+                status: OrderStatus::Rejected,
+            });
+            return events;
+        }
+        if self.book.best_bid().is_none() && self.book.best_ask().is_none() {
+            order.awaiting_quote = true;
+            self.orders.push(order);
+            return events;
+        }
+        self.arrive(order, &mut events);
+        events
+    }
+
+    /// 撤单，返回被撤销的订单
+    pub fn cancel(&mut self, order_id: &str) -> Option<SimOrder> {
+        let index = self
+            .orders
+            .iter()
+            .position(|order| order.order_id == order_id)?;
+        Some(self.orders.remove(index))
+    }
+
+    pub fn on_book_update(&mut self, update: &BookUpdate) -> Vec<MatchEvent> {
+        self.book.apply_update(update);
+        let mut events = Vec::new();
+        for mut order in std::mem::take(&mut self.orders) {
+            if order.awaiting_quote {
+                order.awaiting_quote = false;
+                self.arrive(order, &mut events);
+                continue;
+            }
+            let Some(price) = order.limit() else {
+                self.orders.push(order);
+                continue;
+            };
+            let opposite = match order.side {
+                Side::Buy => self.book.best_ask(),
+                Side::Sell => self.book.best_bid(),
+            };
+            if opposite.is_some_and(|(best, _)| acceptable(order.side, best, price)) {
+                self.fill_crossing(&mut order, price, &mut events);
+                if !order.remaining().is_zero() {
+                    self.orders.push(order);
+                }
+                continue;
+            }
+            let size = self.book.level(order.side, price);
+            if order.queue_unknown {
+                order.queue_unknown = false;
+                order.queue_ahead = size;
+            } else if size < order.level_size {
+                order.queue_ahead = order.queue_ahead * (size.value() / order.level_size.value());
+            }
+            order.queue_ahead = order.queue_ahead.min(size);
+            order.level_size = size;
+            self.orders.push(order);
+        }
+        events
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) -> Vec<MatchEvent> {
+        let mut events = Vec::new();
+        // 一笔成交的数量在按到达顺序排列的挂单之间依次分配
+        let mut available = trade.quantity;
+        for mut order in std::mem::take(&mut self.orders) {
+            if order.awaiting_quote {
+                self.first_quote(order, trade.price, None, &mut events);
+                continue;
+            }
+            let Some(price) = order.limit() else {
+                self.orders.push(order);
+                continue;
+            };
+            let through = match order.side {
+                Side::Buy => trade.price < price,
+                Side::Sell => trade.price > price,
+            };
+            // 排队位置未知时，成交价等于挂单价不算成交
+            let at_price = trade.price == price && trade.side != order.side && !order.queue_unknown;
+            if through || at_price {
+                let ahead = order.queue_ahead.min(available);
+                order.queue_ahead -= ahead;
+                order.level_size = (order.level_size - ahead).max(Quantity::ZERO);
+                let quantity = (available - ahead).min(order.remaining());
+                fill(&mut order, price, quantity, true, &mut events);
+                available -= ahead + quantity;
+            }
+            if !order.remaining().is_zero() {
+                self.orders.push(order);
+            }
+        }
+        events
+    }
+
+    pub fn on_bar(&mut self, bar: &Bar) -> Vec<MatchEvent> {
+        let mut events = Vec::new();
+        for mut order in std::mem::take(&mut self.orders) {
+            if order.awaiting_quote {
+                self.first_quote(order, bar.open, Some(bar), &mut events);
+                continue;
+            }
+            match order.limit() {
+                Some(price) if bar_crosses(bar, order.side, price) => {
+                    fill_all(&mut order, price, true, &mut events);
+                }
+                _ => self.orders.push(order),
+            }
+        }
+        events
+    }
+
+    /// 有深度时订单到达：先主动成交，剩余部分挂单或过期
+    fn arrive(&mut self, mut order: SimOrder, events: &mut Vec<MatchEvent>) {
+        let limit = order.limit();
+        let opposite = match order.side {
+            Side::Buy => self.book.asks(usize::MAX),
+            Side::Sell => self.book.bids(usize::MAX),
+        };
+        let available: Vec<_> = opposite
+            .into_iter()
+            .take_while(|(price, _)| {
+                limit.is_none_or(|limit| acceptable(order.side, *price, limit))
+            })
+            .collect();
+
+        if order.is_post_only() && !available.is_empty() {
+            events.push(MatchEvent::Done {
+                order_id: order.order_id,
+                status: OrderStatus::Rejected,
+            });
+            return;
+        }
+        if order.order_type == OrderType::FillOrKill
+            && available.iter().map(|(_, size)| *size).sum::<Quantity>() < order.remaining()
+        {
+            events.push(MatchEvent::Done {
+                order_id: order.order_id,
+                status: OrderStatus::Expired,
+            });
+            return;
+        }
+
+        for (price, size) in available {
+            let quantity = size.min(order.remaining());
+            if quantity.is_zero() {
+                break;
+            }
+            fill(&mut order, price, quantity, false, events);
+            self.deplete(order.side.opposite(), price, size - quantity);
+        }
+        if order.remaining().is_zero() {
+            return;
+        }
+        match (order.rests(), order.limit()) {
+            (true, Some(price)) => {
+                order.queue_ahead = self.book.level(order.side, price);
+                order.level_size = order.queue_ahead;
+                self.orders.push(order);
+            }
+            _ => events.push(MatchEvent::Done {
+                order_id: order.order_id,
+                status: OrderStatus::Expired,
+            }),
+        }
+    }
+
+    /// 对手方深度穿过挂单价：穿过的深度先消耗排在前面的数量，余下的按挂单价成交，并从本地订单簿中扣除
+    fn fill_crossing(&mut self, order: &mut SimOrder, price: Price, events: &mut Vec<MatchEvent>) {
+        let opposite = match order.side {
+            Side::Buy => self.book.asks(usize::MAX),
+            Side::Sell => self.book.bids(usize::MAX),
+        };
+        let crossing: Vec<_> = opposite
+            .into_iter()
+            .take_while(|(level, _)| acceptable(order.side, *level, price))
+            .collect();
+        let available = crossing.iter().map(|(_, size)| *size).sum::<Quantity>();
+        let ahead = order.queue_ahead.min(available);
+        order.queue_ahead -= ahead;
+        let quantity = (available - ahead).min(order.remaining());
+        fill(order, price, quantity, true, events);
+
+        let mut consumed = ahead + quantity;
+        for (level, size) in crossing {
+            if consumed.is_zero() {
+                break;
+            }
+            let taken = size.min(consumed);
+            consumed -= taken;
+            self.deplete(order.side.opposite(), level, size - taken);
+        }
+        order.level_size = self.book.level(order.side, price);
+    }
+
+    /// 没有深度时用第一条成交价或 K 线开盘价撮合，排队位置未知
+    fn first_quote(
+        &mut self,
+        mut order: SimOrder,
+        taker_price: Price,
+        bar: Option<&Bar>,
+        events: &mut Vec<MatchEvent>,
+    ) {
+        order.awaiting_quote = false;
+        let Some(price) = order.limit() else {
+            fill_all(&mut order, taker_price, false, events);
+            return;
+        };
+        if acceptable(order.side, taker_price, price) {
+            if order.is_post_only() {
+                events.push(MatchEvent::Done {
+                    order_id: order.order_id,
+                    status: OrderStatus::Rejected,
+                });
+            } else {
+                fill_all(&mut order, taker_price, false, events);
+            }
+            return;
+        }
+        if !order.rests() {
+            events.push(MatchEvent::Done {
+                order_id: order.order_id,
+                status: OrderStatus::Expired,
+            });
+            return;
+        }
+        // K 线覆盖了挂单之后的整段时间，开盘价没有成交时继续检查最高最低价
+        if bar.is_some_and(|bar| bar_crosses(bar, order.side, price)) {
+            fill_all(&mut order, price, true, events);
+            return;
+        }
+        order.queue_unknown = true;
+        self.orders.push(order);
+    }
+
+    /// 主动成交后扣除本地订单簿的数量
+    fn deplete(&mut self, side: Side, price: Price, remaining: Quantity) {
+        let level = vec![(price, remaining)];
+        let (bids, asks) = match side {
+            Side::Buy => (level, Vec::new()),
+            Side::Sell => (Vec::new(), level),
+        };
+        self.book.apply_update(&BookUpdate {
+            exchange: self.book.exchange().clone(),
+            symbol: self.book.symbol().to_string(),
+            sequence: self.book.seq_id().unwrap_or_default(),
+            snapshot: false,
+            bids,
+            asks,
+            timestamp: self.book.updated_at().unwrap_or_default(),
+        });
+    }
+}
+
+/// K 线最高最低价严格穿过挂单价，等于挂单价时排队位置未知，不算成交
+fn bar_crosses(bar: &Bar, side: Side, price: Price) -> bool {
+    match side {
+        Side::Buy => bar.low < price,
+        Side::Sell => bar.high > price,
+    }
+}
+
+/// 剩余部分全部成交
+fn fill_all(order: &mut SimOrder, price: Price, is_maker: bool, events: &mut Vec<MatchEvent>) {
+    let quantity = order.remaining();
+    fill(order, price, quantity, is_maker, events);
+}
+
+fn fill(
+    order: &mut SimOrder,
+    price: Price,
+    quantity: Quantity,
+    is_maker: bool,
+    events: &mut Vec<MatchEvent>,
+) {
+    if quantity <= Quantity::ZERO {
+        return;
+    }
+    order.filled += quantity;
+    events.push(MatchEvent::Fill {
+        order_id: order.order_id.clone(),
+        price,
+        quantity,
+        is_maker,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{num, trade};
+    use chrono::DateTime;
+
+    fn book(bids: &[(&str, &str)], asks: &[(&str, &str)], snapshot: bool) -> BookUpdate {
+        let levels = |levels: &[(&str, &str)]| {
+            levels
+                .iter()
+                .map(|(price, size)| (num(price), num(size)))
+                .collect()
+        };
+        BookUpdate {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            sequence: 1,
+            snapshot,
+            bids: levels(bids),
+            asks: levels(asks),
+            timestamp: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn order(id: &str, side: Side, order_type: OrderType, price: &str, quantity: &str) -> SimOrder {
+        SimOrder::new(id, side, order_type, Some(num(price)), num(quantity))
+    }
+
+    fn fills(events: &[MatchEvent]) -> Vec<(Price, Quantity, bool)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                MatchEvent::Fill {
+                    price,
+                    quantity,
+                    is_maker,
+                    ..
+                } => Some((*price, *quantity, *is_maker)),
+                MatchEvent::Done { .. } => None,
+            })
+            .collect()
+    }
+
+    fn done(events: &[MatchEvent]) -> Option<OrderStatus> {
+        events.iter().find_map(|event| match event {
+            MatchEvent::Done { status, .. } => Some(*status),
+            MatchEvent::Fill { .. } => None,
+        })
+    }
+
+    #[test]
+    fn test_order_types_against_depth() {
+        let mut engine = MatchingEngine::new(Exchange::Okex, "BTC-USDT");
+        engine.on_book_update(&book(
+            &[("99", "1"), ("98", "2")],
+            &[("100", "1"), ("101", "2"), ("102", "5")],
+            true,
+        ));
+
+        // IOC 吃掉限价以内的两档，部分成交后剩余过期
+        let events = engine.submit(order(
+            "1",
+            Side::Buy,
+            OrderType::ImmediateOrCancel,
+            "101",
+            "4",
+        ));
+        assert_eq!(
+            fills(&events),
+            vec![(num("100"), num("1"), false), (num("101"), num("2"), false)]
+        );
+        assert_eq!(done(&events), Some(OrderStatus::Expired));
+        assert_eq!(engine.book().best_ask(), Some((num("102"), num("5"))));
+
+        // FOK 深度不足整单过期，只做 Maker 单会主动成交时被拒绝
+        let events = engine.submit(order("2", Side::Buy, OrderType::FillOrKill, "102", "6"));
+        assert!(fills(&events).is_empty());
+        assert_eq!(done(&events), Some(OrderStatus::Expired));
+        let events = engine.submit(order("3", Side::Buy, OrderType::PostOnly, "102", "1"));
+        assert_eq!(done(&events), Some(OrderStatus::Rejected));
+
+        // 市价单吃完买方深度后剩余过期
+        let events = engine.submit(SimOrder::new(
+            "4",
+            Side::Sell,
+            OrderType::OptimalLimitIoc,
+            None,
+            num("5"),
+        ));
+        assert_eq!(fills(&events).len(), 2);
+        assert_eq!(done(&events), Some(OrderStatus::Expired));
+
+        // 限价单部分成交后挂单
+        let events = engine.submit(order("5", Side::Buy, OrderType::Limit, "102", "6"));
+        assert_eq!(fills(&events), vec![(num("102"), num("5"), false)]);
+        assert_eq!(done(&events), None);
+        assert_eq!(engine.order("5").unwrap().remaining(), num("1"));
+    }
+
+    #[test]
+    fn test_queue_position() {
+        let mut engine = MatchingEngine::new(Exchange::Okex, "BTC-USDT");
+        engine.on_book_update(&book(&[("99", "5")], &[("100", "1")], true));
+        let events = engine.submit(order("1", Side::Buy, OrderType::PostOnly, "99", "1"));
+        assert!(events.is_empty());
+        assert_eq!(engine.order("1").unwrap().queue_ahead, num("5"));
+
+        // 同价位的成交先消耗前面的数量
+        assert!(engine.on_trade(&trade(Side::Sell, "99", "3")).is_empty());
+        assert_eq!(engine.order("1").unwrap().queue_ahead, num("2"));
+
+        // 成交后剩 2，推送中减少到 1 视为撤单，前面的数量按比例减半
+        engine.on_book_update(&book(&[("99", "1")], &[], false));
+        assert_eq!(engine.order("1").unwrap().queue_ahead, num("1"));
+        // 数量增加的挂单排在后面
+        engine.on_book_update(&book(&[("99", "4")], &[], false));
+        assert_eq!(engine.order("1").unwrap().queue_ahead, num("1"));
+
+        let events = engine.on_trade(&trade(Side::Sell, "99", "1.5"));
+        assert_eq!(fills(&events), vec![(num("99"), num("0.5"), true)]);
+        // 主动买入的成交不会成交买单
+        assert!(engine.on_trade(&trade(Side::Buy, "99", "1")).is_empty());
+        let events = engine.on_trade(&trade(Side::Sell, "98", "1"));
+        assert_eq!(fills(&events), vec![(num("99"), num("0.5"), true)]);
+        assert!(engine.orders().is_empty());
+    }
+
+    #[test]
+    fn test_crossing_fills_are_capped() {
+        let mut engine = MatchingEngine::new(Exchange::Okex, "BTC-USDT");
+        engine.on_book_update(&book(&[("99", "2")], &[("100", "5")], true));
+        let events = engine.submit(order("1", Side::Buy, OrderType::Limit, "99", "3"));
+        assert!(events.is_empty());
+
+        // 穿过挂单价的成交先消耗前面的 2，只成交余下的 1
+        let events = engine.on_trade(&trade(Side::Sell, "98", "3"));
+        assert_eq!(fills(&events), vec![(num("99"), num("1"), true)]);
+        assert_eq!(engine.order("1").unwrap().queue_ahead, Quantity::ZERO);
+
+        // 卖一价穿过挂单价，只成交穿过的 0.5，并从订单簿中扣除
+        let events = engine.on_book_update(&book(&[], &[("99", "0.5")], false));
+        assert_eq!(fills(&events), vec![(num("99"), num("0.5"), true)]);
+        assert_eq!(engine.book().best_ask(), Some((num("100"), num("5"))));
+        assert_eq!(engine.order("1").unwrap().remaining(), num("1.5"));
+
+        let events = engine.on_trade(&trade(Side::Sell, "98", "5"));
+        assert_eq!(fills(&events), vec![(num("99"), num("1.5"), true)]);
+        assert!(engine.orders().is_empty());
+
+        let events = engine.submit(order("2", Side::Buy, OrderType::Other, "99", "1"));
+        assert_eq!(done(&events), Some(OrderStatus::Rejected));
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use quant_schema::{Decimal, Fill};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
+
+/// 权益曲线上的一个点
+#[derive(Debug, Clone, Serialize)]
+pub struct EquityPoint {
+    pub time: DateTime<Utc>,
+    pub equity: Decimal,
+}
+
+/// 回测的汇总统计
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub initial_equity: Decimal,
+    pub final_equity: Decimal,
+    /// 总收益率，0.1 表示 10%
+    pub total_return: Decimal,
+    /// 最大回撤，相对于之前的最高权益，0.1 表示 10%
+    pub max_drawdown: Decimal,
+    /// 按权益曲线的采样间隔计算并年化的夏普比率，无风险利率为 0；收益率没有波动时为 `None`
+    pub sharpe_ratio: Option<f64>,
+    pub fill_count: usize,
+    /// 成交额，以计价货币计
+    pub turnover: Decimal,
+    pub fees: Decimal,
+}
+
+/// 回测结果：成交记录、权益曲线和汇总统计
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub fills: Vec<Fill>,
+    /// 按固定间隔采样的权益，第一个点为初始权益
+    pub equity_curve: Vec<EquityPoint>,
+    pub summary: Summary,
+}
+
+impl BacktestReport {
+    pub(crate) fn new(
+        fills: Vec<Fill>,
+        equity_curve: Vec<EquityPoint>,
+        interval: TimeDelta,
+        turnover: Decimal,
+    ) -> Self {
+        let initial_equity = equity_curve
+            .first()
+            .map_or(Decimal::ZERO, |point| point.equity);
+        let final_equity = equity_curve
+            .last()
+            .map_or(Decimal::ZERO, |point| point.equity);
+        let summary = Summary {
+            start: equity_curve
+                .first()
+                .map_or(DateTime::UNIX_EPOCH, |point| point.time),
+            end: equity_curve
+                .last()
+                .map_or(DateTime::UNIX_EPOCH, |point| point.time),
+            initial_equity,
+            final_equity,
+            total_return: ratio(final_equity - initial_equity, initial_equity),
+            max_drawdown: max_drawdown(&equity_curve),
+            sharpe_ratio: sharpe_ratio(&equity_curve, interval),
+            fill_count: fills.len(),
+            turnover,
+            fees: fills.iter().map(|fill| fill.fee).sum(),
+        };
+        Self {
+            fills,
+            equity_curve,
+            summary,
+        }
+    }
+}
+
+fn ratio(numerator: Decimal, denominator: Decimal) -> Decimal {
+    if denominator.is_zero() {
+        Decimal::ZERO
+    } else {
+        numerator / denominator
+    }
+}
+
+fn max_drawdown(curve: &[EquityPoint]) -> Decimal {
+    let mut peak = Decimal::MIN;
+    let mut drawdown = Decimal::ZERO;
+    for point in curve {
+        peak = peak.max(point.equity);
+        if peak > Decimal::ZERO {
+            drawdown = drawdown.max((peak - point.equity) / peak);
+        }
+    }
+    drawdown
+}
+
+fn sharpe_ratio(curve: &[EquityPoint], interval: TimeDelta) -> Option<f64> {
+    let returns: Vec<f64> = curve
+        .windows(2)
+        .filter_map(|pair| ratio(pair[1].equity - pair[0].equity, pair[0].equity).to_f64())
+        .collect();
+    if returns.len() < 2 || interval.num_milliseconds() <= 0 {
+        return None;
+    }
+    let count = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / count;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (count - 1.0);
+    let std = variance.sqrt();
+    if std == 0.0 {
+        return None;
+    }
+    let periods_per_year =
+        TimeDelta::days(365).num_milliseconds() as f64 / interval.num_milliseconds() as f64;
+    Some(mean / std * periods_per_year.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let curve: Vec<_> = ["100", "120", "90", "110"]
+            .iter()
+            .enumerate()
+            .map(|(hour, equity)| EquityPoint {
+                time: DateTime::UNIX_EPOCH + TimeDelta::hours(hour as i64),
+                equity: equity.parse().unwrap(),
+            })
+            .collect();
+        let report = BacktestReport::new(Vec::new(), curve, TimeDelta::hours(1), Decimal::ZERO);
+        assert_eq!(report.summary.total_return, "0.1".parse().unwrap());
+        assert_eq!(report.summary.max_drawdown, "0.25".parse().unwrap());
+        assert!(report.summary.sharpe_ratio.unwrap() > 0.0);
+    }
+}
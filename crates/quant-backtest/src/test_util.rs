@@ -0,0 +1,24 @@
+//! 测试用的数据构造
+
+use chrono::DateTime;
+use quant_schema::{Exchange, Side, Trade};
+
+pub fn num<T: std::str::FromStr>(value: &str) -> T
+where
+    T::Err: std::fmt::Debug,
+{
+    value.parse().unwrap()
+}
+
+/// BTC-USDT 的成交，`side` 为吃单方向
+pub fn trade(side: Side, price: &str, quantity: &str) -> Trade {
+    Trade {
+        exchange: Exchange::Okex,
+        symbol: "BTC-USDT".to_string(),
+        trade_id: String::new(),
+        price: num(price),
+        quantity: num(quantity),
+        side,
+        timestamp: DateTime::UNIX_EPOCH,
+    }
+}
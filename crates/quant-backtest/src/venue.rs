@@ -0,0 +1,573 @@
+use crate::matching::{MatchEvent, MatchingEngine, SimOrder};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use quant_exchange::TradingVenue;
+use quant_exchange::error::{Error, Result};
+use quant_exchange::schema::okex::order::OrderType as OkexOrderType;
+use quant_marketdata::book::OrderBook;
+use quant_schema::{
+    Balance, Bar, BookUpdate, CanonicalSymbol, Decimal, Exchange, Fill, MarketType, OrderAck,
+    OrderId, OrderRequest, OrderStatus, Position, Price, Quantity, Symbol, Trade,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// 模拟交易所产生的订单事件，由回测引擎转发给 `StrategyRuntime`
+#[derive(Debug, Clone)]
+pub enum VenueEvent {
+    Fill(Fill),
+    /// 订单被拒绝或过期，`order_id` 为交易所订单ID
+    Status {
+        order_id: String,
+        status: OrderStatus,
+        at: DateTime<Utc>,
+    },
+}
+
+/// 已提交、尚未完成的订单
+#[derive(Debug, Clone)]
+struct OpenOrder {
+    order_id: String,
+    request: OrderRequest,
+    order_type: OkexOrderType,
+    /// 到达交易所的时间，之前的行情不参与撮合
+    arrives_at: DateTime<Utc>,
+    arrived: bool,
+    filled: Quantity,
+}
+
+/// 回测和模拟盘用的模拟交易所：接受订单，经过下单延迟后交给各产品的 [`MatchingEngine`] 撮合，
+/// 产生成交和订单状态变化
+///
+/// 订单在提交时间加上延迟之后到达，确认时间也是到达时间；到达前的行情不参与撮合，到达前撤单直接撤销。
+/// 手续费按每笔成交的成交额收取。
+///
+/// 余额只用于初始化持仓视图，不随成交变化。
+#[derive(Debug)]
+pub struct SimulatedExchange {
+    exchange: Exchange,
+    now: DateTime<Utc>,
+    latency: TimeDelta,
+    maker_fee: Decimal,
+    taker_fee: Decimal,
+    balances: Vec<Balance>,
+    symbols: HashMap<String, Symbol>,
+    engines: HashMap<String, MatchingEngine>,
+    /// 未完成的订单，按提交顺序排列
+    orders: Vec<OpenOrder>,
+    next_order_id: u64,
+    next_trade_id: u64,
+    events: VecDeque<VenueEvent>,
+}
+
+impl SimulatedExchange {
+    pub fn new(exchange: Exchange) -> Self {
+        Self {
+            exchange,
+            now: DateTime::UNIX_EPOCH,
+            latency: TimeDelta::zero(),
+            maker_fee: Decimal::ZERO,
+            taker_fee: Decimal::ZERO,
+            balances: Vec::new(),
+            symbols: HashMap::new(),
+            engines: HashMap::new(),
+            orders: Vec::new(),
+            next_order_id: 1,
+            next_trade_id: 1,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// 挂单和吃单的手续费率，按成交额收取，负数为返佣
+    pub fn fees(mut self, maker: Decimal, taker: Decimal) -> Self {
+        self.maker_fee = maker;
+        self.taker_fee = taker;
+        self
+    }
+
+    /// 从提交订单到交易所确认的延迟，默认为 0
+    pub fn latency(mut self, latency: TimeDelta) -> Self {
+        self.latency = latency.max(TimeDelta::zero());
+        self
+    }
+
+    /// 初始余额
+    pub fn balance(mut self, asset: impl Into<String>, amount: Decimal) -> Self {
+        self.balances.push(Balance {
+            asset: asset.into(),
+            free: amount,
+            locked: Decimal::ZERO,
+        });
+        self
+    }
+
+    /// 登记产品信息，用于计算合约的成交额和手续费币种
+    pub fn add_symbol(mut self, symbol: Symbol) -> Self {
+        self.symbols.insert(symbol.exchange_symbol.clone(), symbol);
+        self
+    }
+
+    pub fn exchange(&self) -> &Exchange {
+        &self.exchange
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    /// 推进模拟时钟，先按到达时间撮合期间到达的订单，订单确认和成交都使用模拟时间
+    pub fn set_time(&mut self, now: DateTime<Utc>) {
+        let now = self.now.max(now);
+        self.arrive_until(now);
+        self.now = now;
+    }
+
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.engines.get(symbol).map(MatchingEngine::book)
+    }
+
+    pub fn on_book_update(&mut self, update: &BookUpdate) {
+        let events = self.engine(&update.symbol).on_book_update(update);
+        self.handle(events);
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) {
+        let events = self.engine(&trade.symbol).on_trade(trade);
+        self.handle(events);
+    }
+
+    pub fn on_bar(&mut self, bar: &Bar) {
+        let events = self.engine(&bar.symbol).on_bar(bar);
+        self.handle(events);
+    }
+
+    /// 取出撮合产生的事件
+    pub fn drain_events(&mut self) -> Vec<VenueEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// 按 `request` 的订单类型和有效期提交，对应 OKX 的 `market`、`limit`、`post_only`、`ioc` 和 `fok`
+    ///
+    /// 通用的 `OrderRequest` 表示不了 `optimal_limit_ioc` 和做市商保护类订单，通过 `TradingVenue` 下单时
+    /// 与实盘一样到不了这些类型；需要模拟时直接调用 [`Self::submit_order_type`]。
+    pub fn submit(&mut self, request: &OrderRequest) -> Result<OrderAck> {
+        let order_type = OkexOrderType::from_schema(request.order_type, request.time_in_force);
+        self.submit_order_type(request, order_type)
+    }
+
+    /// 按 OKX 的订单类型提交，`request` 的订单类型和有效期被忽略；`OrderType::Other` 不能用于下单
+    pub fn submit_order_type(
+        &mut self,
+        request: &OrderRequest,
+        order_type: OkexOrderType,
+    ) -> Result<OrderAck> {
+        if order_type == OkexOrderType::Other {
+            return Err(Error::Unsupported("Unrecognized OKX order type"));
+        }
+        if request.quantity <= Quantity::ZERO {
+            return Err(Error::Other("Order quantity must be positive"));
+        }
+        let market = matches!(
+            order_type,
+            OkexOrderType::Market | OkexOrderType::OptimalLimitIoc
+        );
+        if !market && request.price.is_none() {
+            return Err(Error::Other("Limit order requires a price"));
+        }
+        let order_id = self.next_order_id.to_string();
+        self.next_order_id += 1;
+        let arrives_at = self.now + self.latency;
+        self.orders.push(OpenOrder {
+            order_id: order_id.clone(),
+            request: request.clone(),
+            order_type,
+            arrives_at,
+            arrived: false,
+            filled: Quantity::ZERO,
+        });
+        // 没有延迟时立即到达
+        self.arrive_until(self.now);
+        Ok(OrderAck {
+            exchange: self.exchange.clone(),
+            symbol: request.symbol.clone(),
+            order_id,
+            client_order_id: request.client_order_id.clone(),
+            timestamp: arrives_at,
+        })
+    }
+
+    pub fn cancel(&mut self, symbol: &str, order_id: &OrderId) -> Result<OrderAck> {
+        let index = self
+            .orders
+            .iter()
+            .position(|order| {
+                order.request.symbol == symbol
+                    && match order_id {
+                        OrderId::Exchange(id) => order.order_id == *id,
+                        OrderId::Client(id) => order.request.client_order_id.as_ref() == Some(id),
+                    }
+            })
+            .ok_or(Error::Other("Order does not exist"))?;
+        let order = self.orders.remove(index);
+        if order.arrived {
+            self.engine(symbol).cancel(&order.order_id);
+        }
+        Ok(OrderAck {
+            exchange: self.exchange.clone(),
+            symbol: order.request.symbol,
+            order_id: order.order_id,
+            client_order_id: order.request.client_order_id,
+            timestamp: self.now,
+        })
+    }
+
+    /// 登记的产品
+    pub(crate) fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.values()
+    }
+
+    /// 每张合约的面值，现货和未登记的产品为 1
+    pub(crate) fn multiplier(&self, symbol: &str) -> Decimal {
+        self.symbols
+            .get(symbol)
+            .and_then(|symbol| symbol.contract_value.filter(|_| symbol.is_derivative()))
+            .unwrap_or(Decimal::ONE)
+    }
+
+    /// 某个币种的初始余额
+    pub(crate) fn initial_balance(&self, asset: &str) -> Decimal {
+        self.balances
+            .iter()
+            .filter(|balance| balance.asset == asset)
+            .map(Balance::total)
+            .sum()
+    }
+
+    fn engine(&mut self, symbol: &str) -> &mut MatchingEngine {
+        self.engines
+            .entry(symbol.to_string())
+            .or_insert_with(|| MatchingEngine::new(self.exchange.clone(), symbol))
+    }
+
+    /// 按到达时间把 `until` 之前到达的订单交给撮合引擎
+    fn arrive_until(&mut self, until: DateTime<Utc>) {
+        while let Some(order) = self
+            .orders
+            .iter_mut()
+            .filter(|order| !order.arrived && order.arrives_at <= until)
+            .min_by_key(|order| order.arrives_at)
+        {
+            order.arrived = true;
+            self.now = self.now.max(order.arrives_at);
+            let symbol = order.request.symbol.clone();
+            let sim_order = SimOrder::new(
+                order.order_id.clone(),
+                order.request.side,
+                order.order_type,
+                order.request.price,
+                order.request.quantity,
+            );
+            let events = self.engine(&symbol).submit(sim_order);
+            self.handle(events);
+        }
+    }
+
+    fn handle(&mut self, events: Vec<MatchEvent>) {
+        for event in events {
+            match event {
+                MatchEvent::Fill {
+                    order_id,
+                    price,
+                    quantity,
+                    is_maker,
+                } => self.fill(&order_id, price, quantity, is_maker),
+                MatchEvent::Done { order_id, status } => {
+                    self.orders.retain(|order| order.order_id != order_id);
+                    self.events.push_back(VenueEvent::Status {
+                        order_id,
+                        status,
+                        at: self.now,
+                    });
+                }
+            }
+        }
+    }
+
+    fn fill(&mut self, order_id: &str, price: Price, quantity: Quantity, is_maker: bool) {
+        let Some(index) = self
+            .orders
+            .iter()
+            .position(|order| order.order_id == order_id)
+        else {
+            return;
+        };
+        let order = &mut self.orders[index];
+        order.filled += quantity;
+        let request = if order.filled >= order.request.quantity {
+            self.orders.remove(index).request
+        } else {
+            order.request.clone()
+        };
+        let multiplier = self.multiplier(&request.symbol);
+        let rate = if is_maker {
+            self.maker_fee
+        } else {
+            self.taker_fee
+        };
+        // 未登记的产品按现货名称取计价货币
+        let fee_asset = match self.symbols.get(&request.symbol) {
+            Some(symbol) => symbol
+                .settle_asset
+                .clone()
+                .unwrap_or_else(|| symbol.quote_asset.clone()),
+            None => CanonicalSymbol::from_native(&self.exchange, MarketType::Spot, &request.symbol)
+                .map(|canonical| canonical.quote)
+                .unwrap_or_default(),
+        };
+        let trade_id = self.next_trade_id.to_string();
+        self.next_trade_id += 1;
+        self.events.push_back(VenueEvent::Fill(Fill {
+            exchange: self.exchange.clone(),
+            symbol: request.symbol,
+            trade_id,
+            order_id: order_id.to_string(),
+            client_order_id: request.client_order_id,
+            side: request.side,
+            price,
+            quantity,
+            fee: price * quantity * multiplier * rate,
+            fee_asset,
+            is_maker,
+            timestamp: self.now,
+        }));
+    }
+}
+
+/// [`SimulatedExchange`] 的 `TradingVenue` 接口，注册到 `OrderRouter` 后与实盘交易所的用法相同
+///
+/// 模拟交易所由回测引擎和 `OrderRouter` 共享，引擎通过 [`SimulatedVenue::shared`] 推送行情和取出事件。
+#[derive(Debug, Clone)]
+pub struct SimulatedVenue {
+    exchange: Exchange,
+    inner: Arc<Mutex<SimulatedExchange>>,
+}
+
+impl SimulatedVenue {
+    pub fn new(simulator: SimulatedExchange) -> Self {
+        Self {
+            exchange: simulator.exchange.clone(),
+            inner: Arc::new(Mutex::new(simulator)),
+        }
+    }
+
+    pub fn shared(&self) -> Arc<Mutex<SimulatedExchange>> {
+        self.inner.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SimulatedExchange> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl TradingVenue for SimulatedVenue {
+    fn exchange(&self) -> Exchange {
+        self.exchange.clone()
+    }
+
+    async fn submit_order(&mut self, order: &OrderRequest) -> Result<OrderAck> {
+        self.lock().submit(order)
+    }
+
+    async fn cancel(&mut self, symbol: &str, order_id: &OrderId) -> Result<OrderAck> {
+        self.lock().cancel(symbol, order_id)
+    }
+
+    async fn balances(&mut self) -> Result<Vec<Balance>> {
+        Ok(self.lock().balances.clone())
+    }
+
+    /// 持仓由 `Portfolio` 按成交计算，开始时没有持仓
+    async fn positions(&mut self) -> Result<Vec<Position>> {
+        Ok(Vec::new())
+    }
+
+    async fn subscribe_book(&mut self, _symbol: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{num, trade};
+    use quant_schema::{Side, TimeInForce};
+
+    fn fills(simulator: &mut SimulatedExchange) -> Vec<(Price, Quantity, bool)> {
+        simulator
+            .drain_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                VenueEvent::Fill(fill) => Some((fill.price, fill.quantity, fill.is_maker)),
+                VenueEvent::Status { .. } => None,
+            })
+            .collect()
+    }
+
+    fn buy(price: &str) -> OrderRequest {
+        OrderRequest::limit("BTC-USDT", Side::Buy, num("1"), num(price))
+    }
+
+    #[test]
+    fn test_match_on_next_quote() {
+        let mut simulator =
+            SimulatedExchange::new(Exchange::Okex).fees(num("0.0002"), num("0.0005"));
+        simulator
+            .submit(&OrderRequest::market("BTC-USDT", Side::Buy, num("1")))
+            .unwrap();
+        simulator.submit(&buy("99")).unwrap();
+        simulator
+            .submit(&buy("101").time_in_force(TimeInForce::PostOnly))
+            .unwrap();
+        simulator
+            .submit(&buy("98").time_in_force(TimeInForce::Ioc))
+            .unwrap();
+
+        // 市价单按成交价成交，PostOnly 会主动成交被拒绝，IOC 不能成交过期，99 的限价单挂单
+        simulator.on_trade(&trade(Side::Buy, "100", "1"));
+        let events = simulator.drain_events();
+        assert_eq!(events.len(), 3);
+        assert!(
+            matches!(&events[0], VenueEvent::Fill(fill) if fill.price == num("100") && fill.fee == num::<Decimal>("0.05"))
+        );
+        assert!(matches!(
+            &events[1],
+            VenueEvent::Status {
+                status: OrderStatus::Rejected,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &events[2],
+            VenueEvent::Status {
+                status: OrderStatus::Expired,
+                ..
+            }
+        ));
+
+        // 排队位置未知，成交价等于挂单价时两个方向的主动成交都不成交
+        simulator.on_trade(&trade(Side::Buy, "99", "1"));
+        simulator.on_trade(&trade(Side::Sell, "99", "1"));
+        assert!(fills(&mut simulator).is_empty());
+        // 穿过后按挂单价被动成交，数量不超过成交数量
+        simulator.on_trade(&trade(Side::Sell, "98.5", "0.4"));
+        simulator.on_trade(&trade(Side::Buy, "98.5", "1"));
+        assert_eq!(
+            fills(&mut simulator),
+            vec![(num("99"), num("0.4"), true), (num("99"), num("0.6"), true)]
+        );
+
+        simulator.submit(&buy("90")).unwrap();
+        assert!(
+            simulator
+                .cancel("BTC-USDT", &OrderId::Exchange("5".to_string()))
+                .is_ok()
+        );
+        simulator.on_trade(&trade(Side::Buy, "80", "1"));
+        assert!(fills(&mut simulator).is_empty());
+    }
+
+    #[test]
+    fn test_latency() {
+        let start = DateTime::UNIX_EPOCH;
+        let mut simulator =
+            SimulatedExchange::new(Exchange::Okex).latency(TimeDelta::milliseconds(50));
+        simulator.set_time(start);
+        let book = |price: &str, time: DateTime<Utc>| BookUpdate {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            sequence: 1,
+            snapshot: true,
+            bids: vec![(num("99"), num("1"))],
+            asks: vec![(num(price), num("1"))],
+            timestamp: time,
+        };
+        simulator.on_book_update(&book("100", start));
+        let ack = simulator.submit(&buy("100")).unwrap();
+        assert_eq!(ack.timestamp, start + TimeDelta::milliseconds(50));
+
+        // 到达前卖一价上移，订单到达时不能成交，挂单
+        let moved = start + TimeDelta::milliseconds(20);
+        simulator.set_time(moved);
+        simulator.on_book_update(&book("101", moved));
+        assert!(simulator.drain_events().is_empty());
+        simulator.set_time(start + TimeDelta::milliseconds(100));
+        assert!(simulator.drain_events().is_empty());
+
+        // 到达前撤单
+        simulator.submit(&buy("101")).unwrap();
+        assert!(
+            simulator
+                .cancel("BTC-USDT", &OrderId::Exchange("2".to_string()))
+                .is_ok()
+        );
+        simulator.set_time(start + TimeDelta::milliseconds(200));
+        assert!(simulator.drain_events().is_empty());
+
+        // 成交时间为到达时间
+        simulator.submit(&buy("101")).unwrap();
+        simulator.set_time(start + TimeDelta::milliseconds(300));
+        let events = simulator.drain_events();
+        assert!(matches!(
+            &events[..],
+            [VenueEvent::Fill(fill)] if fill.timestamp == start + TimeDelta::milliseconds(250)
+                && fill.price == num("101")
+        ));
+    }
+
+    #[test]
+    fn test_okx_order_types() {
+        let mut simulator = SimulatedExchange::new(Exchange::Okex);
+        simulator.on_book_update(&BookUpdate {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            sequence: 1,
+            snapshot: true,
+            bids: vec![(num("99"), num("1"))],
+            asks: vec![(num("100"), num("1")), (num("101"), num("2"))],
+            timestamp: DateTime::UNIX_EPOCH,
+        });
+
+        // optimal_limit_ioc 不需要价格，按深度逐档成交
+        let market = OrderRequest::market("BTC-USDT", Side::Buy, num("2"));
+        simulator
+            .submit_order_type(&market, OkexOrderType::OptimalLimitIoc)
+            .unwrap();
+        assert_eq!(
+            fills(&mut simulator),
+            vec![(num("100"), num("1"), false), (num("101"), num("1"), false)]
+        );
+
+        // mmp 按限价单挂单，mmp_and_post_only 会主动成交时被拒绝
+        simulator
+            .submit_order_type(&buy("99.5"), OkexOrderType::MarketMakerProtection)
+            .unwrap();
+        simulator
+            .submit_order_type(&buy("101"), OkexOrderType::MmpAndPostOnly)
+            .unwrap();
+        assert!(matches!(
+            &simulator.drain_events()[..],
+            [VenueEvent::Status {
+                status: OrderStatus::Rejected,
+                ..
+            }]
+        ));
+        simulator.on_trade(&trade(Side::Sell, "99.5", "1"));
+        assert_eq!(fills(&mut simulator), vec![(num("99.5"), num("1"), true)]);
+
+        assert!(matches!(
+            simulator.submit_order_type(&buy("99"), OkexOrderType::Other),
+            Err(Error::Unsupported(_))
+        ));
+    }
+}
@@ -53,9 +53,9 @@ pub enum ExchangeConfig {
     Binance(BinanceConfig),
 }
 
-impl Into<Credentials> for ExchangeConfig {
-    fn into(self) -> Credentials {
-        match self {
+impl From<ExchangeConfig> for Credentials {
+    fn from(config: ExchangeConfig) -> Self {
+        match config {
             ExchangeConfig::Okex(okex_config) => okex_config.credentials,
             ExchangeConfig::Binance(binance_config) => binance_config.credentials,
         }
@@ -88,6 +88,8 @@ pub struct BinanceConfig {
     pub testnet_http_urls: Option<Vec<String>>,
     pub testnet_ws_urls: Option<Vec<String>>,
     pub ip_blacks: Vec<String>,
+    /// `recvWindow` in milliseconds for signed requests, defaults to 5000
+    pub recv_window: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,7 +105,7 @@ impl Config {
     /// Get specific exchange configuration by name
     pub fn get_exchange(&self, exchange: &Exchange) -> Option<ExchangeConfig> {
         let exchanges = self.exchanges.lock().unwrap();
-        exchanges.get(&exchange).cloned()
+        exchanges.get(exchange).cloned()
     }
 
     /// Get default exchange configuration
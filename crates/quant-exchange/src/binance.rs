@@ -1 +1,1116 @@
+use crate::constant::binance_endpoints;
+use crate::error::{Error, Result};
+use crate::schema::binance::account::AccountInfo;
+use crate::schema::binance::general::{ApiErrorResponse, ServerTime};
+use crate::schema::binance::order::OrderResponse;
+use crate::schema::binance::user_data::{ListenKey, UserDataEvent};
+use crate::schema::okex::channel::WsChannelType;
+use crate::{
+    ExchangeClient, ExchangeHttpClient, ExchangeWsClientTrait, Signature, TradingVenue, WsReader,
+    WsSink, parse_number, timestamp_from_millis,
+};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use quant_config::{BinanceConfig, Config, Credentials, ExchangeConfig};
+use quant_schema::{
+    Balance, Exchange, OrderAck, OrderId, OrderRequest, OrderType, Position, Side, TimeInForce,
+};
+use reqwest::{Method, Response, header::HeaderMap};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+use tokio::time::{Instant, Interval};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
 
+pub struct BinanceClient<'a> {
+    pub http_client: &'a reqwest::Client,
+    pub config: &'a Config,
+    /// 服务器时间与本地时间的差值（毫秒），由 `sync_server_time` 更新
+    time_offset: AtomicI64,
+}
+
+impl<'a> BinanceClient<'a> {
+    pub fn new(http_client: &'a reqwest::Client, config: &'a Config) -> Self {
+        Self {
+            http_client,
+            config,
+            time_offset: AtomicI64::new(0),
+        }
+    }
+
+    pub fn get_config(&self) -> Result<BinanceConfig> {
+        match self.config.get_exchange(&Exchange::Binance) {
+            Some(ExchangeConfig::Binance(config)) => Ok(config),
+            _ => Err(Error::ConfigError("Exchange::Binance not found in config")),
+        }
+    }
+
+    pub fn credentials(&self) -> Result<Credentials> {
+        Ok(self.get_config()?.credentials)
+    }
+
+    /// 签名请求使用的 `recvWindow`，未配置时使用默认值
+    pub fn recv_window(&self) -> Result<u64> {
+        let recv_window = self
+            .get_config()?
+            .recv_window
+            .unwrap_or(binance_endpoints::DEFAULT_RECV_WINDOW);
+        if recv_window == 0 || recv_window > binance_endpoints::MAX_RECV_WINDOW {
+            return Err(Error::ConfigError(
+                "recvWindow must be between 1 and 60000 milliseconds",
+            ));
+        }
+        Ok(recv_window)
+    }
+
+    /// 当前的服务器时间偏移量（毫秒）
+    pub fn time_offset(&self) -> i64 {
+        self.time_offset.load(Ordering::Relaxed)
+    }
+
+    /// 按服务器时间校准后的毫秒时间戳
+    pub fn timestamp(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis() + self.time_offset()
+    }
+
+    /// 测试连通性
+    pub async fn ping(&self) -> Result<()> {
+        let _: serde_json::Value = self
+            .request(&Method::GET, "/api/v3/ping", None, None::<&()>, false)
+            .await?;
+        Ok(())
+    }
+
+    /// 获取服务器时间
+    pub async fn server_time(&self) -> Result<ServerTime> {
+        let response = self
+            .send_request(&Method::GET, "/api/v3/time", None, None::<&()>, false)
+            .await?;
+        Self::parse_response(response).await
+    }
+
+    /// 同步服务器时间，返回新的偏移量（毫秒）
+    ///
+    /// 以请求往返的中点作为本地时间，减少网络延迟带来的误差。
+    pub async fn sync_server_time(&self) -> Result<i64> {
+        let sent_at = chrono::Utc::now().timestamp_millis();
+        let ServerTime { server_time } = self.server_time().await?;
+        let received_at = chrono::Utc::now().timestamp_millis();
+        let offset = server_time - (sent_at + received_at) / 2;
+        self.time_offset.store(offset, Ordering::Relaxed);
+        Ok(offset)
+    }
+
+    /// 获取账户信息（签名接口）
+    pub async fn account(&self) -> Result<AccountInfo> {
+        self.request(&Method::GET, "/api/v3/account", None, None::<&()>, true)
+            .await
+    }
+
+    /// 发送请求并解析 JSON 响应
+    ///
+    /// 签名请求遇到 `-1021`（时间戳超出 `recvWindow`）时，会重新同步服务器时间并重试一次。
+    pub async fn request<T: Serialize + Sync, R: DeserializeOwned>(
+        &self,
+        method: &Method,
+        path: &str,
+        query_params: Option<&HashMap<String, String>>,
+        body: Option<&T>,
+        authenticated: bool,
+    ) -> Result<R> {
+        let response = self
+            .send_request(method, path, query_params, body, authenticated)
+            .await?;
+        match Self::parse_response(response).await {
+            Err(Error::BinanceApi { code, .. })
+                if authenticated && code == ApiErrorResponse::TIMESTAMP_OUTSIDE_RECV_WINDOW =>
+            {
+                self.sync_server_time().await?;
+                let response = self
+                    .send_request(method, path, query_params, body, authenticated)
+                    .await?;
+                Self::parse_response(response).await
+            }
+            result => result,
+        }
+    }
+
+    /// 创建 listenKey，若已有有效的 listenKey 则返回同一个并延长有效期
+    pub async fn create_listen_key(&self) -> Result<String> {
+        let response = self
+            .send_api_key_request(&Method::POST, "/api/v3/userDataStream", None)
+            .await?;
+        let ListenKey { listen_key } = Self::parse_response(response).await?;
+        Ok(listen_key)
+    }
+
+    /// 延长 listenKey 的有效期至 60 分钟
+    pub async fn keepalive_listen_key(&self, listen_key: &str) -> Result<()> {
+        let response = self
+            .send_api_key_request(&Method::PUT, "/api/v3/userDataStream", Some(listen_key))
+            .await?;
+        let _: serde_json::Value = Self::parse_response(response).await?;
+        Ok(())
+    }
+
+    /// 关闭 listenKey
+    pub async fn close_listen_key(&self, listen_key: &str) -> Result<()> {
+        let response = self
+            .send_api_key_request(&Method::DELETE, "/api/v3/userDataStream", Some(listen_key))
+            .await?;
+        let _: serde_json::Value = Self::parse_response(response).await?;
+        Ok(())
+    }
+
+    /// 用户数据流的 WebSocket 基础地址
+    pub fn ws_base_url(&self) -> Result<String> {
+        let config = self.get_config()?;
+        let use_testnet = config.credentials.use_testnet.unwrap_or(false);
+        let (urls, fallback) = if use_testnet {
+            (
+                config.testnet_ws_urls.unwrap_or_default(),
+                binance_endpoints::TESTNET_WS_BASE_URL,
+            )
+        } else {
+            (config.ws_urls, binance_endpoints::PROD_WS_BASE_URL)
+        };
+        Ok(urls
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| fallback.to_string()))
+    }
+
+    /// 创建用户数据流，调用 `next_event` 时才会建立连接
+    pub fn user_data_stream(&self) -> BinanceUserDataStream<'_> {
+        BinanceUserDataStream::new(self)
+    }
+
+    /// `USER_STREAM` 类接口只需要 `X-MBX-APIKEY` 头部，不需要签名
+    async fn send_api_key_request(
+        &self,
+        method: &Method,
+        path: &str,
+        listen_key: Option<&str>,
+    ) -> Result<Response> {
+        let mut url = format!("{}{}", self.base_url()?, path);
+        if let Some(listen_key) = listen_key {
+            let query = serde_urlencoded::to_string([("listenKey", listen_key)])?;
+            url = format!("{}?{}", url, query);
+        }
+        let response = self
+            .http_client()
+            .request(method.clone(), &url)
+            .headers(self.sign_request(method, path, "")?)
+            .send()
+            .await?;
+        Ok(response)
+    }
+
+    /// 响应没有 `transactTime` 时以收到响应的时间为准
+    fn order_ack(response: OrderResponse) -> Result<OrderAck> {
+        Ok(OrderAck {
+            exchange: Exchange::Binance,
+            symbol: response.symbol,
+            order_id: response.order_id.to_string(),
+            client_order_id: Some(response.client_order_id).filter(|id| !id.is_empty()),
+            timestamp: match response.transact_time {
+                Some(millis) => timestamp_from_millis(millis)?,
+                None => chrono::Utc::now(),
+            },
+        })
+    }
+
+    async fn parse_response<R: DeserializeOwned>(response: Response) -> Result<R> {
+        let status = response.status();
+        let text = response.text().await?;
+        if status.is_success() {
+            return Ok(serde_json::from_str(&text)?);
+        }
+        match serde_json::from_str::<ApiErrorResponse>(&text) {
+            Ok(ApiErrorResponse { code, msg }) => Err(Error::BinanceApi { code, msg }),
+            Err(_) => Err(Error::BinanceApi {
+                code: -i64::from(status.as_u16()),
+                msg: text,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for BinanceClient<'_> {
+    fn exchange_client_headers(&self) -> HeaderMap {
+        HeaderMap::new()
+    }
+
+    fn base_url(&self) -> Result<String> {
+        let config = self.get_config()?;
+        let use_testnet = config.credentials.use_testnet.unwrap_or(false);
+        let (urls, fallback) = if use_testnet {
+            (
+                config.testnet_http_urls.unwrap_or_default(),
+                binance_endpoints::TESTNET_REST_BASE_URL,
+            )
+        } else {
+            (config.http_urls, binance_endpoints::PROD_REST_BASE_URL)
+        };
+        Ok(urls
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| fallback.to_string()))
+    }
+
+    /// Binance 的签名放在查询参数中，这里只返回 `X-MBX-APIKEY` 头部
+    fn sign_request(&self, _method: &Method, _path: &str, _body: &str) -> Result<HeaderMap> {
+        let credentials = self.credentials()?;
+        let mut headers = self.exchange_client_headers();
+        headers.insert("X-MBX-APIKEY", credentials.api_key.parse()?);
+        Ok(headers)
+    }
+}
+
+#[async_trait]
+impl ExchangeHttpClient for BinanceClient<'_> {
+    async fn login(&self) -> Result<()> {
+        self.sync_server_time().await?;
+        Ok(())
+    }
+
+    fn http_client(&self) -> &reqwest::Client {
+        self.http_client
+    }
+
+    /// Binance 的所有参数都以查询字符串发送，签名请求追加
+    /// `recvWindow`、`timestamp` 和对完整查询字符串的 `signature`
+    async fn send_request<T: Serialize + Sync>(
+        &self,
+        method: &Method,
+        path: &str,
+        query_params: Option<&HashMap<String, String>>,
+        body: Option<&T>,
+        authenticated: bool,
+    ) -> Result<Response> {
+        let mut params: Vec<String> = Vec::new();
+        if let Some(query_params) = query_params
+            && !query_params.is_empty()
+        {
+            params.push(serde_urlencoded::to_string(query_params)?);
+        }
+        if let Some(body) = body {
+            let form = serde_urlencoded::to_string(body)?;
+            if !form.is_empty() {
+                params.push(form);
+            }
+        }
+
+        let mut headers = self.exchange_client_headers();
+        if authenticated {
+            params.push(format!("recvWindow={}", self.recv_window()?));
+            params.push(format!("timestamp={}", self.timestamp()));
+            let query = params.join("&");
+            let signature = self.gen_signature(method, path, &query, "")?;
+            params.push(format!("signature={}", signature));
+            headers.extend(self.sign_request(method, path, &query)?);
+        }
+
+        let mut url = format!("{}{}", self.base_url()?, path);
+        if !params.is_empty() {
+            url = format!("{}?{}", url, params.join("&"));
+        }
+
+        let response = self
+            .http_client()
+            .request(method.clone(), &url)
+            .headers(headers)
+            .send()
+            .await?;
+        Ok(response)
+    }
+}
+
+impl Signature for BinanceClient<'_> {
+    fn secret(&self) -> Result<String> {
+        Ok(self.credentials()?.secret_key)
+    }
+
+    /// Binance 只对 `totalParams`（查询字符串 + 请求体）签名
+    fn merged_string(
+        &self,
+        _method: &Method,
+        _path: &str,
+        body: &str,
+        _timestamp: &str,
+    ) -> Result<String> {
+        Ok(body.to_string())
+    }
+
+    /// Binance 使用十六进制编码的 HMAC-SHA256
+    fn gen_signature(
+        &self,
+        method: &Method,
+        path: &str,
+        body: &str,
+        timestamp: &str,
+    ) -> Result<String> {
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(self.secret()?.as_bytes())?;
+        mac.update(
+            self.merged_string(method, path, body, timestamp)?
+                .as_bytes(),
+        );
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+/// Binance 用户数据流
+///
+/// 负责 listenKey 的整个生命周期：首次连接时创建 listenKey，按固定间隔延长有效期，
+/// 在 listenKey 过期或连接断开时重新创建并重连。
+pub struct BinanceUserDataStream<'a> {
+    client: &'a BinanceClient<'a>,
+    listen_key: Option<String>,
+    sink: Option<WsSink>,
+    reader: Option<WsReader>,
+    keepalive_interval: Duration,
+    keepalive: Interval,
+}
+
+enum StreamAction {
+    Keepalive,
+    Message(Option<std::result::Result<Message, tokio_tungstenite::tungstenite::Error>>),
+}
+
+impl<'a> BinanceUserDataStream<'a> {
+    pub fn new(client: &'a BinanceClient<'a>) -> Self {
+        let keepalive_interval = Duration::from_secs(binance_endpoints::LISTEN_KEY_KEEPALIVE_SECS);
+        Self {
+            client,
+            listen_key: None,
+            sink: None,
+            reader: None,
+            keepalive_interval,
+            keepalive: Self::keepalive_timer(keepalive_interval),
+        }
+    }
+
+    /// 设置 listenKey 的续期间隔
+    pub fn with_keepalive_interval(mut self, keepalive_interval: Duration) -> Self {
+        self.keepalive_interval = keepalive_interval;
+        self.keepalive = Self::keepalive_timer(keepalive_interval);
+        self
+    }
+
+    /// 当前使用的 listenKey
+    pub fn listen_key(&self) -> Option<&str> {
+        self.listen_key.as_deref()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.reader.is_some()
+    }
+
+    /// 创建新的 listenKey 并连接用户数据流
+    pub async fn connect(&mut self) -> Result<()> {
+        self.listen_key = None;
+        let (sink, reader) = self.get_ws_stream(WsChannelType::Private).await?;
+        self.sink = Some(sink);
+        self.reader = Some(reader);
+        Ok(())
+    }
+
+    /// 断开当前连接，重新创建 listenKey 并连接
+    pub async fn reconnect(&mut self) -> Result<()> {
+        if let Some(mut sink) = self.sink.take() {
+            let _ = sink.close().await;
+        }
+        self.reader = None;
+        self.connect().await
+    }
+
+    /// 等待下一个用户数据事件
+    ///
+    /// 期间会按间隔延长 listenKey；收到 `listenKeyExpired` 或连接断开时自动重连，
+    /// 这些内部事件不会返回给调用方。
+    pub async fn next_event(&mut self) -> Result<UserDataEvent> {
+        loop {
+            if self.reader.is_none() {
+                self.connect().await?;
+            }
+            let action = match self.reader.as_mut() {
+                Some(reader) => tokio::select! {
+                    _ = self.keepalive.tick() => StreamAction::Keepalive,
+                    message = reader.next() => StreamAction::Message(message),
+                },
+                None => continue,
+            };
+
+            match action {
+                StreamAction::Keepalive => self.keepalive_listen_key().await?,
+                StreamAction::Message(Some(Ok(Message::Text(text)))) => {
+                    match serde_json::from_str::<UserDataEvent>(&text)? {
+                        UserDataEvent::ListenKeyExpired(_) => self.reconnect().await?,
+                        event => return Ok(event),
+                    }
+                }
+                StreamAction::Message(Some(Ok(Message::Ping(payload)))) => {
+                    if let Some(sink) = self.sink.as_mut() {
+                        sink.send(Message::Pong(payload)).await?;
+                    }
+                }
+                StreamAction::Message(Some(Ok(Message::Close(_))))
+                | StreamAction::Message(Some(Err(_)))
+                | StreamAction::Message(None) => self.reconnect().await?,
+                StreamAction::Message(Some(Ok(_))) => {}
+            }
+        }
+    }
+
+    /// 关闭连接并删除 listenKey
+    pub async fn close(&mut self) -> Result<()> {
+        if let Some(mut sink) = self.sink.take() {
+            let _ = sink.close().await;
+        }
+        self.reader = None;
+        if let Some(listen_key) = self.listen_key.take() {
+            self.client.close_listen_key(&listen_key).await?;
+        }
+        Ok(())
+    }
+
+    /// 延长 listenKey，若 listenKey 已失效则重新创建并重连
+    async fn keepalive_listen_key(&mut self) -> Result<()> {
+        let Some(listen_key) = self.listen_key.clone() else {
+            return Ok(());
+        };
+        match self.client.keepalive_listen_key(&listen_key).await {
+            Err(Error::BinanceApi { code, .. })
+                if code == ApiErrorResponse::LISTEN_KEY_NOT_EXIST =>
+            {
+                self.reconnect().await
+            }
+            result => result,
+        }
+    }
+
+    fn keepalive_timer(period: Duration) -> Interval {
+        tokio::time::interval_at(Instant::now() + period, period)
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for BinanceUserDataStream<'_> {
+    fn exchange_client_headers(&self) -> HeaderMap {
+        self.client.exchange_client_headers()
+    }
+
+    fn base_url(&self) -> Result<String> {
+        self.client.base_url()
+    }
+
+    fn sign_request(&self, method: &Method, path: &str, body: &str) -> Result<HeaderMap> {
+        self.client.sign_request(method, path, body)
+    }
+}
+
+/// 用户数据流只有私有频道，listenKey 既是地址的一部分也是凭证，不需要登录消息
+#[async_trait]
+impl ExchangeWsClientTrait for BinanceUserDataStream<'_> {
+    fn request(&self, channel: WsChannelType) -> Result<Request> {
+        if channel != WsChannelType::Private {
+            return Err(Error::Unsupported("Binance public streams"));
+        }
+        let listen_key = self
+            .listen_key
+            .as_deref()
+            .ok_or(Error::Other("listenKey has not been created"))?;
+        let url = format!("{}/{}", self.client.ws_base_url()?, listen_key);
+        Ok(Request::builder().uri(url).body(())?)
+    }
+
+    fn login_request(&self) -> Result<String> {
+        Err(Error::Unsupported("Binance WebSocket login"))
+    }
+
+    /// 没有 listenKey 时先创建，连接后重新开始续期计时；返回的连接由调用方读取
+    async fn get_ws_stream(&mut self, channel: WsChannelType) -> Result<(WsSink, WsReader)> {
+        if channel == WsChannelType::Private && self.listen_key.is_none() {
+            self.listen_key = Some(self.client.create_listen_key().await?);
+        }
+        let request = self.request(channel)?;
+        let (ws_stream, _) = tokio_tungstenite::connect_async(request.uri()).await?;
+        self.keepalive = Self::keepalive_timer(self.keepalive_interval);
+        Ok(ws_stream.split())
+    }
+
+    fn is_connected(&self) -> bool {
+        BinanceUserDataStream::is_connected(self)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        BinanceUserDataStream::close(self).await
+    }
+
+    async fn reconnect(&mut self) -> Result<()> {
+        BinanceUserDataStream::reconnect(self).await
+    }
+
+    fn set_connected_state(&mut self, state: bool) {
+        if !state {
+            self.sink = None;
+            self.reader = None;
+        }
+    }
+}
+
+#[async_trait]
+impl TradingVenue for BinanceClient<'_> {
+    fn exchange(&self) -> Exchange {
+        Exchange::Binance
+    }
+
+    async fn submit_order(&mut self, order: &OrderRequest) -> Result<OrderAck> {
+        if order.reduce_only {
+            return Err(Error::Unsupported("reduce-only orders on Binance spot"));
+        }
+        let side = match order.side {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        };
+        let mut params = HashMap::from([
+            ("symbol".to_string(), order.symbol.clone()),
+            ("side".to_string(), side.to_string()),
+            ("quantity".to_string(), order.quantity.to_string()),
+            ("newOrderRespType".to_string(), "ACK".to_string()),
+        ]);
+        let order_type = match (order.order_type, order.time_in_force) {
+            (OrderType::Market, _) => "MARKET",
+            (OrderType::Limit, TimeInForce::PostOnly) => "LIMIT_MAKER",
+            (OrderType::Limit, time_in_force) => {
+                let time_in_force = match time_in_force {
+                    TimeInForce::Ioc => "IOC",
+                    TimeInForce::Fok => "FOK",
+                    _ => "GTC",
+                };
+                params.insert("timeInForce".to_string(), time_in_force.to_string());
+                "LIMIT"
+            }
+        };
+        params.insert("type".to_string(), order_type.to_string());
+        if order.order_type == OrderType::Limit {
+            let price = order
+                .price
+                .ok_or(Error::Other("Limit orders require a price"))?;
+            params.insert("price".to_string(), price.to_string());
+        }
+        if let Some(client_order_id) = &order.client_order_id {
+            params.insert("newClientOrderId".to_string(), client_order_id.clone());
+        }
+        let response: OrderResponse = self
+            .request(
+                &Method::POST,
+                "/api/v3/order",
+                Some(&params),
+                None::<&()>,
+                true,
+            )
+            .await?;
+        Self::order_ack(response)
+    }
+
+    async fn cancel(&mut self, symbol: &str, order_id: &OrderId) -> Result<OrderAck> {
+        let mut params = HashMap::from([("symbol".to_string(), symbol.to_string())]);
+        match order_id {
+            OrderId::Exchange(id) => params.insert("orderId".to_string(), id.clone()),
+            OrderId::Client(id) => params.insert("origClientOrderId".to_string(), id.clone()),
+        };
+        let mut response: OrderResponse = self
+            .request(
+                &Method::DELETE,
+                "/api/v3/order",
+                Some(&params),
+                None::<&()>,
+                true,
+            )
+            .await?;
+        // 撤单响应的 `clientOrderId` 是撤单请求本身的 id
+        if let Some(orig_client_order_id) = response.orig_client_order_id.take() {
+            response.client_order_id = orig_client_order_id;
+        }
+        Self::order_ack(response)
+    }
+
+    async fn balances(&mut self) -> Result<Vec<Balance>> {
+        let mut balances = Vec::new();
+        for balance in self.account().await?.balances {
+            let balance = Balance {
+                asset: balance.asset,
+                free: parse_number(&balance.free)?,
+                locked: parse_number(&balance.locked)?,
+            };
+            if !balance.total().is_zero() {
+                balances.push(balance);
+            }
+        }
+        Ok(balances)
+    }
+
+    /// 现货账户没有持仓
+    async fn positions(&mut self) -> Result<Vec<Position>> {
+        Ok(Vec::new())
+    }
+
+    async fn subscribe_book(&mut self, _symbol: &str) -> Result<()> {
+        Err(Error::Unsupported("Binance order book stream"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::schema::binance::user_data::UserDataEvent;
+    use crate::test_util::spawn_push_server;
+    use quant_config::GlobalConfig;
+    use std::sync::Mutex;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    const API_KEY: &str = "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A";
+    const SECRET_KEY: &str = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+
+    fn mock_config(base_url: &str, ws_url: &str) -> Config {
+        let binance = BinanceConfig {
+            credentials: Credentials {
+                api_key: API_KEY.to_string(),
+                secret_key: SECRET_KEY.to_string(),
+                passphrase: String::new(),
+                use_testnet: Some(false),
+                protocol: Some("https".to_string()),
+            },
+            http_urls: vec![base_url.to_string()],
+            ws_urls: vec![ws_url.to_string()],
+            timeout: None,
+            retry_count: None,
+            retry_delay: None,
+            testnet_http_urls: None,
+            testnet_ws_urls: None,
+            ip_blacks: vec![],
+            recv_window: Some(5000),
+        };
+        Config {
+            exchanges: Mutex::new(HashMap::from([(
+                Exchange::Binance,
+                ExchangeConfig::Binance(binance),
+            )])),
+            default_exchange: Some(Exchange::Binance),
+            global_config: GlobalConfig {
+                timeout: 5000,
+                retry_count: 3,
+                log_level: None,
+                retry_delay: 1000,
+                use_testnet: false,
+                protocol: None,
+            },
+        }
+    }
+
+    /// 校验查询字符串中的 `signature` 是否与其余参数匹配
+    struct ValidSignature;
+
+    impl wiremock::Match for ValidSignature {
+        fn matches(&self, request: &Request) -> bool {
+            let Some((payload, signature)) = request
+                .url
+                .query()
+                .and_then(|query| query.rsplit_once("&signature="))
+            else {
+                return false;
+            };
+            let mut mac = Hmac::<sha2::Sha256>::new_from_slice(SECRET_KEY.as_bytes()).unwrap();
+            mac.update(payload.as_bytes());
+            hex::encode(mac.finalize().into_bytes()) == signature
+        }
+    }
+
+    fn account_body() -> serde_json::Value {
+        serde_json::json!({
+            "makerCommission": 15,
+            "takerCommission": 15,
+            "canTrade": true,
+            "canWithdraw": true,
+            "canDeposit": true,
+            "updateTime": 123456789,
+            "accountType": "SPOT",
+            "balances": [
+                {"asset": "BTC", "free": "4723846.89208129", "locked": "0.00000000"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_signature_known_answer() {
+        let config = mock_config("http://127.0.0.1", "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        let signature = client
+            .gen_signature(&Method::POST, "/api/v3/order", query, "")
+            .unwrap();
+        assert_eq!(
+            signature,
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_server_time() {
+        let server = MockServer::start().await;
+        let server_time = chrono::Utc::now().timestamp_millis() + 60_000;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/time"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "serverTime": server_time
+            })))
+            .mount(&server)
+            .await;
+
+        let config = mock_config(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let offset = client.sync_server_time().await.unwrap();
+        assert!((offset - 60_000).abs() < 5_000, "offset = {}", offset);
+        assert_eq!(client.time_offset(), offset);
+    }
+
+    #[tokio::test]
+    async fn test_signed_account_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .and(header("X-MBX-APIKEY", API_KEY))
+            .and(query_param("recvWindow", "5000"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(account_body()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = mock_config(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let account = client.account().await.unwrap();
+        assert_eq!(account.account_type, "SPOT");
+        assert_eq!(account.balances[0].asset, "BTC");
+    }
+
+    #[tokio::test]
+    async fn test_resync_on_recv_window_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "code": -1021,
+                "msg": "Timestamp for this request is outside of the recvWindow."
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/time"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "serverTime": chrono::Utc::now().timestamp_millis()
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(account_body()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = mock_config(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let account = client.account().await.unwrap();
+        assert!(account.can_trade);
+    }
+
+    #[tokio::test]
+    async fn test_api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "code": -2015,
+                "msg": "Invalid API-key, IP, or permissions for action."
+            })))
+            .mount(&server)
+            .await;
+
+        let config = mock_config(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        match client.account().await {
+            Err(Error::BinanceApi { code, .. }) => assert_eq!(code, -2015),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    async fn mount_listen_key(server: &MockServer, listen_key: &str, times: u64) {
+        Mock::given(method("POST"))
+            .and(path("/api/v3/userDataStream"))
+            .and(header("X-MBX-APIKEY", API_KEY))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "listenKey": listen_key
+            })))
+            .up_to_n_times(times)
+            .expect(times)
+            .mount(server)
+            .await;
+    }
+
+    fn execution_report() -> String {
+        serde_json::json!({
+            "e": "executionReport", "E": 1499405658658i64, "s": "ETHBTC",
+            "c": "mUvoqJxFIILMdfAW5iGSOW", "S": "BUY", "o": "LIMIT", "f": "GTC",
+            "q": "1.00000000", "p": "0.10264410", "x": "NEW", "X": "NEW", "r": "NONE",
+            "i": 4293153, "l": "0.00000000", "z": "0.00000000", "L": "0.00000000",
+            "n": "0", "N": null, "T": 1499405658657i64, "t": -1, "m": false
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_listen_key_rest_lifecycle() {
+        let server = MockServer::start().await;
+        mount_listen_key(
+            &server,
+            "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1",
+            1,
+        )
+        .await;
+        for http_method in ["PUT", "DELETE"] {
+            Mock::given(method(http_method))
+                .and(path("/api/v3/userDataStream"))
+                .and(header("X-MBX-APIKEY", API_KEY))
+                .and(query_param(
+                    "listenKey",
+                    "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1",
+                ))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let config = mock_config(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let listen_key = client.create_listen_key().await.unwrap();
+        client.keepalive_listen_key(&listen_key).await.unwrap();
+        client.close_listen_key(&listen_key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream_events() {
+        let server = MockServer::start().await;
+        mount_listen_key(&server, "listen-key-1", 1).await;
+        let balance = serde_json::json!({
+            "e": "outboundAccountPosition", "E": 1564034571105i64, "u": 1564034571073i64,
+            "B": [{"a": "ETH", "f": "10000.000000", "l": "0.000000"}]
+        })
+        .to_string();
+        let ws_url = spawn_push_server(vec![vec![
+            (Duration::ZERO, execution_report()),
+            (Duration::ZERO, balance),
+        ]])
+        .await;
+
+        let config = mock_config(&server.uri(), &ws_url);
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let mut stream = client.user_data_stream();
+
+        match stream.next_event().await.unwrap() {
+            UserDataEvent::ExecutionReport(report) => {
+                assert_eq!(report.symbol, "ETHBTC");
+                assert_eq!(report.order_id, 4293153);
+                assert_eq!(report.execution_type, "NEW");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        match stream.next_event().await.unwrap() {
+            UserDataEvent::AccountPosition(position) => {
+                assert_eq!(position.balances[0].asset, "ETH");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert_eq!(stream.listen_key(), Some("listen-key-1"));
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream_ws_client_trait() {
+        let server = MockServer::start().await;
+        mount_listen_key(&server, "listen-key-1", 1).await;
+        let ws_url = spawn_push_server(vec![vec![(Duration::ZERO, execution_report())]]).await;
+
+        let config = mock_config(&server.uri(), &ws_url);
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let mut stream = client.user_data_stream();
+        assert!(stream.get_ws_stream(WsChannelType::Public).await.is_err());
+
+        let (_sink, mut reader) = stream.get_ws_stream(WsChannelType::Private).await.unwrap();
+        assert_eq!(stream.listen_key(), Some("listen-key-1"));
+        let request = stream.request(WsChannelType::Private).unwrap();
+        assert_eq!(request.uri().path(), "/listen-key-1");
+        match reader.next().await {
+            Some(Ok(Message::Text(text))) => assert!(matches!(
+                serde_json::from_str::<UserDataEvent>(&text).unwrap(),
+                UserDataEvent::ExecutionReport(_)
+            )),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream_recreates_expired_key() {
+        let server = MockServer::start().await;
+        mount_listen_key(&server, "listen-key-1", 1).await;
+        mount_listen_key(&server, "listen-key-2", 1).await;
+        let expired = serde_json::json!({
+            "e": "listenKeyExpired", "E": 1576653824250i64, "listenKey": "listen-key-1"
+        })
+        .to_string();
+        let balance_update = serde_json::json!({
+            "e": "balanceUpdate", "E": 1573200697110i64, "a": "BTC",
+            "d": "100.00000000", "T": 1573200697068i64
+        })
+        .to_string();
+        let ws_url = spawn_push_server(vec![
+            vec![(Duration::ZERO, expired)],
+            vec![(Duration::ZERO, balance_update)],
+        ])
+        .await;
+
+        let config = mock_config(&server.uri(), &ws_url);
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let mut stream = client.user_data_stream();
+
+        match stream.next_event().await.unwrap() {
+            UserDataEvent::BalanceUpdate(update) => assert_eq!(update.delta, "100.00000000"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert_eq!(stream.listen_key(), Some("listen-key-2"));
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream_keepalive() {
+        let server = MockServer::start().await;
+        mount_listen_key(&server, "listen-key-1", 1).await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v3/userDataStream"))
+            .and(query_param("listenKey", "listen-key-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1..)
+            .mount(&server)
+            .await;
+        let ws_url =
+            spawn_push_server(vec![vec![(Duration::from_millis(300), execution_report())]]).await;
+
+        let config = mock_config(&server.uri(), &ws_url);
+        let http_client = reqwest::Client::new();
+        let client = BinanceClient::new(&http_client, &config);
+        let mut stream = client
+            .user_data_stream()
+            .with_keepalive_interval(Duration::from_millis(50));
+
+        let event = stream.next_event().await.unwrap();
+        assert!(matches!(event, UserDataEvent::ExecutionReport(_)));
+    }
+
+    #[tokio::test]
+    async fn test_trading_venue() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v3/order"))
+            .and(query_param("symbol", "BTCUSDT"))
+            .and(query_param("side", "BUY"))
+            .and(query_param("type", "LIMIT"))
+            .and(query_param("timeInForce", "IOC"))
+            .and(query_param("quantity", "0.01"))
+            .and(query_param("price", "25000"))
+            .and(query_param("newClientOrderId", "b15"))
+            .and(header("X-MBX-APIKEY", API_KEY))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "symbol": "BTCUSDT", "orderId": 28, "orderListId": -1,
+                "clientOrderId": "b15", "transactTime": 1507725176595i64
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v3/order"))
+            .and(query_param("origClientOrderId", "b15"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "symbol": "BTCUSDT", "origClientOrderId": "b15", "orderId": 28,
+                "orderListId": -1, "clientOrderId": "cancelMyOrder1",
+                "transactTime": 1684804350068i64, "status": "CANCELED"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "makerCommission": 15, "takerCommission": 15, "canTrade": true,
+                "canWithdraw": true, "canDeposit": true, "updateTime": 123456789,
+                "accountType": "SPOT",
+                "balances": [
+                    {"asset": "BTC", "free": "4723846.89208129", "locked": "0.00000000"},
+                    {"asset": "LTC", "free": "0.00000000", "locked": "0.00000000"}
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = mock_config(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let mut client = BinanceClient::new(&http_client, &config);
+        let venue: &mut dyn TradingVenue = &mut client;
+        assert_eq!(venue.exchange(), Exchange::Binance);
+
+        let order = OrderRequest::limit(
+            "BTCUSDT",
+            Side::Buy,
+            "0.01".parse().unwrap(),
+            "25000".parse().unwrap(),
+        )
+        .time_in_force(TimeInForce::Ioc)
+        .client_order_id("b15");
+        let ack = venue.submit_order(&order).await.unwrap();
+        assert_eq!(ack.order_id, "28");
+        assert_eq!(ack.timestamp.timestamp_millis(), 1507725176595);
+
+        let ack = venue
+            .cancel("BTCUSDT", &OrderId::Client("b15".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(ack.client_order_id.as_deref(), Some("b15"));
+
+        let balances = venue.balances().await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].asset, "BTC");
+        assert!(venue.positions().await.unwrap().is_empty());
+        assert!(matches!(
+            venue.subscribe_book("BTCUSDT").await,
+            Err(Error::Unsupported(_))
+        ));
+    }
+}
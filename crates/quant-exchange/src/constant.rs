@@ -24,3 +24,48 @@ pub mod okx_endpoints {
     /// 模拟盘 WebSocket 业务频道
     pub const DEMO_WS_BUSINESS_URL: &str = "wss://wspap.okx.com:8443/ws/v5/business";
 }
+
+/// Binance API 端点常量
+pub mod binance_endpoints {
+    /// 生产环境 REST API 基础地址
+    pub const PROD_REST_BASE_URL: &str = "https://api.binance.com";
+
+    /// 生产环境 WebSocket 地址
+    pub const PROD_WS_BASE_URL: &str = "wss://stream.binance.com:9443/ws";
+
+    /// 测试网 REST API 基础地址
+    pub const TESTNET_REST_BASE_URL: &str = "https://testnet.binance.vision";
+
+    /// 测试网 WebSocket 地址
+    pub const TESTNET_WS_BASE_URL: &str = "wss://stream.testnet.binance.vision/ws";
+
+    /// 默认的 `recvWindow`（毫秒）
+    pub const DEFAULT_RECV_WINDOW: u64 = 5000;
+
+    /// Binance 允许的最大 `recvWindow`（毫秒）
+    pub const MAX_RECV_WINDOW: u64 = 60000;
+
+    /// listenKey 有效期为 60 分钟，官方建议每 30 分钟延长一次（秒）
+    pub const LISTEN_KEY_KEEPALIVE_SECS: u64 = 30 * 60;
+}
+
+/// WebSocket 连接相关的默认参数
+pub mod websocket {
+    /// 重连退避的最大等待时间（毫秒）
+    pub const MAX_RECONNECT_DELAY_MS: u64 = 30_000;
+
+    /// 超过该时间没有收到任何消息，即认为连接已失效（秒）
+    pub const DEFAULT_STALL_TIMEOUT_SECS: u64 = 30;
+
+    /// 发送 `ping` 的间隔（秒），需小于 OKX 的 30 秒空闲断开时间
+    pub const DEFAULT_PING_INTERVAL_SECS: u64 = 25;
+
+    /// 发送 `ping` 后等待 `pong` 的超时时间（秒）
+    pub const DEFAULT_PONG_TIMEOUT_SECS: u64 = 10;
+
+    /// WebSocket 交易请求在 `expTime` 之后继续等待响应的时间（毫秒）
+    pub const ORDER_RESPONSE_GRACE_MS: u64 = 1000;
+
+    /// 批量下单一次最多的订单数量
+    pub const MAX_BATCH_ORDERS: usize = 20;
+}
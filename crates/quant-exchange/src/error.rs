@@ -19,7 +19,99 @@ pub enum Error {
     #[error("{0}")]
     Other(&'static str),
     #[error("WebSocket error: {0}")]
-    WsError(#[from] tokio_tungstenite::tungstenite::Error),
+    WsError(Box<tokio_tungstenite::tungstenite::Error>),
     #[error(transparent)]
     WSHTTPError(#[from] tokio_tungstenite::tungstenite::http::Error),
+    #[error(transparent)]
+    UrlEncodeError(#[from] serde_urlencoded::ser::Error),
+    #[error("Binance API error {code}: {msg}")]
+    BinanceApi { code: i64, msg: String },
+    /// `id` 为失败的订阅或取消订阅请求的 `id`
+    #[error("OKX subscription {id} failed {code}: {msg}")]
+    Subscription {
+        id: String,
+        code: String,
+        msg: String,
+    },
+    #[error("WebSocket reconnect failed after {0} attempts")]
+    ReconnectExhausted(u32),
+    #[error("OKX API error {code}: {msg}")]
+    Api { code: String, msg: String },
+    #[error(transparent)]
+    Symbol(#[from] quant_schema::SymbolError),
+    #[error("Not supported: {0}")]
+    Unsupported(&'static str),
+    #[error("Timed out waiting for response to request {0}")]
+    RequestTimeout(String),
+    #[error("OKX login failed ({reason:?}) {code}: {msg}")]
+    Login {
+        reason: LoginFailure,
+        code: String,
+        msg: String,
+    },
+}
+
+/// OKX 接口错误码的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// 请求过于频繁
+    RateLimit,
+    /// 余额不足
+    InsufficientBalance,
+    /// 产品不存在或不匹配
+    InvalidInstrument,
+    /// API Key、签名、时间戳或 Passphrase 错误
+    Authentication,
+    /// 其它错误
+    Other,
+}
+
+impl ApiErrorKind {
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "50011" | "50040" | "50061" => ApiErrorKind::RateLimit,
+            "51008" | "51119" | "51127" | "58350" => ApiErrorKind::InsufficientBalance,
+            "51001" | "51002" => ApiErrorKind::InvalidInstrument,
+            "50102" | "50103" | "50104" | "50105" | "50111" | "50113" => {
+                ApiErrorKind::Authentication
+            }
+            _ => ApiErrorKind::Other,
+        }
+    }
+}
+
+impl Error {
+    /// `Error::Api` 对应的错误分类，其它错误返回 `None`
+    pub fn api_kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            Error::Api { code, .. } => Some(ApiErrorKind::from_code(code)),
+            _ => None,
+        }
+    }
+
+    /// 只影响单条 WebSocket 消息的错误：消息无法解析，或订阅请求被拒绝；连接本身仍然可用
+    pub fn is_message_error(&self) -> bool {
+        matches!(self, Error::SerdeError(_) | Error::Subscription { .. })
+    }
+}
+
+/// WebSocket 登录失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginFailure {
+    /// API Key 不存在或已失效
+    InvalidApiKey,
+    /// Passphrase 错误
+    InvalidPassphrase,
+    /// 本地时间与服务器时间相差过大
+    TimestampSkew,
+    /// 签名错误，通常是 Secret Key 不正确
+    InvalidSignature,
+    /// 超时或其它原因
+    Other,
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WsError(Box::new(err))
+    }
 }
@@ -0,0 +1,150 @@
+//! WebSocket 心跳
+//!
+//! OKX 在 30 秒内没有数据交互时会断开连接，客户端需要定时发送文本 `ping` 并等待 `pong`。
+//! 这里只维护计时和延迟状态，由连接的读循环负责实际收发。
+
+use crate::constant::websocket;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// 心跳计时器到期后需要执行的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeartbeatAction {
+    /// 还没到时间
+    Idle,
+    /// 需要发送 `ping`
+    SendPing,
+    /// 超时未收到 `pong`，连接已失效
+    Dead,
+}
+
+#[derive(Debug)]
+pub struct Heartbeat {
+    ping_interval: Duration,
+    pong_timeout: Duration,
+    last_ping_at: Instant,
+    ping_sent_at: Option<Instant>,
+    latency: watch::Sender<Option<Duration>>,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_secs(websocket::DEFAULT_PING_INTERVAL_SECS),
+            Duration::from_secs(websocket::DEFAULT_PONG_TIMEOUT_SECS),
+        )
+    }
+}
+
+impl Heartbeat {
+    pub fn new(ping_interval: Duration, pong_timeout: Duration) -> Self {
+        Self {
+            ping_interval,
+            pong_timeout,
+            last_ping_at: Instant::now(),
+            ping_sent_at: None,
+            latency: watch::channel(None).0,
+        }
+    }
+
+    /// 最近一次 `ping` / `pong` 的往返延迟
+    pub fn latency(&self) -> Option<Duration> {
+        *self.latency.borrow()
+    }
+
+    /// 订阅往返延迟的更新，供 quant-metrics 等在其它任务中读取
+    pub fn subscribe_latency(&self) -> watch::Receiver<Option<Duration>> {
+        self.latency.subscribe()
+    }
+
+    /// 是否已发送 `ping` 且尚未收到 `pong`
+    pub fn is_awaiting_pong(&self) -> bool {
+        self.ping_sent_at.is_some()
+    }
+
+    /// 重新开始计时，连接建立后调用
+    pub fn reset(&mut self) {
+        self.last_ping_at = Instant::now();
+        self.ping_sent_at = None;
+    }
+
+    /// 等待到下一个需要处理的时间点：等待 `pong` 时为超时时间，否则为下一次 `ping` 的时间
+    pub async fn wait(&self) {
+        tokio::time::sleep_until(self.next_deadline()).await;
+    }
+
+    /// 检查计时器，返回需要执行的动作；返回 `SendPing` 时即记为已发送
+    pub fn poll(&mut self) -> HeartbeatAction {
+        let now = Instant::now();
+        match self.ping_sent_at {
+            Some(sent_at) if now >= sent_at + self.pong_timeout => HeartbeatAction::Dead,
+            Some(_) => HeartbeatAction::Idle,
+            None if now >= self.last_ping_at + self.ping_interval => {
+                self.last_ping_at = now;
+                self.ping_sent_at = Some(now);
+                HeartbeatAction::SendPing
+            }
+            None => HeartbeatAction::Idle,
+        }
+    }
+
+    /// 收到 `pong`，返回本次往返延迟
+    pub fn on_pong(&mut self) -> Option<Duration> {
+        let latency = self.ping_sent_at.take()?.elapsed();
+        self.latency.send_replace(Some(latency));
+        Some(latency)
+    }
+
+    fn next_deadline(&self) -> Instant {
+        match self.ping_sent_at {
+            Some(sent_at) => sent_at + self.pong_timeout,
+            None => self.last_ping_at + self.ping_interval,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ping_pong_latency() {
+        let mut heartbeat = Heartbeat::new(Duration::from_millis(20), Duration::from_secs(1));
+        let mut receiver = heartbeat.subscribe_latency();
+        assert_eq!(heartbeat.poll(), HeartbeatAction::Idle);
+
+        heartbeat.wait().await;
+        assert_eq!(heartbeat.poll(), HeartbeatAction::SendPing);
+        assert!(heartbeat.is_awaiting_pong());
+        assert_eq!(heartbeat.poll(), HeartbeatAction::Idle);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let latency = heartbeat.on_pong().unwrap();
+        assert!(latency >= Duration::from_millis(10));
+        assert!(!heartbeat.is_awaiting_pong());
+        assert_eq!(heartbeat.latency(), Some(latency));
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(*receiver.borrow_and_update(), Some(latency));
+    }
+
+    #[tokio::test]
+    async fn test_missing_pong_is_dead() {
+        let mut heartbeat = Heartbeat::new(Duration::from_millis(10), Duration::from_millis(30));
+        heartbeat.wait().await;
+        assert_eq!(heartbeat.poll(), HeartbeatAction::SendPing);
+        heartbeat.wait().await;
+        assert_eq!(heartbeat.poll(), HeartbeatAction::Dead);
+
+        heartbeat.reset();
+        assert!(!heartbeat.is_awaiting_pong());
+        assert_eq!(heartbeat.poll(), HeartbeatAction::Idle);
+    }
+
+    #[test]
+    fn test_unsolicited_pong() {
+        let mut heartbeat = Heartbeat::default();
+        assert_eq!(heartbeat.on_pong(), None);
+        assert_eq!(heartbeat.latency(), None);
+    }
+}
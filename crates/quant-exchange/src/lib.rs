@@ -1,8 +1,11 @@
 pub mod binance;
 pub mod constant;
 pub mod error;
+pub mod heartbeat;
 pub mod okex;
 pub mod schema;
+#[cfg(test)]
+mod test_util;
 
 use crate::schema::okex::channel::WsChannelType;
 use async_trait::async_trait;
@@ -13,8 +16,9 @@ use futures_util::{
     stream::{SplitSink, SplitStream, StreamExt},
 };
 use hmac::{Hmac, Mac};
+use quant_schema::{Balance, Exchange, OrderAck, OrderId, OrderRequest, Position};
 use reqwest::{Client, Method, Response, header::HeaderMap};
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::collections::HashMap;
 use tokio_tungstenite::tungstenite::handshake::client::Request;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::protocol::Message};
@@ -45,16 +49,14 @@ pub trait ExchangeHttpClient: ExchangeClient {
         body: Option<&T>,
         authenticated: bool,
     ) -> Result<Response> {
-        let mut url = format!("{}{}", self.base_url()?, path);
-
-        // 添加查询参数
-        if let Some(params) = query_params {
-            if !params.is_empty() {
-                let query_string: Vec<String> =
-                    params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
-                url = format!("{}?{}", url, query_string.join("&"));
+        // 添加查询参数，签名使用包含查询字符串的请求路径
+        let request_path = match query_params {
+            Some(params) if !params.is_empty() => {
+                format!("{}?{}", path, serde_urlencoded::to_string(params)?)
             }
-        }
+            _ => path.to_string(),
+        };
+        let url = format!("{}{}", self.base_url()?, request_path);
 
         let body_str = match body {
             Some(b) => serde_json::to_string(b)?,
@@ -69,7 +71,7 @@ pub trait ExchangeHttpClient: ExchangeClient {
 
         // 如果需要认证，添加签名头部
         if authenticated {
-            let auth_headers = self.sign_request(&method, path, &body_str)?;
+            let auth_headers = self.sign_request(method, &request_path, &body_str)?;
             headers.extend(auth_headers);
         }
 
@@ -87,6 +89,46 @@ pub trait ExchangeHttpClient: ExchangeClient {
     }
 }
 
+/// 与交易所无关的交易接口，策略只依赖该接口即可在任一 `Exchange` 上运行
+///
+/// `symbol` 均为交易所自己的产品ID，如 OKX 的 `BTC-USDT`、Binance 的 `BTCUSDT`。
+#[async_trait]
+pub trait TradingVenue {
+    fn exchange(&self) -> Exchange;
+
+    /// 下单，返回交易所确认的订单ID
+    async fn submit_order(&mut self, order: &OrderRequest) -> Result<OrderAck>;
+
+    /// 撤单
+    async fn cancel(&mut self, symbol: &str, order_id: &OrderId) -> Result<OrderAck>;
+
+    /// 余额不为零的币种
+    async fn balances(&mut self) -> Result<Vec<Balance>>;
+
+    /// 当前持仓，现货账户返回空列表
+    async fn positions(&mut self) -> Result<Vec<Position>>;
+
+    /// 订阅深度，数据从交易所客户端的事件流返回
+    async fn subscribe_book(&mut self, symbol: &str) -> Result<()>;
+}
+
+/// 解析交易所返回的数字字符串（`Price`、`Quantity` 或 `Decimal`），空字符串视为 0
+pub(crate) fn parse_number<T: std::str::FromStr + Default>(value: &str) -> Result<T> {
+    if value.is_empty() {
+        return Ok(T::default());
+    }
+    value
+        .parse()
+        .map_err(|_| error::Error::Other("Invalid number returned by exchange"))
+}
+
+/// 毫秒时间戳转换为 UTC 时间
+pub(crate) fn timestamp_from_millis(millis: i64) -> Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp_millis(millis).ok_or(error::Error::Other(
+        "Timestamp out of range returned by exchange",
+    ))
+}
+
 pub trait Signature {
     fn secret(&self) -> Result<String>;
     fn merged_string(
@@ -116,7 +158,7 @@ pub trait Signature {
 
 #[async_trait]
 pub trait ExchangeWsClientTrait: ExchangeClient {
-    fn request(&self) -> Result<Request>;
+    fn request(&self, channel: WsChannelType) -> Result<Request>;
 
     fn login_request(&self) -> Result<String>;
     async fn get_ws_stream(&mut self, channel: WsChannelType) -> Result<(WsSink, WsReader)> {
@@ -124,8 +166,7 @@ pub trait ExchangeWsClientTrait: ExchangeClient {
             self.close().await?;
             return Err(error::Error::Other("WebSocket is already connected"));
         }
-        let request = self.request()?;
-        println!("Connecting to WebSocket: {:?}", request.headers());
+        let request = self.request(channel)?;
         let (ws_stream, _) = tokio_tungstenite::connect_async(request.uri()).await?;
         let (mut write, read) = ws_stream.split();
 
@@ -1,67 +1,195 @@
-use crate::constant::okx_endpoints;
-use crate::error::{Error, Result};
-use crate::schema::okex::login::{LoginArgs, LoginRequest};
-use crate::{ExchangeClient, ExchangeHttpClient, ExchangeWsClientTrait, Signature};
+use crate::constant::{okx_endpoints, websocket};
+use crate::error::{Error, LoginFailure, Result};
+use crate::heartbeat::{Heartbeat, HeartbeatAction};
+use crate::schema::okex::account::{AccountBalance, PositionDetail};
+use crate::schema::okex::channel::{OkexWsChannel, WsChannelType};
+use crate::schema::okex::login::{LoginArgs, LoginRequest, LoginResponse};
+use crate::schema::okex::market::{Bar, CandleData, TradeData, TradePaging};
+use crate::schema::okex::order::{
+    AmendOrderArgs, CancelOrderArgs, CreateOrder, CreateOrderResponse, Instrument, OrderData,
+    OrderOp,
+};
+use crate::schema::okex::public::InstrumentInfo;
+use crate::schema::okex::response::{ApiResponse, ItemStatus};
+use crate::schema::okex::subscription::{
+    EventKind, InstType, OkexWsMessage, SubscribeArg, SubscribeEvent, SubscribeOp, SubscribeRequest,
+};
+use crate::schema::okex::trade::{Fill, OrderDetail, OrderQuery};
+use crate::{
+    ExchangeClient, ExchangeHttpClient, ExchangeWsClientTrait, Signature, TradingVenue, WsReader,
+    WsSink, parse_number,
+};
 use async_trait::async_trait;
-use bytes::Bytes;
-use quant_config::{Config, Credentials, OkexConfig, USER_CONFIG};
-use quant_schema::Exchange;
-use reqwest::{Method, header::HeaderMap};
+use futures_util::{SinkExt, StreamExt};
+use quant_config::{Config, Credentials, ExchangeConfig, OkexConfig};
+use quant_schema::{
+    Balance, Exchange, OrderAck, OrderId, OrderRequest, Position, PositionSide, Quantity,
+    ReceiveTime,
+};
+use reqwest::{
+    Method, Response,
+    header::{HeaderMap, HeaderValue},
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::tungstenite::handshake::client::Request;
 
 pub struct OkexClient<'a> {
     pub http_client: &'a reqwest::Client,
     pub config: &'a Config,
     pub ws_is_connected: bool,
+    pub subscriptions: Subscriptions,
+    /// 超过该时间没有收到消息即认为连接已失效，触发重连
+    pub stall_timeout: Duration,
+    /// `ping` / `pong` 心跳，`pong` 超时即认为连接已失效，触发重连
+    pub heartbeat: Heartbeat,
+    ws_channel: Option<WsChannelType>,
+    ws_sink: Option<WsSink>,
+    ws_reader: Option<WsReader>,
+    /// 等待交易请求响应期间收到的其它文本消息及其接收时间，由 `next_event` 依次处理
+    pending_messages: VecDeque<(String, ReceiveTime)>,
+    /// `next_event` 最近返回的消息的接收时间
+    last_received: Option<ReceiveTime>,
+}
+
+/// `next_event` 返回给调用方的事件
+#[derive(Debug)]
+pub enum OkexWsEvent {
+    /// 收到的消息
+    Message(OkexWsMessage),
+
+    /// 连接断开后已重连，并重新发送了断开前的订阅
+    Reconnected {
+        /// 本次重连尝试的次数
+        attempts: u32,
+        /// 重新发送订阅的频道数量
+        resubscribed: usize,
+    },
+}
+
+/// 订阅状态：已确认的订阅，以及已发送但尚未收到确认的请求
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    active: HashSet<SubscribeArg>,
+    pending: HashMap<String, SubscribeRequest>,
+}
+
+impl Subscriptions {
+    /// 已确认的订阅
+    pub fn active(&self) -> impl Iterator<Item = &SubscribeArg> {
+        self.active.iter()
+    }
+
+    pub fn is_active(&self, arg: &SubscribeArg) -> bool {
+        self.active.contains(arg)
+    }
+
+    /// 尚未收到确认的请求数量
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 取出需要在重连后重新订阅的频道：已确认的订阅加上尚未确认的订阅请求，
+    /// 排除尚未确认的取消订阅请求
+    fn take_for_replay(&mut self) -> Vec<SubscribeArg> {
+        let mut args: HashSet<SubscribeArg> = self.active.drain().collect();
+        for (_, request) in self.pending.drain() {
+            match request.op {
+                SubscribeOp::Subscribe => args.extend(request.args),
+                SubscribeOp::Unsubscribe => {
+                    for arg in &request.args {
+                        args.remove(arg);
+                    }
+                }
+            }
+        }
+        args.into_iter().collect()
+    }
+
+    fn track(&mut self, id: String, request: SubscribeRequest) {
+        self.pending.insert(id, request);
+    }
+
+    /// 根据服务端事件更新订阅状态，属于订阅请求的 `error` 事件转换为 `Error::Subscription`
+    fn on_event(&mut self, event: &SubscribeEvent) -> Result<()> {
+        match event.event {
+            EventKind::Subscribe | EventKind::Unsubscribe => {
+                let Some(arg) = &event.arg else {
+                    return Ok(());
+                };
+                if event.event == EventKind::Subscribe {
+                    self.active.insert(arg.clone());
+                } else {
+                    self.active.remove(arg);
+                }
+                if let Some(id) = &event.id
+                    && let Some(request) = self.pending.get_mut(id)
+                {
+                    request.args.retain(|pending| pending != arg);
+                    if request.args.is_empty() {
+                        self.pending.remove(id);
+                    }
+                }
+                Ok(())
+            }
+            // 按 `id` 找到出错的请求，只有该请求失败；对应不到订阅请求的错误不影响订阅状态
+            EventKind::Error => {
+                let Some(id) = event
+                    .id
+                    .as_ref()
+                    .filter(|id| self.pending.contains_key(*id))
+                else {
+                    return Ok(());
+                };
+                self.pending.remove(id);
+                Err(Error::Subscription {
+                    id: id.clone(),
+                    code: event.code.clone().unwrap_or_default(),
+                    msg: event.msg.clone().unwrap_or_default(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
 impl ExchangeClient for OkexClient<'_> {
     fn exchange_client_headers(&self) -> HeaderMap {
-        let headers = HeaderMap::new();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
         headers
     }
 
+    /// REST 基础地址，模拟盘与实盘使用同一域名，通过 `x-simulated-trading` 头部区分
     fn base_url(&self) -> Result<String> {
-        let credentials: Credentials = self.get_config()?;
-        let Credentials {
-            use_testnet,
-            protocol,
-            ..
-        } = credentials;
-
-        let use_testnet = use_testnet.unwrap_or(false);
-        let protocol = protocol.as_deref().unwrap_or("https");
-
-        match (use_testnet, protocol) {
-            (true, "https") => Ok(okx_endpoints::DEMO_REST_BASE_URL.to_string()),
-            (true, "wss") => Ok(okx_endpoints::DEMO_WS_PUBLIC_URL.to_string()),
-            (false, "https") => Ok(okx_endpoints::PROD_REST_BASE_URL.to_string()),
-            (false, "wss") => Ok(okx_endpoints::PROD_WS_PUBLIC_URL.to_string()),
-            _ => Err(Error::ConfigError(
-                "Invalid protocol or testnet configuration",
-            )),
-        }
+        let config = self.okex_config()?;
+        let (urls, fallback) = if config.credentials.use_testnet.unwrap_or(false) {
+            (
+                config.testnet_http_urls.unwrap_or_default(),
+                okx_endpoints::DEMO_REST_BASE_URL,
+            )
+        } else {
+            (config.http_urls, okx_endpoints::PROD_REST_BASE_URL)
+        };
+        Ok(urls
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| fallback.to_string()))
     }
 
+    /// `path` 需要包含查询字符串，如 `/api/v5/account/balance?ccy=BTC`
     fn sign_request(&self, method: &Method, path: &str, body: &str) -> Result<HeaderMap> {
-        let config = self.get_config()?;
-
-        let mut headers = self.exchange_client_headers();
         let timestamp = chrono::Utc::now()
             .format("%Y-%m-%dT%H:%M:%S%.3fZ")
             .to_string();
-        let sign = self.gen_signature(method, path, body, &timestamp)?;
-        // Here you would implement the signing logic specific to Okex
-        // For example, you might need to add an API key, signature, etc.
-        // This is a placeholder implementation.
-        headers.insert("OK-ACCESS-KEY", config.api_key.parse()?);
-        headers.insert("OK-ACCESS-SIGN", sign.parse().unwrap());
-        headers.insert("OK-ACCESS-TIMESTAMP", timestamp.parse()?);
-        if config.use_testnet.unwrap_or(false) && config.protocol.as_deref() == Some("wss") {
-            headers.insert("x-simulated-trading", "1".parse()?);
-        }
-        Ok(headers)
+        self.auth_headers(method, path, body, &timestamp)
     }
 }
 
@@ -75,17 +203,647 @@ impl ExchangeHttpClient for OkexClient<'_> {
     }
 }
 
-impl OkexClient<'_> {
+impl<'a> OkexClient<'a> {
+    pub fn new(http_client: &'a reqwest::Client, config: &'a Config) -> Self {
+        Self {
+            http_client,
+            config,
+            ws_is_connected: false,
+            subscriptions: Subscriptions::default(),
+            stall_timeout: Duration::from_secs(websocket::DEFAULT_STALL_TIMEOUT_SECS),
+            heartbeat: Heartbeat::default(),
+            ws_channel: None,
+            ws_sink: None,
+            ws_reader: None,
+            pending_messages: VecDeque::new(),
+            last_received: None,
+        }
+    }
+
+    /// 建立 WebSocket 连接，私有频道会登录并等待登录结果
+    pub async fn connect(&mut self, channel: WsChannelType) -> Result<()> {
+        let (sink, reader) = self.get_ws_stream(channel).await?;
+        self.ws_channel = Some(channel);
+        self.ws_sink = Some(sink);
+        self.ws_reader = Some(reader);
+        self.heartbeat.reset();
+        if channel == WsChannelType::Private
+            && let Err(err) = self.await_login().await
+        {
+            self.close().await?;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// 等待登录结果，超时时间为全局配置的 `timeout`
+    async fn await_login(&mut self) -> Result<()> {
+        let timeout = Duration::from_millis(self.config.global_config.timeout);
+        let reader = self
+            .ws_reader
+            .as_mut()
+            .ok_or(Error::Other("WebSocket is not connected"))?;
+        let wait = async {
+            loop {
+                match reader.next().await {
+                    Some(Ok(Message::Text(text))) => {
+                        let Ok(response) = serde_json::from_str::<LoginResponse>(&text) else {
+                            continue;
+                        };
+                        if response.event == "login" || response.event == "error" {
+                            return response.into_result();
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => return Err(err.into()),
+                    None => return Err(Error::Other("WebSocket closed before login response")),
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(Error::Login {
+                    reason: LoginFailure::Other,
+                    code: String::new(),
+                    msg: "Timed out waiting for login response".to_string(),
+                })
+            })
+    }
+
+    /// 最近一次心跳的往返延迟
+    pub fn latency(&self) -> Option<Duration> {
+        self.heartbeat.latency()
+    }
+
+    /// `next_event` 最近返回的消息从连接中读取的时间，在解析消息之前记录
+    pub fn last_received(&self) -> Option<ReceiveTime> {
+        self.last_received
+    }
+
+    /// 发送订阅请求，返回请求的 `id`；订阅在收到确认事件后才会生效
+    pub async fn subscribe(&mut self, args: Vec<SubscribeArg>) -> Result<String> {
+        self.send_subscribe_request(SubscribeOp::Subscribe, args)
+            .await
+    }
+
+    /// 发送取消订阅请求，返回请求的 `id`
+    pub async fn unsubscribe(&mut self, args: Vec<SubscribeArg>) -> Result<String> {
+        self.send_subscribe_request(SubscribeOp::Unsubscribe, args)
+            .await
+    }
+
+    /// 等待下一个事件
+    ///
+    /// 期间按 `heartbeat` 的间隔发送 `ping`，`pong` 只用于计算延迟，不会返回给调用方。
+    /// 连接关闭、出错、`pong` 超时或超过 `stall_timeout` 没有消息时，会按退避策略重连
+    /// 并重新订阅，然后返回 `OkexWsEvent::Reconnected`。
+    pub async fn next_event(&mut self) -> Result<OkexWsEvent> {
+        if let Some((text, received)) = self.pending_messages.pop_front() {
+            self.last_received = Some(received);
+            return Ok(OkexWsEvent::Message(self.handle_message(&text)?));
+        }
+        loop {
+            let reader = self
+                .ws_reader
+                .as_mut()
+                .ok_or(Error::Other("WebSocket is not connected"))?;
+            let message = tokio::select! {
+                message = tokio::time::timeout(self.stall_timeout, reader.next()) => message,
+                _ = self.heartbeat.wait() => {
+                    match self.heartbeat.poll() {
+                        HeartbeatAction::SendPing => {
+                            if self.send_ping().await.is_err() {
+                                return self.recover().await;
+                            }
+                        }
+                        HeartbeatAction::Dead => return self.recover().await,
+                        HeartbeatAction::Idle => {}
+                    }
+                    continue;
+                }
+            };
+            match message {
+                Ok(Some(Ok(Message::Text(text)))) if text.as_str() == "pong" => {
+                    self.heartbeat.on_pong();
+                }
+                Ok(Some(Ok(Message::Text(text)))) => {
+                    self.last_received = Some(ReceiveTime::now());
+                    return Ok(OkexWsEvent::Message(self.handle_message(&text)?));
+                }
+                Ok(Some(Ok(Message::Ping(payload)))) => {
+                    if let Some(sink) = self.ws_sink.as_mut() {
+                        sink.send(Message::Pong(payload)).await?;
+                    }
+                }
+                Ok(Some(Ok(Message::Close(_)))) | Ok(Some(Err(_))) | Ok(None) | Err(_) => {
+                    return self.recover().await;
+                }
+                Ok(Some(Ok(_))) => {}
+            }
+        }
+    }
+
+    /// 重连并重新订阅，返回 `OkexWsEvent::Reconnected`
+    async fn recover(&mut self) -> Result<OkexWsEvent> {
+        let attempts = self.reconnect_with_backoff().await?;
+        let resubscribed = self.replay_subscriptions().await?;
+        Ok(OkexWsEvent::Reconnected {
+            attempts,
+            resubscribed,
+        })
+    }
+
+    async fn send_ping(&mut self) -> Result<()> {
+        let sink = self
+            .ws_sink
+            .as_mut()
+            .ok_or(Error::Other("WebSocket is not connected"))?;
+        sink.send(Message::Text("ping".into())).await?;
+        Ok(())
+    }
+
+    /// WebSocket 地址，`ws_urls` 依次为 public、private、business 频道
+    pub fn ws_url(&self, channel: WsChannelType) -> Result<String> {
+        let config = self.okex_config()?;
+        let (urls, defaults) = if config.credentials.use_testnet.unwrap_or(false) {
+            (
+                config.testnet_ws_urls.unwrap_or_default(),
+                [
+                    okx_endpoints::DEMO_WS_PUBLIC_URL,
+                    okx_endpoints::DEMO_WS_PRIVATE_URL,
+                    okx_endpoints::DEMO_WS_BUSINESS_URL,
+                ],
+            )
+        } else {
+            (
+                config.ws_urls,
+                [
+                    okx_endpoints::PROD_WS_PUBLIC_URL,
+                    okx_endpoints::PROD_WS_PRIVATE_URL,
+                    okx_endpoints::PROD_WS_BUSINESS_URL,
+                ],
+            )
+        };
+        let index = match channel {
+            WsChannelType::Public => 0,
+            WsChannelType::Private => 1,
+            WsChannelType::Business => 2,
+        };
+        Ok(urls
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| defaults[index].to_string()))
+    }
+
+    /// 重连次数和初始退避时间（毫秒），优先使用 OKX 的配置，否则使用全局配置
+    fn retry_policy(&self) -> (u32, u64) {
+        let global = &self.config.global_config;
+        let okex = self.okex_config().ok();
+        let retry_count = okex
+            .as_ref()
+            .and_then(|config| config.retry_count)
+            .map(u32::from)
+            .unwrap_or(global.retry_count);
+        let retry_delay = okex
+            .as_ref()
+            .and_then(|config| config.retry_delay)
+            .unwrap_or(global.retry_delay);
+        (retry_count, retry_delay)
+    }
+
+    /// 按指数退避重连，返回成功时的尝试次数
+    async fn reconnect_with_backoff(&mut self) -> Result<u32> {
+        let channel = self
+            .ws_channel
+            .ok_or(Error::Other("WebSocket channel is not set"))?;
+        let (retry_count, retry_delay) = self.retry_policy();
+        self.close().await?;
+        for attempt in 1..=retry_count.max(1) {
+            tokio::time::sleep(reconnect_delay(retry_delay, attempt)).await;
+            match self.connect(channel).await {
+                Ok(()) => return Ok(attempt),
+                // 凭证错误重试也不会成功
+                Err(err @ Error::Login { reason, .. }) if reason != LoginFailure::Other => {
+                    return Err(err);
+                }
+                Err(_) => {}
+            }
+        }
+        Err(Error::ReconnectExhausted(retry_count.max(1)))
+    }
+
+    /// 重新发送断开前的订阅，返回频道数量
+    async fn replay_subscriptions(&mut self) -> Result<usize> {
+        let args = self.subscriptions.take_for_replay();
+        let count = args.len();
+        if count > 0 {
+            self.subscribe(args).await?;
+        }
+        Ok(count)
+    }
+
+    /// 解析收到的文本消息并更新订阅状态
+    pub fn handle_message(&mut self, text: &str) -> Result<OkexWsMessage> {
+        let message: OkexWsMessage = serde_json::from_str(text)?;
+        if let OkexWsMessage::Event(event) = &message {
+            self.subscriptions.on_event(event)?;
+        }
+        Ok(message)
+    }
+
+    async fn send_subscribe_request(
+        &mut self,
+        op: SubscribeOp,
+        args: Vec<SubscribeArg>,
+    ) -> Result<String> {
+        if args.is_empty() {
+            return Err(Error::Other("Subscribe args must not be empty"));
+        }
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let request = SubscribeRequest {
+            id: Some(id.clone()),
+            op,
+            args,
+        };
+        let sink = self
+            .ws_sink
+            .as_mut()
+            .ok_or(Error::Other("WebSocket is not connected"))?;
+        sink.send(Message::Text(serde_json::to_string(&request)?.into()))
+            .await?;
+        self.subscriptions.track(id.clone(), request);
+        Ok(id)
+    }
+
+    /// 下单，`sCode` 不为 `0` 时返回 `Error::Api`
+    pub async fn place_order(&mut self, order: Instrument) -> Result<OrderData> {
+        let request = CreateOrder::new(OrderOp::Order, vec![order]);
+        Self::single_item(ApiResponse::from(self.send_order_request(request).await?).into_items()?)
+    }
+
+    /// 批量下单，返回每个订单的结果，由调用方检查各自的 `sCode`
+    pub async fn place_batch_orders(&mut self, orders: Vec<Instrument>) -> Result<Vec<OrderData>> {
+        if orders.is_empty() || orders.len() > websocket::MAX_BATCH_ORDERS {
+            return Err(Error::Other("Batch orders must contain 1 to 20 orders"));
+        }
+        let request = CreateOrder::new(OrderOp::BatchOrders, orders);
+        ApiResponse::from(self.send_order_request(request).await?).into_items()
+    }
+
+    /// 修改订单，`sCode` 不为 `0` 时返回 `Error::Api`
+    pub async fn amend_order(&mut self, args: AmendOrderArgs) -> Result<OrderData> {
+        let request = CreateOrder::new(OrderOp::AmendOrder, vec![args]);
+        Self::single_item(ApiResponse::from(self.send_order_request(request).await?).into_items()?)
+    }
+
+    /// 撤单，`sCode` 不为 `0` 时返回 `Error::Api`
+    pub async fn cancel_order(&mut self, args: CancelOrderArgs) -> Result<OrderData> {
+        let request = CreateOrder::new(OrderOp::CancelOrder, vec![args]);
+        Self::single_item(ApiResponse::from(self.send_order_request(request).await?).into_items()?)
+    }
+
+    /// 发送交易请求并等待 `id` 相同的响应
+    ///
+    /// 设置了 `exp_time` 时最多等待到截止时间之后 `ORDER_RESPONSE_GRACE_MS`，已过期的请求不会发送；
+    /// 否则使用全局配置的 `timeout`。等待期间收到的其它消息会缓存起来，由 `next_event` 返回。
+    pub async fn send_order_request<A: Serialize>(
+        &mut self,
+        request: CreateOrder<A>,
+    ) -> Result<CreateOrderResponse> {
+        if self.ws_channel != Some(WsChannelType::Private) {
+            return Err(Error::Other("Orders must be sent on the private channel"));
+        }
+        if request.args.is_empty() {
+            return Err(Error::Other("Order args must not be empty"));
+        }
+        let timeout = match request.exp_time.as_deref() {
+            Some(exp_time) => {
+                let exp_time: i64 = exp_time
+                    .parse()
+                    .map_err(|_| Error::Other("expTime must be a millisecond timestamp"))?;
+                let remaining = exp_time - chrono::Utc::now().timestamp_millis();
+                if remaining <= 0 {
+                    return Err(Error::RequestTimeout(request.id));
+                }
+                Duration::from_millis(remaining as u64 + websocket::ORDER_RESPONSE_GRACE_MS)
+            }
+            None => Duration::from_millis(self.config.global_config.timeout),
+        };
+        let sink = self
+            .ws_sink
+            .as_mut()
+            .ok_or(Error::Other("WebSocket is not connected"))?;
+        sink.send(Message::Text(serde_json::to_string(&request)?.into()))
+            .await?;
+        tokio::time::timeout(timeout, self.await_order_response(&request.id))
+            .await
+            .unwrap_or(Err(Error::RequestTimeout(request.id)))
+    }
+
+    async fn await_order_response(&mut self, id: &str) -> Result<CreateOrderResponse> {
+        loop {
+            let reader = self
+                .ws_reader
+                .as_mut()
+                .ok_or(Error::Other("WebSocket is not connected"))?;
+            match reader.next().await {
+                Some(Ok(Message::Text(text))) if text.as_str() == "pong" => {
+                    self.heartbeat.on_pong();
+                }
+                Some(Ok(Message::Text(text))) => {
+                    let received = ReceiveTime::now();
+                    if let Ok(OkexWsMessage::Order(response)) = serde_json::from_str(&text)
+                        && response.id == id
+                    {
+                        return Ok(response);
+                    }
+                    self.pending_messages
+                        .push_back((text.to_string(), received));
+                }
+                Some(Ok(Message::Ping(payload))) => {
+                    if let Some(sink) = self.ws_sink.as_mut() {
+                        sink.send(Message::Pong(payload)).await?;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err(Error::Other("WebSocket closed before order response"));
+                }
+                Some(Err(err)) => return Err(err.into()),
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+
+    fn is_private_connected(&self) -> bool {
+        self.ws_is_connected && self.ws_channel == Some(WsChannelType::Private)
+    }
+
+    /// 单个订单的请求：`code` 为 `1` 时返回该订单的 `sCode` / `sMsg`
+    fn single_item<T: ItemStatus>(items: Vec<T>) -> Result<T> {
+        let item = items
+            .into_iter()
+            .next()
+            .ok_or(Error::Other("Empty order response"))?;
+        item.status()?;
+        Ok(item)
+    }
+
+    /// REST 下单，WebSocket 不可用时使用；`sCode` 不为 `0` 时返回 `Error::Api`
+    pub async fn rest_place_order(&self, order: &Instrument) -> Result<OrderData> {
+        Self::single_item(
+            self.send_batch(&Method::POST, "/api/v5/trade/order", Some(order))
+                .await?,
+        )
+    }
+
+    /// REST 批量下单，返回每个订单的结果，由调用方检查各自的 `sCode`
+    pub async fn rest_place_batch_orders(&self, orders: &[Instrument]) -> Result<Vec<OrderData>> {
+        if orders.is_empty() || orders.len() > websocket::MAX_BATCH_ORDERS {
+            return Err(Error::Other("Batch orders must contain 1 to 20 orders"));
+        }
+        self.send_batch(&Method::POST, "/api/v5/trade/batch-orders", Some(&orders))
+            .await
+    }
+
+    /// REST 修改订单，`sCode` 不为 `0` 时返回 `Error::Api`
+    pub async fn rest_amend_order(&self, args: &AmendOrderArgs) -> Result<OrderData> {
+        Self::single_item(
+            self.send_batch(&Method::POST, "/api/v5/trade/amend-order", Some(args))
+                .await?,
+        )
+    }
+
+    /// REST 撤单，`sCode` 不为 `0` 时返回 `Error::Api`
+    pub async fn rest_cancel_order(&self, args: &CancelOrderArgs) -> Result<OrderData> {
+        Self::single_item(
+            self.send_batch(&Method::POST, "/api/v5/trade/cancel-order", Some(args))
+                .await?,
+        )
+    }
+
+    /// 查询订单，`ordId` 与 `clOrdId` 必须传一个
+    pub async fn get_order(&self, args: &CancelOrderArgs) -> Result<OrderDetail> {
+        let mut params = HashMap::from([("instId".to_string(), args.inst_id.clone())]);
+        if let Some(ord_id) = &args.ord_id {
+            params.insert("ordId".to_string(), ord_id.clone());
+        }
+        if let Some(cl_ord_id) = &args.cl_ord_id {
+            params.insert("clOrdId".to_string(), cl_ord_id.clone());
+        }
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/trade/order",
+            Some(&params),
+            None::<&()>,
+            true,
+        )
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(Error::Other("Order not found"))
+    }
+
+    /// 未成交订单列表
+    pub async fn get_pending_orders(&self, query: &OrderQuery) -> Result<Vec<OrderDetail>> {
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/trade/orders-pending",
+            Some(&query.to_params()),
+            None::<&()>,
+            true,
+        )
+        .await
+    }
+
+    /// 最近 7 天的历史订单，`instType` 必填
+    pub async fn get_order_history(
+        &self,
+        inst_type: InstType,
+        query: &OrderQuery,
+    ) -> Result<Vec<OrderDetail>> {
+        let query = query.clone().inst_type(inst_type);
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/trade/orders-history",
+            Some(&query.to_params()),
+            None::<&()>,
+            true,
+        )
+        .await
+    }
+
+    /// 最近 3 天的成交明细
+    pub async fn get_fills(&self, query: &OrderQuery) -> Result<Vec<Fill>> {
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/trade/fills",
+            Some(&query.to_params()),
+            None::<&()>,
+            true,
+        )
+        .await
+    }
+
+    /// 产品列表，期权必须指定 `inst_family`（如 `BTC-USD`）
+    pub async fn get_instruments(
+        &self,
+        inst_type: InstType,
+        inst_family: Option<&str>,
+    ) -> Result<Vec<InstrumentInfo>> {
+        if inst_type == InstType::Option && inst_family.is_none() {
+            return Err(Error::Other("Option instruments require instFamily"));
+        }
+        let mut query = HashMap::from([("instType".to_string(), inst_type.as_str().to_string())]);
+        if let Some(inst_family) = inst_family {
+            query.insert("instFamily".to_string(), inst_family.to_string());
+        }
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/public/instruments",
+            Some(&query),
+            None::<&()>,
+            false,
+        )
+        .await
+    }
+
+    /// 历史 K 线，只包含已完结的 K 线，按时间倒序返回
+    /// * `after` 返回开始时间早于该毫秒时间戳的数据，`before` 返回晚于该时间戳的数据
+    /// * `limit` 最大为 100，默认 100
+    pub async fn get_history_candles(
+        &self,
+        inst_id: &str,
+        bar: Bar,
+        after: Option<i64>,
+        before: Option<i64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<CandleData>> {
+        let mut query = history_query(after, before, limit);
+        query.insert("instId".to_string(), inst_id.to_string());
+        query.insert("bar".to_string(), bar.as_str().to_string());
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/market/history-candles",
+            Some(&query),
+            None::<&()>,
+            false,
+        )
+        .await
+    }
+
+    /// 历史成交，按时间倒序返回；`paging` 决定 `after` / `before` 是成交ID还是时间戳，`limit` 同 [`Self::get_history_candles`]
+    pub async fn get_history_trades(
+        &self,
+        inst_id: &str,
+        paging: TradePaging,
+        after: Option<i64>,
+        before: Option<i64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<TradeData>> {
+        let mut query = history_query(after, before, limit);
+        query.insert("instId".to_string(), inst_id.to_string());
+        query.insert("type".to_string(), paging.as_str().to_string());
+        self.send_typed(
+            &Method::GET,
+            "/api/v5/market/history-trades",
+            Some(&query),
+            None::<&()>,
+            false,
+        )
+        .await
+    }
+
     pub fn get_config(&self) -> Result<Credentials> {
-        let config = USER_CONFIG
-            .get()
-            .ok_or(Error::Other("USER CONFIG not initialized."))?
-            .get_exchange(&Exchange::Okex)
-            .ok_or_else(|| Error::ConfigError("Exchange::Okex not found in config"))?;
-        Ok(config.into())
+        Ok(self.okex_config()?.credentials)
+    }
+
+    /// 使用指定的 ISO 时间戳（如 `2020-12-08T09:08:57.715Z`）生成 REST 鉴权头部
+    pub fn auth_headers(
+        &self,
+        method: &Method,
+        request_path: &str,
+        body: &str,
+        timestamp: &str,
+    ) -> Result<HeaderMap> {
+        let config = self.get_config()?;
+        let sign = self.gen_signature(method, request_path, body, timestamp)?;
+
+        let mut headers = self.exchange_client_headers();
+        headers.insert("OK-ACCESS-KEY", config.api_key.parse()?);
+        headers.insert("OK-ACCESS-SIGN", sign.parse()?);
+        headers.insert("OK-ACCESS-TIMESTAMP", timestamp.parse()?);
+        headers.insert("OK-ACCESS-PASSPHRASE", config.passphrase.parse()?);
+        if config.use_testnet.unwrap_or(false) {
+            headers.insert("x-simulated-trading", "1".parse()?);
+        }
+        Ok(headers)
+    }
+
+    pub fn okex_config(&self) -> Result<OkexConfig> {
+        match self.config.get_exchange(&Exchange::Okex) {
+            Some(ExchangeConfig::Okex(config)) => Ok(config),
+            _ => Err(Error::ConfigError("Exchange::Okex not found in config")),
+        }
+    }
+
+    /// 发送请求并解析 `{code, msg, data}`，`code` 不为 `0` 时返回 `Error::Api`
+    pub async fn send_typed<B: Serialize + Sync, T: DeserializeOwned>(
+        &self,
+        method: &Method,
+        path: &str,
+        query_params: Option<&HashMap<String, String>>,
+        body: Option<&B>,
+        authenticated: bool,
+    ) -> Result<Vec<T>> {
+        let response = self
+            .send_request(method, path, query_params, body, authenticated)
+            .await?;
+        Self::parse_envelope::<T>(response).await?.into_result()
+    }
+
+    /// 批量接口：部分或全部失败时仍返回每一项，由调用方检查 `sCode` / `sMsg`
+    pub async fn send_batch<B: Serialize + Sync, T: DeserializeOwned + ItemStatus>(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Vec<T>> {
+        let response = self.send_request(method, path, None, body, true).await?;
+        Self::parse_envelope::<T>(response).await?.into_items()
+    }
+
+    async fn parse_envelope<T: DeserializeOwned>(response: Response) -> Result<ApiResponse<T>> {
+        let status = response.status();
+        let text = response.text().await?;
+        match serde_json::from_str::<ApiResponse<T>>(&text) {
+            Ok(envelope) => Ok(envelope),
+            Err(err) => match serde_json::from_str::<ApiResponse<serde_json::Value>>(&text) {
+                // 出错时 `data` 的结构可能与成功时不同
+                Ok(envelope) if envelope.code != "0" => Err(Error::Api {
+                    code: envelope.code,
+                    msg: envelope.msg,
+                }),
+                _ if !status.is_success() => Err(Error::Api {
+                    code: status.as_u16().to_string(),
+                    msg: text,
+                }),
+                _ => Err(err.into()),
+            },
+        }
     }
 }
 
+/// 第 `attempt` 次重连前的等待时间：`retry_delay * 2^(attempt - 1)`，不超过上限
+fn reconnect_delay(retry_delay: u64, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(
+        retry_delay
+            .saturating_mul(factor)
+            .min(websocket::MAX_RECONNECT_DELAY_MS),
+    )
+}
+
 impl Signature for OkexClient<'_> {
     fn secret(&self) -> Result<String> {
         let config = self.get_config()?;
@@ -99,16 +857,7 @@ impl Signature for OkexClient<'_> {
         body: &str,
         timestamp: &str,
     ) -> Result<String> {
-        let config = self.get_config()?;
-        Ok(format!(
-            "{}{}{}{}{}{}",
-            &config.api_key,
-            &config.secret_key,
-            &timestamp,
-            method.as_str(),
-            path,
-            body
-        ))
+        Ok(format!("{}{}{}{}", timestamp, method.as_str(), path, body))
     }
 }
 
@@ -116,16 +865,12 @@ impl Signature for OkexClient<'_> {
 impl ExchangeWsClientTrait for OkexClient<'_> {
     fn login_request(&self) -> Result<String> {
         let config = self.get_config()?;
-        let login_args = LoginArgs {
-            api_key: config.api_key.clone(),
-            passphrase: config.passphrase.clone(),
-            timestamp: Some(chrono::Utc::now().to_rfc3339()),
-            sign: None, // Sign will be added later
-        };
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let login_args = LoginArgs::new(&config, timestamp)?;
 
         let login_request = LoginRequest {
             op: "login".to_string(),
-            args: login_args,
+            args: vec![login_args],
             is_testnet: config.use_testnet.unwrap_or(false),
         };
 
@@ -138,12 +883,17 @@ impl ExchangeWsClientTrait for OkexClient<'_> {
     }
 
     async fn close(&mut self) -> Result<()> {
+        if let Some(mut sink) = self.ws_sink.take() {
+            let _ = sink.close().await;
+        }
+        self.ws_reader = None;
         self.ws_is_connected = false;
         Ok(())
     }
 
     async fn reconnect(&mut self) -> Result<()> {
-        self.ws_is_connected = true;
+        self.reconnect_with_backoff().await?;
+        self.replay_subscriptions().await?;
         Ok(())
     }
 
@@ -151,41 +901,1398 @@ impl ExchangeWsClientTrait for OkexClient<'_> {
         self.ws_is_connected = state;
     }
 
-    fn request(&self) -> Result<Request> {
+    fn request(&self, channel: WsChannelType) -> Result<Request> {
         let headers = self.sign_request(&Method::GET, "/users/self/verify", "")?;
-        println!("Headers: {:?}", headers);
-        let mut request = Request::builder().uri(self.base_url()?).body(())?;
+        let mut request = Request::builder().uri(self.ws_url(channel)?).body(())?;
         let reuest_headers = request.headers_mut();
         for (key, value) in headers {
-            if key.is_some() {
-                reuest_headers.insert(key.unwrap(), value);
+            if let Some(key) = key {
+                reuest_headers.insert(key, value);
             }
         }
         Ok(request)
     }
 }
 
+#[async_trait]
+impl TradingVenue for OkexClient<'_> {
+    fn exchange(&self) -> Exchange {
+        Exchange::Okex
+    }
+
+    /// 已登录私有频道时通过 WebSocket 下单，否则使用 REST
+    async fn submit_order(&mut self, order: &OrderRequest) -> Result<OrderAck> {
+        let instrument = Instrument::try_from(order)?;
+        let data = if self.is_private_connected() {
+            self.place_order(instrument).await?
+        } else {
+            self.rest_place_order(&instrument).await?
+        };
+        data.to_ack(&order.symbol)
+    }
+
+    async fn cancel(&mut self, symbol: &str, order_id: &OrderId) -> Result<OrderAck> {
+        let (ord_id, cl_ord_id) = match order_id {
+            OrderId::Exchange(id) => (Some(id.clone()), None),
+            OrderId::Client(id) => (None, Some(id.clone())),
+        };
+        let args = CancelOrderArgs {
+            inst_id: symbol.to_string(),
+            ord_id,
+            cl_ord_id,
+        };
+        let data = if self.is_private_connected() {
+            self.cancel_order(args).await?
+        } else {
+            self.rest_cancel_order(&args).await?
+        };
+        data.to_ack(symbol)
+    }
+
+    async fn balances(&mut self) -> Result<Vec<Balance>> {
+        let accounts: Vec<AccountBalance> = self
+            .send_typed(
+                &Method::GET,
+                "/api/v5/account/balance",
+                None,
+                None::<&()>,
+                true,
+            )
+            .await?;
+        let mut balances = Vec::new();
+        for detail in accounts.into_iter().flat_map(|account| account.details) {
+            let balance = Balance {
+                asset: detail.ccy,
+                free: parse_number(&detail.avail_bal)?,
+                locked: parse_number(&detail.frozen_bal)?,
+            };
+            if !balance.total().is_zero() {
+                balances.push(balance);
+            }
+        }
+        Ok(balances)
+    }
+
+    async fn positions(&mut self) -> Result<Vec<Position>> {
+        let details: Vec<PositionDetail> = self
+            .send_typed(
+                &Method::GET,
+                "/api/v5/account/positions",
+                None,
+                None::<&()>,
+                true,
+            )
+            .await?;
+        let mut positions = Vec::new();
+        for detail in details {
+            let pos: Quantity = parse_number(&detail.pos)?;
+            if pos.is_zero() {
+                continue;
+            }
+            let side = match detail.pos_side.as_str() {
+                "long" => PositionSide::Long,
+                "short" => PositionSide::Short,
+                _ if pos < Quantity::ZERO => PositionSide::Short,
+                _ => PositionSide::Long,
+            };
+            positions.push(Position {
+                symbol: detail.inst_id,
+                side,
+                quantity: pos.abs(),
+                entry_price: parse_number(&detail.avg_px)?,
+                unrealized_pnl: parse_number(&detail.upl)?,
+                leverage: Some(detail.lever.as_str())
+                    .filter(|lever| !lever.is_empty())
+                    .map(parse_number)
+                    .transpose()?,
+            });
+        }
+        Ok(positions)
+    }
+
+    /// 订阅 `books` 频道，需要先连接公共频道
+    async fn subscribe_book(&mut self, symbol: &str) -> Result<()> {
+        if self.ws_channel != Some(WsChannelType::Public) {
+            return Err(Error::Other(
+                "Order book channels require the public WebSocket",
+            ));
+        }
+        self.subscribe(vec![
+            SubscribeArg::new(OkexWsChannel::Books).inst_id(symbol),
+        ])
+        .await?;
+        Ok(())
+    }
+}
+
+/// 历史数据的分页参数，未设置的参数不会发送
+fn history_query(
+    after: Option<i64>,
+    before: Option<i64>,
+    limit: Option<u32>,
+) -> HashMap<String, String> {
+    let mut query = HashMap::new();
+    if let Some(after) = after {
+        query.insert("after".to_string(), after.to_string());
+    }
+    if let Some(before) = before {
+        query.insert("before".to_string(), before.to_string());
+    }
+    if let Some(limit) = limit {
+        query.insert("limit".to_string(), limit.to_string());
+    }
+    query
+}
+
 #[cfg(test)]
 mod test {
+    use bytes::Bytes;
     use futures_util::SinkExt;
     use tokio_tungstenite::tungstenite::Message;
 
-    use crate::schema::okex::channel::WsChannelType;
+    use crate::schema::okex::channel::{OkexWsChannel, WsChannelType};
+    use crate::test_util::{spawn_push_server, spawn_ws_server};
+    use futures_util::StreamExt;
+    use quant_config::{Config, GlobalConfig, USER_CONFIG};
+    use std::sync::Mutex;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
+    use crate::error::ApiErrorKind;
+    use crate::schema::okex::order::{OrderData, PositionSide};
+    use crate::schema::okex::trade::OrderState;
+    use crate::test_util::num;
+    use base64::{Engine, engine::general_purpose};
+    use hmac::{Hmac, Mac};
+
+    fn test_config(ws_url: &str) -> Config {
+        config_with_urls("http://127.0.0.1", ws_url)
+    }
+
+    fn config_with_urls(http_url: &str, ws_url: &str) -> Config {
+        let okex = OkexConfig {
+            credentials: Credentials {
+                api_key: "985d5b66-57ce-40fb-b714-afc0b9787083".to_string(),
+                secret_key: "D9E2B1A4C7F3E8D5B6A9C2F1E4D7B8A3".to_string(),
+                passphrase: "passphrase".to_string(),
+                use_testnet: Some(true),
+                protocol: Some("wss".to_string()),
+            },
+            http_urls: vec![],
+            ws_urls: vec![],
+            timeout: None,
+            retry_count: None,
+            retry_delay: None,
+            testnet_http_urls: Some(vec![http_url.to_string()]),
+            testnet_ws_urls: Some(vec![ws_url.to_string(); 3]),
+            ip_blacks: vec![],
+        };
+        Config {
+            exchanges: Mutex::new(HashMap::from([(
+                Exchange::Okex,
+                ExchangeConfig::Okex(okex),
+            )])),
+            default_exchange: Some(Exchange::Okex),
+            global_config: GlobalConfig {
+                timeout: 5000,
+                retry_count: 3,
+                log_level: None,
+                retry_delay: 10,
+                use_testnet: true,
+                protocol: None,
+            },
+        }
+    }
+
+    /// 对每个订阅请求逐个参数回复确认，`instId` 为 `INVALID` 的参数回复错误；
+    /// 第一个连接在处理 `close_first_after` 个请求后主动断开
+    async fn spawn_subscribe_server(close_first_after: Option<usize>) -> String {
+        spawn_ws_server(move |index, mut ws| async move {
+            let mut handled = 0;
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.as_str() == "ping" {
+                    ws.send(Message::Text("pong".into())).await.unwrap();
+                    continue;
+                }
+                let request: SubscribeRequest = serde_json::from_str(&text).unwrap();
+                for arg in request.args {
+                    let reply = if arg.inst_id.as_deref() == Some("INVALID") {
+                        serde_json::json!({
+                            "id": request.id, "event": "error", "code": "60018",
+                            "msg": "Wrong URL or channel:orders,instId:INVALID doesn't exist.",
+                            "connId": "a4d3ae55"
+                        })
+                    } else {
+                        let event = match request.op {
+                            SubscribeOp::Subscribe => "subscribe",
+                            SubscribeOp::Unsubscribe => "unsubscribe",
+                        };
+                        serde_json::json!({
+                            "id": request.id, "event": event, "arg": arg, "connId": "a4d3ae55"
+                        })
+                    };
+                    ws.send(Message::Text(reply.to_string().into()))
+                        .await
+                        .unwrap();
+                }
+                handled += 1;
+                if index == 0 && Some(handled) == close_first_after {
+                    let _ = ws.close(None).await;
+                    return;
+                }
+            }
+        })
+        .await
+    }
+
+    async fn next_message(client: &mut OkexClient<'_>) -> Result<OkexWsMessage> {
+        match client.next_event().await? {
+            OkexWsEvent::Message(message) => Ok(message),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_request_serialization() {
+        let request = SubscribeRequest {
+            id: Some("1512".to_string()),
+            op: SubscribeOp::Subscribe,
+            args: vec![
+                SubscribeArg::new(OkexWsChannel::Order)
+                    .inst_type(InstType::Spot)
+                    .inst_id("BTC-USDT"),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "id": "1512",
+                "op": "subscribe",
+                "args": [{"channel": "orders", "instType": "SPOT", "instId": "BTC-USDT"}]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_tracks_active_subscriptions() {
+        let url = spawn_subscribe_server(None).await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.connect(WsChannelType::Public).await.unwrap();
+
+        let orders = SubscribeArg::new(OkexWsChannel::Order).inst_type(InstType::Any);
+        let account = SubscribeArg::new(OkexWsChannel::Account).ccy("BTC");
+        client
+            .subscribe(vec![orders.clone(), account.clone()])
+            .await
+            .unwrap();
+        assert_eq!(client.subscriptions.pending_count(), 1);
+
+        for _ in 0..2 {
+            let message = next_message(&mut client).await.unwrap();
+            assert!(matches!(message, OkexWsMessage::Event(_)));
+        }
+        assert!(client.subscriptions.is_active(&orders));
+        assert!(client.subscriptions.is_active(&account));
+        assert_eq!(client.subscriptions.pending_count(), 0);
+
+        client.unsubscribe(vec![account.clone()]).await.unwrap();
+        next_message(&mut client).await.unwrap();
+        assert!(client.subscriptions.is_active(&orders));
+        assert!(!client.subscriptions.is_active(&account));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_error_event() {
+        let url = spawn_subscribe_server(None).await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.connect(WsChannelType::Public).await.unwrap();
+
+        let valid = SubscribeArg::new(OkexWsChannel::Order).inst_type(InstType::Any);
+        let invalid = SubscribeArg::new(OkexWsChannel::Order)
+            .inst_type(InstType::Spot)
+            .inst_id("INVALID");
+        let failed = client.subscribe(vec![invalid.clone()]).await.unwrap();
+        client.subscribe(vec![valid.clone()]).await.unwrap();
+        assert_eq!(client.subscriptions.pending_count(), 2);
+
+        // 错误只属于第一个请求，第二个请求仍在等待确认
+        match next_message(&mut client).await {
+            Err(Error::Subscription { id, code, .. }) => {
+                assert_eq!(id, failed);
+                assert_eq!(code, "60018");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(!client.subscriptions.is_active(&invalid));
+        assert_eq!(client.subscriptions.pending_count(), 1);
+        next_message(&mut client).await.unwrap();
+        assert!(client.subscriptions.is_active(&valid));
+        assert_eq!(client.subscriptions.pending_count(), 0);
+
+        // 对应不到请求的错误原样返回，不影响订阅状态
+        let message = client
+            .handle_message(
+                r#"{"event":"error","code":"60012","msg":"Invalid request","connId":"a4d3ae55"}"#,
+            )
+            .unwrap();
+        assert!(matches!(message, OkexWsMessage::Event(_)));
+        assert!(client.subscriptions.is_active(&valid));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_subscriptions() {
+        let url = spawn_subscribe_server(Some(1)).await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.connect(WsChannelType::Public).await.unwrap();
+
+        let orders = SubscribeArg::new(OkexWsChannel::Order).inst_type(InstType::Any);
+        client.subscribe(vec![orders.clone()]).await.unwrap();
+        next_message(&mut client).await.unwrap();
+        assert!(client.subscriptions.is_active(&orders));
+
+        match client.next_event().await.unwrap() {
+            OkexWsEvent::Reconnected {
+                attempts,
+                resubscribed,
+            } => {
+                assert_eq!(attempts, 1);
+                assert_eq!(resubscribed, 1);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(!client.subscriptions.is_active(&orders));
+        next_message(&mut client).await.unwrap();
+        assert!(client.subscriptions.is_active(&orders));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_on_stalled_stream() {
+        let url = spawn_subscribe_server(None).await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.stall_timeout = Duration::from_millis(100);
+        client.connect(WsChannelType::Public).await.unwrap();
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(
+            event,
+            OkexWsEvent::Reconnected {
+                attempts: 1,
+                resubscribed: 0
+            }
+        ));
+        assert!(client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_exhausted() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.ws_channel = Some(WsChannelType::Public);
+
+        match client.reconnect().await {
+            Err(Error::ReconnectExhausted(attempts)) => assert_eq!(attempts, 3),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_measures_latency() {
+        // 回复每个 `ping`，第二次回复后推送一条数据
+        let url = spawn_ws_server(|_, mut ws| async move {
+            let mut pings = 0;
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.as_str() != "ping" {
+                    continue;
+                }
+                ws.send(Message::Text("pong".into())).await.unwrap();
+                pings += 1;
+                if pings == 2 {
+                    let push = r#"{"arg":{"channel":"account"},"data":[{"totalEq":"1"}]}"#;
+                    ws.send(Message::Text(push.into())).await.unwrap();
+                }
+            }
+        })
+        .await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.heartbeat = Heartbeat::new(Duration::from_millis(50), Duration::from_secs(1));
+        let latency = client.heartbeat.subscribe_latency();
+        client.connect(WsChannelType::Public).await.unwrap();
+
+        let message = next_message(&mut client).await.unwrap();
+        assert!(matches!(message, OkexWsMessage::Push(_)));
+        assert!(client.latency().is_some());
+        assert_eq!(*latency.borrow(), client.latency());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_missing_pong_reconnects() {
+        let url = spawn_push_server(vec![]).await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.heartbeat = Heartbeat::new(Duration::from_millis(50), Duration::from_millis(100));
+        client.connect(WsChannelType::Public).await.unwrap();
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(
+            event,
+            OkexWsEvent::Reconnected { attempts: 1, .. }
+        ));
+        assert!(!client.heartbeat.is_awaiting_pong());
+    }
+
+    /// 校验登录请求的签名，签名正确时回复 `reply_code`
+    async fn spawn_login_server(reply_code: &'static str) -> String {
+        spawn_ws_server(move |_, mut ws| async move {
+            let Some(Ok(Message::Text(text))) = ws.next().await else {
+                return;
+            };
+            let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(request["op"], "login");
+            let args = &request["args"][0];
+            let timestamp = args["timestamp"].as_str().unwrap();
+            let skew = chrono::Utc::now().timestamp() - timestamp.parse::<i64>().unwrap();
+            assert!(skew.abs() <= 30);
+            let credentials = test_config("").get_exchange(&Exchange::Okex).unwrap().into();
+            let expected = LoginArgs::new(&credentials, timestamp.to_string()).unwrap();
+            let reply = if Some(args["sign"].as_str().unwrap()) == expected.sign.as_deref() {
+                match reply_code {
+                    "0" => serde_json::json!({"event": "login", "code": "0", "msg": "", "connId": "a4d3ae55"}),
+                    code => serde_json::json!({"event": "error", "code": code, "msg": "Login failed.", "connId": "a4d3ae55"}),
+                }
+            } else {
+                serde_json::json!({"event": "error", "code": "60007", "msg": "Invalid sign", "connId": "a4d3ae55"})
+            };
+            ws.send(Message::Text(reply.to_string().into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await
+    }
+
+    #[test]
+    fn test_login_args_signature() {
+        let config = test_config("");
+        let credentials: Credentials = config.get_exchange(&Exchange::Okex).unwrap().into();
+        let args = LoginArgs::new(&credentials, "1538054050".to_string()).unwrap();
+        assert_eq!(
+            args.sign.as_deref(),
+            Some("hWubiTqyBGALQTDTv2/uXK19A0/Mlfyv8WawmsvP4Jo=")
+        );
+
+        let request = LoginRequest {
+            op: "login".to_string(),
+            args: vec![args],
+            is_testnet: true,
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "op": "login",
+                "args": [{
+                    "apiKey": "985d5b66-57ce-40fb-b714-afc0b9787083",
+                    "passphrase": "passphrase",
+                    "timestamp": "1538054050",
+                    "sign": "hWubiTqyBGALQTDTv2/uXK19A0/Mlfyv8WawmsvP4Jo="
+                }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_private_connect_logs_in() {
+        let url = spawn_login_server("0").await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.connect(WsChannelType::Private).await.unwrap();
+        assert!(client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_private_connect_login_failure() {
+        let url = spawn_login_server("60024").await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        match client.connect(WsChannelType::Private).await {
+            Err(Error::Login { reason, code, .. }) => {
+                assert_eq!(reason, LoginFailure::InvalidPassphrase);
+                assert_eq!(code, "60024");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(!client.is_connected());
+    }
+
+    /// 登录后处理交易请求：先推送一条订单频道的数据再回复；`instId` 为 `SLOW` 时不回复，
+    /// `ordId` 为 `MISSING` 时回复失败
+    async fn spawn_order_server() -> String {
+        spawn_ws_server(|_, mut ws| async move {
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["op"] == "login" {
+                    let reply = serde_json::json!({"event": "login", "code": "0", "msg": "", "connId": "a4d3ae55"});
+                    ws.send(Message::Text(reply.to_string().into())).await.unwrap();
+                    continue;
+                }
+                let args = request["args"].as_array().unwrap();
+                if args.iter().any(|arg| arg["instId"] == "SLOW") {
+                    continue;
+                }
+                let push = serde_json::json!({
+                    "arg": {"channel": "orders", "instType": "ANY"},
+                    "data": [{"instId": "BTC-USDT", "state": "live"}]
+                });
+                ws.send(Message::Text(push.to_string().into())).await.unwrap();
+                let data: Vec<_> = args
+                    .iter()
+                    .enumerate()
+                    .map(|(index, arg)| {
+                        if arg["ordId"] == "MISSING" || arg["sz"] == "0" {
+                            serde_json::json!({"ordId": "", "clOrdId": arg["clOrdId"], "sCode": "51400", "sMsg": "Order does not exist"})
+                        } else {
+                            serde_json::json!({"ordId": format!("{}", 1000 + index), "clOrdId": arg["clOrdId"], "tag": "", "ts": "1695190491421", "sCode": "0", "sMsg": ""})
+                        }
+                    })
+                    .collect();
+                let failed = data.iter().filter(|item| item["sCode"] != "0").count();
+                let code = match failed {
+                    0 => "0",
+                    n if n == data.len() => "1",
+                    _ => "2",
+                };
+                let reply = serde_json::json!({
+                    "id": request["id"], "op": request["op"], "code": code, "msg": "",
+                    "data": data, "inTime": "1695190491421339", "outTime": "1695190491423240"
+                });
+                ws.send(Message::Text(reply.to_string().into())).await.unwrap();
+            }
+        })
+        .await
+    }
+
+    async fn connect_order_client(config: &Config) -> OkexClient<'_> {
+        static HTTP_CLIENT: std::sync::LazyLock<reqwest::Client> =
+            std::sync::LazyLock::new(reqwest::Client::new);
+        let mut client = OkexClient::new(&HTTP_CLIENT, config);
+        client.connect(WsChannelType::Private).await.unwrap();
+        client
+    }
+
+    fn limit_order(inst_id: &str, sz: &str) -> Instrument {
+        use crate::schema::okex::order::{OrderType, Side, TradingMode};
+        Instrument::new(inst_id, TradingMode::Cash, Side::Buy, OrderType::Limit, sz)
+            .px("25000")
+            .cl_ord_id("b15")
+    }
+
+    #[test]
+    fn test_create_order_serialization() {
+        let request = CreateOrder::new(
+            OrderOp::AmendOrder,
+            vec![AmendOrderArgs {
+                inst_id: "BTC-USDT".to_string(),
+                ord_id: Some("2510789768709120".to_string()),
+                new_sz: Some("2".to_string()),
+                ..Default::default()
+            }],
+        )
+        .exp_time(1597026383085);
+        assert_eq!(request.id.len(), 32);
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["op"], "amend-order");
+        assert_eq!(value["expTime"], "1597026383085");
+        assert_eq!(
+            value["args"],
+            serde_json::json!([{"instId": "BTC-USDT", "ordId": "2510789768709120", "newSz": "2"}])
+        );
+
+        let order = serde_json::to_value(limit_order("BTC-USDT", "1")).unwrap();
+        assert_eq!(
+            order,
+            serde_json::json!({
+                "instId": "BTC-USDT", "tdMode": "cash", "clOrdId": "b15", "side": "buy",
+                "ordType": "limit", "sz": "1", "px": "25000"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_place_order_buffers_interleaved_messages() {
+        let url = spawn_order_server().await;
+        let config = test_config(&url);
+        let mut client = connect_order_client(&config).await;
+
+        let order = client
+            .place_order(limit_order("BTC-USDT", "1"))
+            .await
+            .unwrap();
+        assert_eq!(order.ord_id, "1000");
+        assert_eq!(order.cl_ord_id.as_deref(), Some("b15"));
+
+        // 等待响应期间收到的推送仍然由 next_event 返回
+        let message = next_message(&mut client).await.unwrap();
+        assert!(
+            matches!(message, OkexWsMessage::Push(push) if push.arg.channel == OkexWsChannel::Order)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_orders_partial_success() {
+        let url = spawn_order_server().await;
+        let config = test_config(&url);
+        let mut client = connect_order_client(&config).await;
+
+        let orders = client
+            .place_batch_orders(vec![
+                limit_order("BTC-USDT", "1"),
+                limit_order("BTC-USDT", "0"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(orders.len(), 2);
+        assert!(orders[0].is_success());
+        assert_eq!(orders[1].s_code, "51400");
+
+        assert!(client.place_batch_orders(vec![]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_amend_and_cancel_order() {
+        let url = spawn_order_server().await;
+        let config = test_config(&url);
+        let mut client = connect_order_client(&config).await;
+
+        let amended = client
+            .amend_order(AmendOrderArgs {
+                inst_id: "BTC-USDT".to_string(),
+                ord_id: Some("1000".to_string()),
+                new_px: Some("26000".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(amended.is_success());
+
+        let cancel = CancelOrderArgs {
+            inst_id: "BTC-USDT".to_string(),
+            ord_id: Some("MISSING".to_string()),
+            cl_ord_id: None,
+        };
+        match client.cancel_order(cancel).await {
+            Err(Error::Api { code, .. }) => assert_eq!(code, "51400"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_request_honors_exp_time() {
+        let url = spawn_order_server().await;
+        let config = test_config(&url);
+        let mut client = connect_order_client(&config).await;
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let expired =
+            CreateOrder::new(OrderOp::Order, vec![limit_order("BTC-USDT", "1")]).exp_time(now - 1);
+        let id = expired.id.clone();
+        assert!(matches!(
+            client.send_order_request(expired).await,
+            Err(Error::RequestTimeout(timed_out)) if timed_out == id
+        ));
+
+        let started = std::time::Instant::now();
+        let slow =
+            CreateOrder::new(OrderOp::Order, vec![limit_order("SLOW", "1")]).exp_time(now + 100);
+        assert!(matches!(
+            client.send_order_request(slow).await,
+            Err(Error::RequestTimeout(_))
+        ));
+        let elapsed = started.elapsed();
+        assert!(elapsed < Duration::from_millis(config.global_config.timeout));
+        assert!(elapsed >= Duration::from_millis(websocket::ORDER_RESPONSE_GRACE_MS));
+    }
+
+    #[tokio::test]
+    async fn test_order_requires_private_channel() {
+        let url = spawn_subscribe_server(None).await;
+        let config = test_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.connect(WsChannelType::Public).await.unwrap();
+        assert!(
+            client
+                .place_order(limit_order("BTC-USDT", "1"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_login_response_classification() {
+        let response: LoginResponse = serde_json::from_str(
+            r#"{"event":"error","code":"60006","msg":"Timestamp request expired","connId":"a4d3ae55"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            response.into_result(),
+            Err(Error::Login {
+                reason: LoginFailure::TimestampSkew,
+                ..
+            })
+        ));
+    }
+
+    /// 按 OKX 规则重新计算请求签名并与 `OK-ACCESS-SIGN` 比较
+    struct ValidSignature;
+
+    impl wiremock::Match for ValidSignature {
+        fn matches(&self, request: &wiremock::Request) -> bool {
+            let header = |name: &str| {
+                request
+                    .headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+            };
+            let (Some(timestamp), Some(sign)) =
+                (header("OK-ACCESS-TIMESTAMP"), header("OK-ACCESS-SIGN"))
+            else {
+                return false;
+            };
+            let request_path = match request.url.query() {
+                Some(query) => format!("{}?{}", request.url.path(), query),
+                None => request.url.path().to_string(),
+            };
+            let prehash = format!(
+                "{}{}{}{}",
+                timestamp,
+                request.method.as_str(),
+                request_path,
+                String::from_utf8_lossy(&request.body)
+            );
+            let mut mac =
+                Hmac::<sha2::Sha256>::new_from_slice(b"D9E2B1A4C7F3E8D5B6A9C2F1E4D7B8A3").unwrap();
+            mac.update(prehash.as_bytes());
+            general_purpose::STANDARD.encode(mac.finalize().into_bytes()) == sign
+        }
+    }
+
+    #[test]
+    fn test_rest_signature_known_answer() {
+        let config = test_config("ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let timestamp = "2020-12-08T09:08:57.715Z";
+
+        let headers = client
+            .auth_headers(
+                &Method::GET,
+                "/api/v5/account/balance?ccy=BTC",
+                "",
+                timestamp,
+            )
+            .unwrap();
+        assert_eq!(
+            headers["OK-ACCESS-SIGN"],
+            "iOE9fFTYoWQCehY8zPSXaqq3uVoGkJTBQW80AV7fBJc="
+        );
+        assert_eq!(
+            headers["OK-ACCESS-KEY"],
+            "985d5b66-57ce-40fb-b714-afc0b9787083"
+        );
+        assert_eq!(headers["OK-ACCESS-TIMESTAMP"], timestamp);
+        assert_eq!(headers["OK-ACCESS-PASSPHRASE"], "passphrase");
+        assert_eq!(headers["x-simulated-trading"], "1");
+
+        let body =
+            r#"{"instId":"BTC-USDT","tdMode":"cash","side":"buy","ordType":"market","sz":"100"}"#;
+        let headers = client
+            .auth_headers(&Method::POST, "/api/v5/trade/order", body, timestamp)
+            .unwrap();
+        assert_eq!(
+            headers["OK-ACCESS-SIGN"],
+            "H24lyWl92USukKbCzS1NKDTd3W2rlEYWIWiia8omiHA="
+        );
+    }
+
+    #[test]
+    fn test_production_omits_simulated_header() {
+        let config = test_config("ws://127.0.0.1");
+        if let Some(ExchangeConfig::Okex(mut okex)) = config.get_exchange(&Exchange::Okex) {
+            okex.credentials.use_testnet = Some(false);
+            config
+                .exchanges
+                .lock()
+                .unwrap()
+                .insert(Exchange::Okex, ExchangeConfig::Okex(okex));
+        }
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let headers = client
+            .sign_request(&Method::GET, "/api/v5/account/balance", "")
+            .unwrap();
+        assert!(!headers.contains_key("x-simulated-trading"));
+        assert_eq!(
+            client.base_url().unwrap(),
+            okx_endpoints::PROD_REST_BASE_URL
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_rest_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/balance"))
+            .and(query_param("ccy", "BTC"))
+            .and(header("OK-ACCESS-PASSPHRASE", "passphrase"))
+            .and(header("x-simulated-trading", "1"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "", "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .and(header("Content-Type", "application/json"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "", "data": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = config_with_urls(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let query = HashMap::from([("ccy".to_string(), "BTC".to_string())]);
+        let response = client
+            .send_request(
+                &Method::GET,
+                "/api/v5/account/balance",
+                Some(&query),
+                None::<&()>,
+                true,
+            )
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        let body = serde_json::json!({"instId": "BTC-USDT", "tdMode": "cash", "side": "buy", "ordType": "market", "sz": "100"});
+        let response = client
+            .send_request(
+                &Method::POST,
+                "/api/v5/trade/order",
+                None,
+                Some(&body),
+                true,
+            )
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Balance {
+        #[serde(rename = "totalEq")]
+        total_eq: String,
+    }
+
+    async fn mount_envelope(server: &MockServer, status: u16, body: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/balance"))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    async fn get_balance(server: &MockServer) -> Result<Vec<Balance>> {
+        let config = config_with_urls(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        client
+            .send_typed(
+                &Method::GET,
+                "/api/v5/account/balance",
+                None,
+                None::<&()>,
+                true,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_send_typed_success() {
+        let server = MockServer::start().await;
+        mount_envelope(
+            &server,
+            200,
+            serde_json::json!({"code": "0", "msg": "", "data": [{"totalEq": "41624.32"}]}),
+        )
+        .await;
+        let balances = get_balance(&server).await.unwrap();
+        assert_eq!(balances[0].total_eq, "41624.32");
+    }
 
     #[tokio::test]
+    async fn test_send_typed_api_errors() {
+        let cases = [
+            (429, "50011", ApiErrorKind::RateLimit),
+            (200, "51008", ApiErrorKind::InsufficientBalance),
+            (200, "51001", ApiErrorKind::InvalidInstrument),
+            (401, "50111", ApiErrorKind::Authentication),
+            (200, "59999", ApiErrorKind::Other),
+        ];
+        for (status, code, kind) in cases {
+            let server = MockServer::start().await;
+            // 出错时 `data` 的结构与成功时不同，也应当解析出错误码
+            mount_envelope(
+                &server,
+                status,
+                serde_json::json!({"code": code, "msg": "error", "data": [{"unexpected": 1}]}),
+            )
+            .await;
+            let err = get_balance(&server).await.unwrap_err();
+            assert!(matches!(&err, Error::Api { code: c, .. } if c == code));
+            assert_eq!(err.api_kind(), Some(kind));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_typed_non_envelope_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/balance"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .mount(&server)
+            .await;
+        match get_balance(&server).await {
+            Err(Error::Api { code, msg }) => {
+                assert_eq!(code, "502");
+                assert_eq!(msg, "Bad Gateway");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_partial_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/batch-orders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "2",
+                "msg": "",
+                "data": [
+                    {"clOrdId": "oktswap6", "ordId": "12345689", "tag": "", "ts": "1695190491421", "sCode": "0", "sMsg": ""},
+                    {"clOrdId": "oktswap7", "ordId": "", "tag": "", "ts": "1695190491421", "sCode": "51008", "sMsg": "Order failed. Insufficient USDT balance in account."}
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = config_with_urls(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let items: Vec<OrderData> = client
+            .send_batch(
+                &Method::POST,
+                "/api/v5/trade/batch-orders",
+                Some(&Vec::<serde_json::Value>::new()),
+            )
+            .await
+            .unwrap();
+        assert!(items[0].is_success());
+        assert!(!items[1].is_success());
+        let err = items[1].status().unwrap_err();
+        assert_eq!(err.api_kind(), Some(ApiErrorKind::InsufficientBalance));
+    }
+
+    #[tokio::test]
+    async fn test_rest_place_and_cancel_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .and(wiremock::matchers::body_json(serde_json::json!({
+                "instId": "BTC-USDT", "tdMode": "cash", "clOrdId": "b15", "side": "buy",
+                "ordType": "limit", "sz": "1", "px": "25000"
+            })))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{"clOrdId": "b15", "ordId": "312269865356374016", "tag": "", "ts": "1695190491421", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/cancel-order"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "1", "msg": "",
+                "data": [{"clOrdId": "", "ordId": "1", "ts": "1695190491421", "sCode": "51400", "sMsg": "Cancellation failed as the order has been filled, canceled or does not exist."}]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = config_with_urls(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let order = client
+            .rest_place_order(&limit_order("BTC-USDT", "1"))
+            .await
+            .unwrap();
+        assert_eq!(order.ord_id, "312269865356374016");
+
+        let cancel = CancelOrderArgs {
+            inst_id: "BTC-USDT".to_string(),
+            ord_id: Some("1".to_string()),
+            cl_ord_id: None,
+        };
+        match client.rest_cancel_order(&cancel).await {
+            Err(Error::Api { code, .. }) => assert_eq!(code, "51400"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rest_order_queries() {
+        let server = MockServer::start().await;
+        let order = serde_json::json!({
+            "instType": "SPOT", "instId": "BTC-USDT", "ordId": "312269865356374016",
+            "clOrdId": "b15", "tag": "", "px": "25000", "sz": "1", "ordType": "limit",
+            "side": "buy", "posSide": "", "tdMode": "cash", "accFillSz": "0.4",
+            "fillPx": "25000", "avgPx": "25000", "state": "partially_filled", "fee": "-0.0004",
+            "feeCcy": "BTC", "reduceOnly": "false", "cTime": "1597026383085",
+            "uTime": "1597026383085"
+        });
+        let mut unknown_type = order.clone();
+        unknown_type["ordType"] = "elp".into();
+        unknown_type["reduceOnly"] = "true".into();
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/orders-pending"))
+            .and(query_param("instId", "BTC-USDT"))
+            .and(query_param("state", "partially_filled"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({"code": "0", "msg": "", "data": [order, unknown_type]}),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/orders-history"))
+            .and(query_param("instType", "SPOT"))
+            .and(query_param("limit", "10"))
+            .and(ValidSignature)
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"code": "0", "msg": "", "data": []})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/trade/fills"))
+            .and(query_param("ordId", "312269865356374016"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{
+                    "instType": "SWAP", "instId": "BTC-USDT-SWAP", "tradeId": "123",
+                    "ordId": "312269865356374016", "clOrdId": "b16", "billId": "1111",
+                    "tag": "", "fillPx": "25000", "fillSz": "3", "side": "sell",
+                    "posSide": "short", "execType": "M", "feeCcy": "USDT", "fee": "-0.03",
+                    "fillTime": "1597026383085", "ts": "1597026383085"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = config_with_urls(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+
+        let pending = client
+            .get_pending_orders(
+                &OrderQuery::default()
+                    .inst_id("BTC-USDT")
+                    .state(OrderState::PartiallyFilled),
+            )
+            .await
+            .unwrap();
+        assert_eq!(pending[0].state, OrderState::PartiallyFilled);
+        assert_eq!(pending[0].pos_side, None);
+        assert_eq!(pending[0].acc_fill_sz, "0.4");
+        assert_eq!(
+            pending[1].ord_type,
+            crate::schema::okex::order::OrderType::Other
+        );
+        let order = quant_schema::Order::try_from(&pending[0]).unwrap();
+        assert_eq!(order.status, quant_schema::OrderStatus::PartiallyFilled);
+        assert_eq!(order.price, Some(num("25000")));
+        assert_eq!(order.remaining_quantity(), num("0.6"));
+        assert_eq!(order.client_order_id.as_deref(), Some("b15"));
+        assert!(!order.reduce_only);
+        // 未识别的订单类型不能转换为通用订单
+        assert!(matches!(
+            quant_schema::Order::try_from(&pending[1]),
+            Err(Error::Unsupported(_))
+        ));
+        let mut limit = pending[1].clone();
+        limit.ord_type = crate::schema::okex::order::OrderType::Limit;
+        assert!(quant_schema::Order::try_from(&limit).unwrap().reduce_only);
+
+        let history = client
+            .get_order_history(InstType::Spot, &OrderQuery::default().limit(10))
+            .await
+            .unwrap();
+        assert!(history.is_empty());
+
+        let fills = client
+            .get_fills(&OrderQuery::default().ord_id("312269865356374016"))
+            .await
+            .unwrap();
+        assert_eq!(fills[0].pos_side, Some(PositionSide::Short));
+        assert_eq!(fills[0].fill_sz, "3");
+        let fill = quant_schema::Fill::try_from(&fills[0]).unwrap();
+        assert_eq!(fill.side, quant_schema::Side::Sell);
+        assert_eq!(fill.fee, num("0.03"));
+        assert!(fill.is_maker);
+    }
+
+    #[tokio::test]
+    async fn test_trading_venue_over_rest() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v5/trade/order"))
+            .and(wiremock::matchers::body_json(serde_json::json!({
+                "instId": "BTC-USDT", "tdMode": "cash", "side": "buy", "ordType": "market",
+                "sz": "0.5", "tgtCcy": "base_ccy"
+            })))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{"clOrdId": "", "ordId": "312269865356374016", "tag": "", "ts": "1695190491421", "sCode": "0", "sMsg": ""}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/balance"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{"totalEq": "41624.32", "uTime": "1614846244194", "details": [
+                    {"ccy": "USDT", "eq": "1000", "cashBal": "1000", "availBal": "900", "frozenBal": "100"},
+                    {"ccy": "BTC", "eq": "0", "cashBal": "0", "availBal": "", "frozenBal": "0"}
+                ]}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v5/account/positions"))
+            .and(ValidSignature)
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": "0", "msg": "",
+                "data": [{
+                    "instType": "SWAP", "instId": "BTC-USDT-SWAP", "mgnMode": "cross",
+                    "posSide": "net", "pos": "-2", "avgPx": "25000", "upl": "12.5", "lever": "5"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = config_with_urls(&server.uri(), "ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        let venue: &mut dyn TradingVenue = &mut client;
+        assert_eq!(venue.exchange(), Exchange::Okex);
+
+        let ack = venue
+            .submit_order(&OrderRequest::market(
+                "BTC-USDT",
+                quant_schema::Side::Buy,
+                num("0.5"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(ack.order_id, "312269865356374016");
+        assert_eq!(ack.client_order_id, None);
+        assert_eq!(ack.timestamp.timestamp_millis(), 1695190491421);
+
+        let balances = venue.balances().await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].asset, "USDT");
+        assert_eq!(balances[0].total(), num("1000"));
+
+        let positions = venue.positions().await.unwrap();
+        assert_eq!(positions[0].side, quant_schema::PositionSide::Short);
+        assert_eq!(positions[0].quantity, num("2"));
+        assert_eq!(positions[0].leverage, Some("5".parse().unwrap()));
+
+        // 深度频道只能在公共连接上订阅
+        assert!(venue.subscribe_book("BTC-USDT").await.is_err());
+    }
+
+    #[test]
+    fn test_order_request_conversion() {
+        let request = OrderRequest::limit(
+            "BTC-USDT-SWAP",
+            quant_schema::Side::Sell,
+            num("3"),
+            num("25000.5"),
+        )
+        .time_in_force(quant_schema::TimeInForce::PostOnly)
+        .client_order_id("b15")
+        .reduce_only(true);
+        let instrument = Instrument::try_from(&request).unwrap();
+        assert_eq!(
+            serde_json::to_value(&instrument).unwrap(),
+            serde_json::json!({
+                "instId": "BTC-USDT-SWAP", "tdMode": "cross", "clOrdId": "b15", "side": "sell",
+                "ordType": "post_only", "sz": "3", "px": "25000.5", "reduceOnly": true
+            })
+        );
+
+        let round_trip = OrderRequest::try_from(&instrument).unwrap();
+        assert_eq!(
+            round_trip.time_in_force,
+            quant_schema::TimeInForce::PostOnly
+        );
+        assert_eq!(round_trip.price, Some(num("25000.5")));
+        assert!(round_trip.reduce_only);
+
+        let data = OrderData {
+            ord_id: String::new(),
+            cl_ord_id: Some("b15".to_string()),
+            tag: None,
+            ts: "1695190491421".to_string(),
+            s_code: "51008".to_string(),
+            s_msg: "Insufficient balance".to_string(),
+            in_time: None,
+            out_time: None,
+        };
+        let order = data.to_order(&request).unwrap();
+        assert_eq!(order.status, quant_schema::OrderStatus::Rejected);
+        assert_eq!(order.quantity, num("3"));
+
+        let mut missing_ts = data.clone();
+        missing_ts.ts = String::new();
+        assert!(missing_ts.to_ack("BTC-USDT").is_err());
+
+        let mut missing_price = request.clone();
+        missing_price.price = None;
+        assert!(Instrument::try_from(&missing_price).is_err());
+    }
+
+    #[test]
+    fn test_reconnect_delay_backoff() {
+        assert_eq!(reconnect_delay(1000, 1), Duration::from_millis(1000));
+        assert_eq!(reconnect_delay(1000, 2), Duration::from_millis(2000));
+        assert_eq!(reconnect_delay(1000, 4), Duration::from_millis(8000));
+        assert_eq!(
+            reconnect_delay(1000, 40),
+            Duration::from_millis(websocket::MAX_RECONNECT_DELAY_MS)
+        );
+    }
+
+    #[test]
+    fn test_push_data_message() {
+        let config = test_config("ws://127.0.0.1");
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        let text = r#"{"arg":{"channel":"account","uid":"44705892343619584"},"data":[{"totalEq":"41624.32"}]}"#;
+        match client.handle_message(text).unwrap() {
+            OkexWsMessage::Push(push) => {
+                assert_eq!(push.arg.channel, OkexWsChannel::Account);
+                assert_eq!(push.data.len(), 1);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_market_data_conversions() {
+        use crate::schema::okex::market::{Bar, CandleData, FundingRateData, TradeData};
+
+        let text = r#"{"arg":{"channel":"candle1H","instId":"BTC-USDT-SWAP"},"data":[["1597026383085","8533.02","8553.74","8527.17","8548.26","45247","529.5858061","4523478.3","1"]]}"#;
+        let push = match serde_json::from_str::<OkexWsMessage>(text).unwrap() {
+            OkexWsMessage::Push(push) => push,
+            other => panic!("unexpected message: {:?}", other),
+        };
+        assert_eq!(push.arg.channel, OkexWsChannel::Candle(Bar::H1));
+        assert_eq!(push.arg.channel.channel_type(), WsChannelType::Business);
+        assert_eq!(serde_json::to_value(&push.arg.channel).unwrap(), "candle1H");
+        let candle = serde_json::from_value::<CandleData>(push.data[0].clone())
+            .unwrap()
+            .to_candle("BTC-USDT-SWAP", quant_schema::MarketType::Swap, Bar::H1)
+            .unwrap();
+        assert_eq!(candle.interval, quant_schema::Interval::hours(1));
+        assert_eq!(candle.volume, num("529.5858061"));
+        assert_eq!(candle.close, num("8548.26"));
+        assert!(candle.closed);
+        assert_eq!(
+            "candle7m".parse::<OkexWsChannel>(),
+            Err("Unknown channel: candle7m".to_string())
+        );
+
+        let trade: TradeData = serde_json::from_str(
+            r#"{"instId":"BTC-USDT","tradeId":"130639474","px":"42219.9","sz":"0.12060306","side":"sell","ts":"1630048897897","count":"3"}"#,
+        )
+        .unwrap();
+        let trade = quant_schema::Trade::try_from(&trade).unwrap();
+        assert_eq!(trade.side, quant_schema::Side::Sell);
+        assert_eq!(trade.quantity, num("0.12060306"));
+
+        let funding: FundingRateData = serde_json::from_str(
+            r#"{"instType":"SWAP","instId":"BTC-USD-SWAP","fundingRate":"0.0001875391284828","fundingTime":"1700726400000","nextFundingRate":"","nextFundingTime":"1700755200000","ts":"1700724675402"}"#,
+        )
+        .unwrap();
+        let funding = quant_schema::FundingRate::try_from(&funding).unwrap();
+        assert_eq!(funding.rate, num("0.0001875391284828"));
+        assert!(funding.next_rate.is_none());
+        assert_eq!(funding.timestamp.unwrap().timestamp_millis(), 1700724675402);
+        // 没有推送时间时不用本地时间代替
+        let legacy: FundingRateData = serde_json::from_str(
+            r#"{"instType":"SWAP","instId":"BTC-USD-SWAP","fundingRate":"0.0001","fundingTime":"1700726400000"}"#,
+        )
+        .unwrap();
+        assert!(
+            quant_schema::FundingRate::try_from(&legacy)
+                .unwrap()
+                .timestamp
+                .is_none()
+        );
+        assert_eq!(
+            funding.next_funding_time.unwrap().timestamp_millis(),
+            1700755200000
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires network access to OKX"]
     async fn test_ws_login() {
         Config::load_from_file("../../config.toml").expect("Failed to load config");
         let config = USER_CONFIG.get().unwrap();
         let credentials: Credentials = config.get_exchange(&Exchange::Okex).unwrap().into();
         println!("{:?}", credentials);
-        let mut client = OkexClient {
-            http_client: &reqwest::Client::new(),
-            config: config,
-            ws_is_connected: false,
-        };
-        let (mut ws_sink, ws_reader) = client
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, config);
+        let (mut ws_sink, _ws_reader) = client
             .get_ws_stream(WsChannelType::Public)
             .await
             .expect("Failed to get WebSocket stream");
@@ -193,6 +2300,5 @@ mod test {
         let pong = ws_sink.send(Message::Ping(Bytes::from("value"))).await;
         println!("Pong response: {:?}", pong);
         assert!(pong.is_ok(), "Failed to send ping");
-        assert!(false)
     }
 }
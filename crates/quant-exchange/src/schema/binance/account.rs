@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// `GET /api/v3/account` 的响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountInfo {
+    #[serde(rename = "makerCommission")]
+    pub maker_commission: i64,
+    #[serde(rename = "takerCommission")]
+    pub taker_commission: i64,
+    #[serde(rename = "canTrade")]
+    pub can_trade: bool,
+    #[serde(rename = "canWithdraw")]
+    pub can_withdraw: bool,
+    #[serde(rename = "canDeposit")]
+    pub can_deposit: bool,
+    /// 账户信息更新时间（毫秒时间戳）
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+    /// 账户类型，如 `SPOT`
+    #[serde(rename = "accountType")]
+    pub account_type: String,
+    pub balances: Vec<Balance>,
+}
+
+/// 单个币种的余额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    /// 币种，如 `BTC`
+    pub asset: String,
+    /// 可用余额
+    pub free: String,
+    /// 冻结余额
+    pub locked: String,
+}
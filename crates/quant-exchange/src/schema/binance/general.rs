@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// `GET /api/v3/time` 的响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTime {
+    /// 服务器时间（毫秒时间戳）
+    #[serde(rename = "serverTime")]
+    pub server_time: i64,
+}
+
+/// Binance 接口出错时返回的结构
+/// * 例如：`{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiErrorResponse {
+    pub code: i64,
+    pub msg: String,
+}
+
+impl ApiErrorResponse {
+    /// 请求时间戳超出 `recvWindow`
+    pub const TIMESTAMP_OUTSIDE_RECV_WINDOW: i64 = -1021;
+
+    /// listenKey 不存在或已过期
+    pub const LISTEN_KEY_NOT_EXIST: i64 = -1125;
+}
@@ -0,0 +1,4 @@
+pub mod account;
+pub mod general;
+pub mod order;
+pub mod user_data;
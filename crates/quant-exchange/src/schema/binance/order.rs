@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// `POST /api/v3/order`（`newOrderRespType=ACK`）和 `DELETE /api/v3/order` 的响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderResponse {
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: i64,
+    /// 撤单时为本次撤单请求的 id，原订单的 id 见 `orig_client_order_id`
+    #[serde(rename = "clientOrderId")]
+    pub client_order_id: String,
+    /// 仅撤单时返回
+    #[serde(rename = "origClientOrderId")]
+    pub orig_client_order_id: Option<String>,
+    /// 交易所处理请求的时间（毫秒时间戳）
+    #[serde(rename = "transactTime")]
+    pub transact_time: Option<i64>,
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+/// `POST /api/v3/userDataStream` 的响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenKey {
+    #[serde(rename = "listenKey")]
+    pub listen_key: String,
+}
+
+/// 用户数据流推送的事件，按 `e` 字段区分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "e")]
+pub enum UserDataEvent {
+    /// 订单更新
+    #[serde(rename = "executionReport")]
+    ExecutionReport(Box<ExecutionReport>),
+
+    /// 账户余额变化后推送的最新余额
+    #[serde(rename = "outboundAccountPosition")]
+    AccountPosition(AccountPosition),
+
+    /// 充值、提币或划转导致的余额变化
+    #[serde(rename = "balanceUpdate")]
+    BalanceUpdate(BalanceUpdate),
+
+    /// listenKey 已过期，需要重新创建
+    #[serde(rename = "listenKeyExpired")]
+    ListenKeyExpired(ListenKeyExpired),
+
+    /// 暂不处理的事件
+    #[serde(other)]
+    Unknown,
+}
+
+/// 订单更新事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    /// 事件时间
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    /// 交易对，如 `BTCUSDT`
+    #[serde(rename = "s")]
+    pub symbol: String,
+    /// 客户端订单id
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    /// 订单方向：`BUY` / `SELL`
+    #[serde(rename = "S")]
+    pub side: String,
+    /// 订单类型：`LIMIT` / `MARKET` 等
+    #[serde(rename = "o")]
+    pub order_type: String,
+    /// 有效方式：`GTC` / `IOC` / `FOK`
+    #[serde(rename = "f")]
+    pub time_in_force: String,
+    /// 委托数量
+    #[serde(rename = "q")]
+    pub quantity: String,
+    /// 委托价格
+    #[serde(rename = "p")]
+    pub price: String,
+    /// 本次事件的执行类型：`NEW` / `CANCELED` / `REJECTED` / `TRADE` / `EXPIRED`
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    /// 订单当前状态：`NEW` / `PARTIALLY_FILLED` / `FILLED` / `CANCELED` 等
+    #[serde(rename = "X")]
+    pub order_status: String,
+    /// 拒绝原因
+    #[serde(rename = "r")]
+    pub reject_reason: String,
+    /// 订单id
+    #[serde(rename = "i")]
+    pub order_id: i64,
+    /// 本次成交数量
+    #[serde(rename = "l")]
+    pub last_filled_quantity: String,
+    /// 累计成交数量
+    #[serde(rename = "z")]
+    pub cumulative_filled_quantity: String,
+    /// 本次成交价格
+    #[serde(rename = "L")]
+    pub last_filled_price: String,
+    /// 手续费数量
+    #[serde(rename = "n")]
+    pub commission: String,
+    /// 手续费币种
+    #[serde(rename = "N")]
+    pub commission_asset: Option<String>,
+    /// 成交时间
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+    /// 成交id，未成交时为 `-1`
+    #[serde(rename = "t")]
+    pub trade_id: i64,
+    /// 是否为挂单方成交
+    #[serde(rename = "m")]
+    pub is_maker: bool,
+}
+
+/// 账户余额推送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountPosition {
+    /// 事件时间
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    /// 账户最后更新时间
+    #[serde(rename = "u")]
+    pub last_update_time: i64,
+    #[serde(rename = "B")]
+    pub balances: Vec<AccountBalance>,
+}
+
+/// 账户余额推送中的单个币种
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountBalance {
+    /// 币种
+    #[serde(rename = "a")]
+    pub asset: String,
+    /// 可用余额
+    #[serde(rename = "f")]
+    pub free: String,
+    /// 冻结余额
+    #[serde(rename = "l")]
+    pub locked: String,
+}
+
+/// 余额变化推送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceUpdate {
+    /// 事件时间
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    /// 币种
+    #[serde(rename = "a")]
+    pub asset: String,
+    /// 余额变化量
+    #[serde(rename = "d")]
+    pub delta: String,
+    /// 清算时间
+    #[serde(rename = "T")]
+    pub clear_time: i64,
+}
+
+/// listenKey 过期事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenKeyExpired {
+    /// 事件时间
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "listenKey")]
+    pub listen_key: String,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// `GET /api/v5/account/balance` 返回的账户信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountBalance {
+    /// 美金层面的总权益
+    #[serde(rename = "totalEq")]
+    pub total_eq: String,
+    /// 账户信息的更新时间（毫秒时间戳）
+    #[serde(rename = "uTime")]
+    pub u_time: String,
+    /// 各币种的资产详情
+    pub details: Vec<BalanceDetail>,
+}
+
+/// 单个币种的资产
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceDetail {
+    pub ccy: String,
+    /// 币种总权益
+    pub eq: String,
+    /// 币种余额
+    #[serde(rename = "cashBal")]
+    pub cash_bal: String,
+    /// 可用余额
+    #[serde(rename = "availBal")]
+    pub avail_bal: String,
+    /// 挂单冻结数量
+    #[serde(rename = "frozenBal")]
+    pub frozen_bal: String,
+}
+
+/// `GET /api/v5/account/positions` 返回的持仓
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionDetail {
+    #[serde(rename = "instType")]
+    pub inst_type: String,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    /// 保证金模式：`cross` / `isolated`
+    #[serde(rename = "mgnMode")]
+    pub mgn_mode: String,
+    /// 持仓方向：`long` / `short` / `net`，`net` 模式下 `pos` 的正负表示方向
+    #[serde(rename = "posSide")]
+    pub pos_side: String,
+    /// 持仓数量
+    pub pos: String,
+    /// 开仓均价
+    #[serde(rename = "avgPx")]
+    pub avg_px: String,
+    /// 未实现收益
+    pub upl: String,
+    /// 杠杆倍数
+    pub lever: String,
+}
@@ -1,34 +1,65 @@
+use crate::schema::okex::market::Bar;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// WebSocket 频道，序列化为 OKX 的频道名称，如 `orders`、`candle1m`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum OkexWsChannel {
     /// 订单频道
-    #[serde(rename = "orders")]
     Order,
 
     /// 账户频道
-    #[serde(rename = "account")]
     Account,
 
     /// 持仓频道
-    #[serde(rename = "positions")]
     Position,
 
     /// 账户余额和持仓频道
-    #[serde(rename = "balance_and_position")]
     BalanceAndPosition,
 
     /// 爆仓风险预警推送频道
-    #[serde(rename = "liquidation-warning")]
     LiquidationWarning,
 
     /// 账户Greeks频道（期权相关）
-    #[serde(rename = "account-greeks")]
     AccountGreeks,
+
+    /// 产品频道，产品状态或参数变化时推送
+    Instruments,
+
+    /// 400 档深度频道，首次推送全量，之后推送增量
+    Books,
+
+    /// 5 档深度频道，每次推送全量
+    Books5,
+
+    /// 1 档深度频道，逐笔推送
+    BboTbt,
+
+    /// 行情频道，最新成交价、买一卖一和 24 小时成交量
+    Tickers,
+
+    /// 交易频道，逐笔成交
+    Trades,
+
+    /// K 线频道，属于业务频道
+    Candle(Bar),
+
+    /// 资金费率频道，仅适用于永续
+    FundingRate,
+
+    /// 标记价格频道
+    MarkPrice,
+
+    /// 指数行情频道
+    IndexTickers,
+
+    /// 持仓总量频道
+    OpenInterest,
 }
 
 impl OkexWsChannel {
-    pub fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             OkexWsChannel::Order => "orders",
             OkexWsChannel::Account => "account",
@@ -36,10 +67,89 @@ impl OkexWsChannel {
             OkexWsChannel::BalanceAndPosition => "balance_and_position",
             OkexWsChannel::LiquidationWarning => "liquidation-warning",
             OkexWsChannel::AccountGreeks => "account-greeks",
+            OkexWsChannel::Instruments => "instruments",
+            OkexWsChannel::Books => "books",
+            OkexWsChannel::Books5 => "books5",
+            OkexWsChannel::BboTbt => "bbo-tbt",
+            OkexWsChannel::Tickers => "tickers",
+            OkexWsChannel::Trades => "trades",
+            OkexWsChannel::Candle(bar) => bar.channel(),
+            OkexWsChannel::FundingRate => "funding-rate",
+            OkexWsChannel::MarkPrice => "mark-price",
+            OkexWsChannel::IndexTickers => "index-tickers",
+            OkexWsChannel::OpenInterest => "open-interest",
+        }
+    }
+
+    /// 频道所属的 WebSocket 连接类型
+    pub fn channel_type(&self) -> WsChannelType {
+        match self {
+            OkexWsChannel::Order
+            | OkexWsChannel::Account
+            | OkexWsChannel::Position
+            | OkexWsChannel::BalanceAndPosition
+            | OkexWsChannel::LiquidationWarning
+            | OkexWsChannel::AccountGreeks => WsChannelType::Private,
+            OkexWsChannel::Instruments
+            | OkexWsChannel::Books
+            | OkexWsChannel::Books5
+            | OkexWsChannel::BboTbt
+            | OkexWsChannel::Tickers
+            | OkexWsChannel::Trades
+            | OkexWsChannel::FundingRate
+            | OkexWsChannel::MarkPrice
+            | OkexWsChannel::IndexTickers
+            | OkexWsChannel::OpenInterest => WsChannelType::Public,
+            OkexWsChannel::Candle(_) => WsChannelType::Business,
         }
     }
 }
 
+impl FromStr for OkexWsChannel {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let channel = match value {
+            "orders" => OkexWsChannel::Order,
+            "account" => OkexWsChannel::Account,
+            "positions" => OkexWsChannel::Position,
+            "balance_and_position" => OkexWsChannel::BalanceAndPosition,
+            "liquidation-warning" => OkexWsChannel::LiquidationWarning,
+            "account-greeks" => OkexWsChannel::AccountGreeks,
+            "instruments" => OkexWsChannel::Instruments,
+            "books" => OkexWsChannel::Books,
+            "books5" => OkexWsChannel::Books5,
+            "bbo-tbt" => OkexWsChannel::BboTbt,
+            "tickers" => OkexWsChannel::Tickers,
+            "trades" => OkexWsChannel::Trades,
+            "funding-rate" => OkexWsChannel::FundingRate,
+            "mark-price" => OkexWsChannel::MarkPrice,
+            "index-tickers" => OkexWsChannel::IndexTickers,
+            "open-interest" => OkexWsChannel::OpenInterest,
+            _ => value
+                .strip_prefix("candle")
+                .and_then(|bar| bar.parse().ok())
+                .map(OkexWsChannel::Candle)
+                .ok_or_else(|| format!("Unknown channel: {value}"))?,
+        };
+        Ok(channel)
+    }
+}
+
+impl TryFrom<String> for OkexWsChannel {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<OkexWsChannel> for String {
+    fn from(channel: OkexWsChannel) -> Self {
+        channel.as_str().to_string()
+    }
+}
+
 /// WebSocket 频道类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WsChannelType {
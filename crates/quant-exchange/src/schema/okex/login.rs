@@ -1,35 +1,63 @@
 use crate::Signature;
-use crate::error::{Error, Result};
-use quant_config::{Credentials, ExchangeConfig, USER_CONFIG};
-use quant_schema::Exchange;
+use crate::error::{Error, LoginFailure, Result};
+use quant_config::Credentials;
 use serde::{Deserialize, Serialize};
 
+/// 登录签名固定使用的请求方法和路径
+pub const LOGIN_VERIFY_PATH: &str = "/users/self/verify";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginRequest {
     pub op: String,
-    pub args: LoginArgs,
+    pub args: Vec<LoginArgs>,
     #[serde(skip)]
     pub is_testnet: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct LoginArgs {
     #[serde(rename = "apiKey")]
     pub api_key: String,
     pub passphrase: String,
+    /// Unix 时间戳（秒），与服务器时间相差超过 30 秒会被拒绝
     pub timestamp: Option<String>,
     pub sign: Option<String>,
+    #[serde(skip)]
+    secret_key: String,
+}
+
+impl LoginArgs {
+    /// 使用给定的时间戳（秒）生成签名后的登录参数
+    /// * `sign = Base64(HmacSHA256(timestamp + "GET" + "/users/self/verify", secret))`
+    pub fn new(credentials: &Credentials, timestamp: String) -> Result<Self> {
+        let mut args = Self {
+            api_key: credentials.api_key.clone(),
+            passphrase: credentials.passphrase.clone(),
+            timestamp: None,
+            sign: None,
+            secret_key: credentials.secret_key.clone(),
+        };
+        args.sign =
+            Some(args.gen_signature(&reqwest::Method::GET, LOGIN_VERIFY_PATH, "", &timestamp)?);
+        args.timestamp = Some(timestamp);
+        Ok(args)
+    }
+}
+
+impl std::fmt::Debug for LoginArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoginArgs")
+            .field("api_key", &self.api_key)
+            .field("passphrase", &"***")
+            .field("timestamp", &self.timestamp)
+            .field("sign", &self.sign)
+            .finish()
+    }
 }
 
 impl Signature for LoginArgs {
     fn secret(&self) -> Result<String> {
-        let credentials: Credentials = USER_CONFIG
-            .get()
-            .ok_or(Error::Other("USER CONFIG not initialized."))?
-            .get_exchange(&Exchange::Okex)
-            .ok_or_else(|| Error::ConfigError("Exchange::Okex not found in config"))?
-            .into();
-        Ok(credentials.secret_key.clone())
+        Ok(self.secret_key.clone())
     }
 
     fn merged_string(
@@ -44,11 +72,35 @@ impl Signature for LoginArgs {
     }
 }
 
+/// 登录结果
+/// * 成功：`{"event":"login","code":"0","msg":"","connId":"a4d3ae55"}`
+/// * 失败：`{"event":"error","code":"60009","msg":"Login failed.","connId":"a4d3ae55"}`
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
     pub event: String,
     pub code: String,
     pub msg: String,
-    #[serde(rename = "connId")]
+    #[serde(rename = "connId", default)]
     pub conn_id: String,
 }
+
+impl LoginResponse {
+    /// 成功返回 `Ok`，失败时按错误码转换为 `Error::Login`
+    pub fn into_result(self) -> Result<()> {
+        if self.event == "login" && self.code == "0" {
+            return Ok(());
+        }
+        let reason = match self.code.as_str() {
+            "60005" => LoginFailure::InvalidApiKey,
+            "60024" => LoginFailure::InvalidPassphrase,
+            "60004" | "60006" => LoginFailure::TimestampSkew,
+            "60007" => LoginFailure::InvalidSignature,
+            _ => LoginFailure::Other,
+        };
+        Err(Error::Login {
+            reason,
+            code: self.code,
+            msg: self.msg,
+        })
+    }
+}
@@ -0,0 +1,458 @@
+use crate::error::{Error, Result};
+use crate::parse_number;
+use crate::schema::okex::order::Side;
+use crate::schema::okex::subscription::InstType;
+use crate::schema::okex::trade::parse_millis;
+use quant_schema::{
+    Candle, Decimal, Exchange, FundingRate, IndexPrice, Interval, MarkPrice, MarketType,
+    OpenInterest, Price, Quantity, Symbol, Ticker, Trade,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// K 线周期，日线及以上默认按香港时间（UTC+8）对齐
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Bar {
+    #[serde(rename = "1s")]
+    S1,
+    #[serde(rename = "1m")]
+    M1,
+    #[serde(rename = "3m")]
+    M3,
+    #[serde(rename = "5m")]
+    M5,
+    #[serde(rename = "15m")]
+    M15,
+    #[serde(rename = "30m")]
+    M30,
+    #[serde(rename = "1H")]
+    H1,
+    #[serde(rename = "2H")]
+    H2,
+    #[serde(rename = "4H")]
+    H4,
+    #[serde(rename = "6H")]
+    H6,
+    #[serde(rename = "12H")]
+    H12,
+    #[serde(rename = "1D")]
+    D1,
+    #[serde(rename = "2D")]
+    D2,
+    #[serde(rename = "3D")]
+    D3,
+    #[serde(rename = "1W")]
+    W1,
+}
+
+impl Bar {
+    pub const ALL: [Bar; 15] = [
+        Bar::S1,
+        Bar::M1,
+        Bar::M3,
+        Bar::M5,
+        Bar::M15,
+        Bar::M30,
+        Bar::H1,
+        Bar::H2,
+        Bar::H4,
+        Bar::H6,
+        Bar::H12,
+        Bar::D1,
+        Bar::D2,
+        Bar::D3,
+        Bar::W1,
+    ];
+
+    /// REST 接口 `bar` 参数的取值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bar::S1 => "1s",
+            Bar::M1 => "1m",
+            Bar::M3 => "3m",
+            Bar::M5 => "5m",
+            Bar::M15 => "15m",
+            Bar::M30 => "30m",
+            Bar::H1 => "1H",
+            Bar::H2 => "2H",
+            Bar::H4 => "4H",
+            Bar::H6 => "6H",
+            Bar::H12 => "12H",
+            Bar::D1 => "1D",
+            Bar::D2 => "2D",
+            Bar::D3 => "3D",
+            Bar::W1 => "1W",
+        }
+    }
+
+    /// WebSocket 频道名称
+    pub fn channel(&self) -> &'static str {
+        match self {
+            Bar::S1 => "candle1s",
+            Bar::M1 => "candle1m",
+            Bar::M3 => "candle3m",
+            Bar::M5 => "candle5m",
+            Bar::M15 => "candle15m",
+            Bar::M30 => "candle30m",
+            Bar::H1 => "candle1H",
+            Bar::H2 => "candle2H",
+            Bar::H4 => "candle4H",
+            Bar::H6 => "candle6H",
+            Bar::H12 => "candle12H",
+            Bar::D1 => "candle1D",
+            Bar::D2 => "candle2D",
+            Bar::D3 => "candle3D",
+            Bar::W1 => "candle1W",
+        }
+    }
+
+    pub fn interval(&self) -> Interval {
+        match self {
+            Bar::S1 => Interval::seconds(1),
+            Bar::M1 => Interval::minutes(1),
+            Bar::M3 => Interval::minutes(3),
+            Bar::M5 => Interval::minutes(5),
+            Bar::M15 => Interval::minutes(15),
+            Bar::M30 => Interval::minutes(30),
+            Bar::H1 => Interval::hours(1),
+            Bar::H2 => Interval::hours(2),
+            Bar::H4 => Interval::hours(4),
+            Bar::H6 => Interval::hours(6),
+            Bar::H12 => Interval::hours(12),
+            Bar::D1 => Interval::days(1),
+            Bar::D2 => Interval::days(2),
+            Bar::D3 => Interval::days(3),
+            Bar::W1 => Interval::weeks(1),
+        }
+    }
+}
+
+impl FromStr for Bar {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        Bar::ALL
+            .into_iter()
+            .find(|bar| bar.as_str() == value)
+            .ok_or(Error::Other("Unknown candle bar"))
+    }
+}
+
+impl TryFrom<Interval> for Bar {
+    type Error = Error;
+
+    fn try_from(interval: Interval) -> Result<Self> {
+        Bar::ALL
+            .into_iter()
+            .find(|bar| bar.interval() == interval)
+            .ok_or(Error::Unsupported(
+                "OKX has no candle bar for this interval",
+            ))
+    }
+}
+
+/// 行情数据，`tickers` 频道推送和 `GET /api/v5/market/ticker` 的返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerData {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    /// 最新成交价
+    pub last: String,
+    #[serde(rename = "lastSz")]
+    pub last_sz: String,
+    #[serde(rename = "askPx")]
+    pub ask_px: String,
+    #[serde(rename = "askSz")]
+    pub ask_sz: String,
+    #[serde(rename = "bidPx")]
+    pub bid_px: String,
+    #[serde(rename = "bidSz")]
+    pub bid_sz: String,
+    /// 24 小时开盘价
+    #[serde(rename = "open24h")]
+    pub open_24h: String,
+    #[serde(rename = "high24h")]
+    pub high_24h: String,
+    #[serde(rename = "low24h")]
+    pub low_24h: String,
+    /// 24 小时成交量，币币为计价货币数量，衍生品为交易货币数量
+    #[serde(rename = "volCcy24h")]
+    pub vol_ccy_24h: String,
+    /// 24 小时成交量，币币为交易货币数量，衍生品为张数
+    #[serde(rename = "vol24h")]
+    pub vol_24h: String,
+    /// UTC 0 时开盘价
+    #[serde(rename = "sodUtc0", default)]
+    pub sod_utc0: String,
+    /// UTC+8 时开盘价
+    #[serde(rename = "sodUtc8", default)]
+    pub sod_utc8: String,
+    pub ts: String,
+}
+
+impl TickerData {
+    /// 转换为 `Ticker`，`symbol` 为该产品的信息
+    ///
+    /// OKX 不提供成交笔数和加权均价，这两项为 0；昨收价取 24 小时开盘价。
+    /// 衍生品的 24 小时成交额按最新价估算。
+    pub fn to_ticker(&self, symbol: Symbol) -> Result<Ticker> {
+        let last_price: Price = parse_number(&self.last)?;
+        let open_24h: Price = parse_number(&self.open_24h)?;
+        let (volume_24h, quote_volumn_24h) = if symbol.is_derivative() {
+            let volume: Quantity = parse_number(&self.vol_ccy_24h)?;
+            (volume, volume * last_price)
+        } else {
+            (
+                parse_number(&self.vol_24h)?,
+                parse_number(&self.vol_ccy_24h)?,
+            )
+        };
+        let price_change_24h = last_price.value() - open_24h.value();
+        let price_change_percent_24h = if open_24h.is_zero() {
+            Decimal::ZERO
+        } else {
+            price_change_24h / open_24h.value() * Decimal::ONE_HUNDRED
+        };
+        Ok(Ticker {
+            symbol,
+            exchange: "okex".to_string(),
+            timestamp: parse_millis(&self.ts)?,
+            last_price,
+            bid_price: parse_number(&self.bid_px)?,
+            ask_price: parse_number(&self.ask_px)?,
+            volume_24h,
+            quote_volumn_24h,
+            high_24h: parse_number(&self.high_24h)?,
+            low_24h: parse_number(&self.low_24h)?,
+            open_24h,
+            close_24h: last_price,
+            price_change_24h,
+            price_change_percent_24h,
+            weighted_avg_price_24h: Price::ZERO,
+            prev_close_price: open_24h,
+            count_24h: 0,
+        })
+    }
+}
+
+/// `GET /api/v5/market/history-trades` 的分页方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradePaging {
+    /// `after` / `before` 为成交ID
+    TradeId,
+    /// `after` / `before` 为毫秒时间戳
+    Timestamp,
+}
+
+impl TradePaging {
+    /// REST 接口 `type` 参数的取值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradePaging::TradeId => "1",
+            TradePaging::Timestamp => "2",
+        }
+    }
+}
+
+/// 逐笔成交，`trades` 频道推送和 `GET /api/v5/market/history-trades` 的返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeData {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "tradeId")]
+    pub trade_id: String,
+    pub px: String,
+    pub sz: String,
+    /// 吃单方向
+    pub side: Side,
+    pub ts: String,
+}
+
+impl TryFrom<&TradeData> for Trade {
+    type Error = Error;
+
+    fn try_from(trade: &TradeData) -> Result<Self> {
+        Ok(Trade {
+            exchange: Exchange::Okex,
+            symbol: trade.inst_id.clone(),
+            trade_id: trade.trade_id.clone(),
+            price: parse_number(&trade.px)?,
+            quantity: parse_number(&trade.sz)?,
+            side: trade.side.into(),
+            timestamp: parse_millis(&trade.ts)?,
+        })
+    }
+}
+
+/// K 线数据：`[开始时间, 开, 高, 低, 收, vol, volCcy, volCcyQuote, confirm]`
+/// * `vol` 币币为交易货币数量，衍生品为张数
+/// * `volCcy` 币币为计价货币数量，衍生品为交易货币数量
+/// * `confirm` 为 `1` 表示 K 线已完结
+///
+/// `candle*` 频道推送和 `GET /api/v5/market/history-candles` 的返回格式相同
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleData(pub Vec<String>);
+
+impl CandleData {
+    /// 转换为 `Candle`，推送数据中不包含产品ID和周期，需要从订阅参数中取得；
+    /// 合约的成交量取以币计的 `volCcy`，因此还需要产品类型
+    pub fn to_candle(&self, inst_id: &str, market_type: MarketType, bar: Bar) -> Result<Candle> {
+        let field = |index: usize| {
+            self.0
+                .get(index)
+                .map(String::as_str)
+                .ok_or(Error::Other("Incomplete candle returned by exchange"))
+        };
+        let volume = if market_type.is_derivative() {
+            field(6)?
+        } else {
+            field(5)?
+        };
+        Ok(Candle {
+            exchange: Exchange::Okex,
+            symbol: inst_id.to_string(),
+            interval: bar.interval(),
+            open_time: parse_millis(field(0)?)?,
+            open: parse_number(field(1)?)?,
+            high: parse_number(field(2)?)?,
+            low: parse_number(field(3)?)?,
+            close: parse_number(field(4)?)?,
+            volume: parse_number(volume)?,
+            quote_volume: parse_number(field(7)?)?,
+            closed: field(8)? == "1",
+        })
+    }
+}
+
+/// 资金费率，`funding-rate` 频道推送的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRateData {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "fundingRate")]
+    pub funding_rate: String,
+    /// 下一期预测资金费率，部分收取方式下为空字符串
+    #[serde(rename = "nextFundingRate", default)]
+    pub next_funding_rate: String,
+    #[serde(rename = "fundingTime")]
+    pub funding_time: String,
+    #[serde(rename = "nextFundingTime", default)]
+    pub next_funding_time: String,
+    /// 推送时间，旧版本推送中没有该字段
+    #[serde(default)]
+    pub ts: String,
+}
+
+impl TryFrom<&FundingRateData> for FundingRate {
+    type Error = Error;
+
+    fn try_from(data: &FundingRateData) -> Result<Self> {
+        let funding_time = parse_millis(&data.funding_time)?;
+        let next_rate = (!data.next_funding_rate.is_empty())
+            .then(|| parse_number(&data.next_funding_rate))
+            .transpose()?;
+        let next_funding_time = (!data.next_funding_time.is_empty())
+            .then(|| parse_millis(&data.next_funding_time))
+            .transpose()?;
+        let timestamp = (!data.ts.is_empty())
+            .then(|| parse_millis(&data.ts))
+            .transpose()?;
+        Ok(FundingRate {
+            exchange: Exchange::Okex,
+            symbol: data.inst_id.clone(),
+            rate: parse_number(&data.funding_rate)?,
+            next_rate,
+            funding_time,
+            next_funding_time,
+            timestamp,
+        })
+    }
+}
+
+/// 标记价格，`mark-price` 频道推送的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkPriceData {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "markPx")]
+    pub mark_px: String,
+    pub ts: String,
+}
+
+impl TryFrom<&MarkPriceData> for MarkPrice {
+    type Error = Error;
+
+    fn try_from(data: &MarkPriceData) -> Result<Self> {
+        Ok(MarkPrice {
+            exchange: Exchange::Okex,
+            symbol: data.inst_id.clone(),
+            price: parse_number(&data.mark_px)?,
+            timestamp: parse_millis(&data.ts)?,
+        })
+    }
+}
+
+/// 指数行情，`index-tickers` 频道推送的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexTickerData {
+    /// 指数，如 `BTC-USDT`
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "idxPx")]
+    pub idx_px: String,
+    #[serde(rename = "open24h", default)]
+    pub open_24h: String,
+    #[serde(rename = "high24h", default)]
+    pub high_24h: String,
+    #[serde(rename = "low24h", default)]
+    pub low_24h: String,
+    pub ts: String,
+}
+
+impl TryFrom<&IndexTickerData> for IndexPrice {
+    type Error = Error;
+
+    fn try_from(data: &IndexTickerData) -> Result<Self> {
+        Ok(IndexPrice {
+            exchange: Exchange::Okex,
+            symbol: data.inst_id.clone(),
+            price: parse_number(&data.idx_px)?,
+            timestamp: parse_millis(&data.ts)?,
+        })
+    }
+}
+
+/// 持仓总量，`open-interest` 频道推送的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenInterestData {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    /// 持仓量，按张计
+    pub oi: String,
+    /// 持仓量，按交易货币计
+    #[serde(rename = "oiCcy")]
+    pub oi_ccy: String,
+    pub ts: String,
+}
+
+impl TryFrom<&OpenInterestData> for OpenInterest {
+    type Error = Error;
+
+    fn try_from(data: &OpenInterestData) -> Result<Self> {
+        Ok(OpenInterest {
+            exchange: Exchange::Okex,
+            symbol: data.inst_id.clone(),
+            contracts: parse_number(&data.oi)?,
+            base_amount: parse_number(&data.oi_ccy)?,
+            timestamp: parse_millis(&data.ts)?,
+        })
+    }
+}
@@ -1,4 +1,9 @@
+pub mod account;
 pub mod channel;
 pub mod login;
+pub mod market;
 pub mod order;
+pub mod public;
+pub mod response;
 pub mod subscription;
+pub mod trade;
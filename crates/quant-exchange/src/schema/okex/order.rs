@@ -1,16 +1,60 @@
+use crate::error::{Error, Result};
+use crate::parse_number;
+use crate::schema::okex::trade::parse_millis;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateOrder {
-    id: String,
-    op: String,
-    args: Vec<Instrument>,
-    #[serde(rename = "expTime")]
+/// WebSocket 交易请求，`args` 按 `op` 分别为 `Instrument`、`AmendOrderArgs` 或 `CancelOrderArgs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOrder<A = Instrument> {
+    /// 消息的唯一标识，响应中会带上同样的 `id`
+    pub id: String,
+    pub op: OrderOp,
+    pub args: Vec<A>,
+    /// 请求有效截止时间（毫秒时间戳），超过该时间服务端不再处理
+    #[serde(rename = "expTime", skip_serializing_if = "Option::is_none")]
     pub exp_time: Option<String>,
 }
 
+impl<A> CreateOrder<A> {
+    /// 生成随机 `id` 的请求
+    pub fn new(op: OrderOp, args: Vec<A>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            op,
+            args,
+            exp_time: None,
+        }
+    }
+
+    /// 设置请求有效截止时间（毫秒时间戳）
+    pub fn exp_time(mut self, exp_time: i64) -> Self {
+        self.exp_time = Some(exp_time.to_string());
+        self
+    }
+}
+
+/// WebSocket 交易操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderOp {
+    /// 下单
+    #[serde(rename = "order")]
+    Order,
+
+    /// 批量下单，最多 20 个
+    #[serde(rename = "batch-orders")]
+    BatchOrders,
+
+    /// 修改订单
+    #[serde(rename = "amend-order")]
+    AmendOrder,
+
+    /// 撤单
+    #[serde(rename = "cancel-order")]
+    CancelOrder,
+}
+
 /// 具体的参数
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Instrument {
     /// 产品id，如 `BTC-USD`
     #[serde(rename = "instId")]
@@ -24,51 +68,170 @@ pub struct Instrument {
     #[serde(rename = "tdMode")]
     pub td_mode: TradingMode,
 
-    /// 保证金币种
-    #[serde(rename = "ccy")]
-    pub ccy: String,
+    /// 保证金币种，仅适用于单币种保证金模式下的全仓杠杆订单
+    #[serde(rename = "ccy", skip_serializing_if = "Option::is_none")]
+    pub ccy: Option<String>,
 
-    #[serde(rename = "clOrdId")]
+    #[serde(rename = "clOrdId", skip_serializing_if = "Option::is_none")]
     pub cl_ord_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tag: Option<String>,
 
     pub side: Side,
-    #[serde(rename = "posSide")]
+    #[serde(rename = "posSide", skip_serializing_if = "Option::is_none")]
     pub pos_side: Option<PositionSide>,
     #[serde(rename = "ordType")]
     pub ord_type: OrderType,
     /// 委托数量
     #[serde(rename = "sz")]
     pub sz: String,
-    /// 委托价格
-    #[serde(rename = "px")]
-    pub px: String,
+    /// 委托价格，仅适用于 `limit`、`post_only`、`fok`、`ioc`、`mmp`、`mmp_and_post_only`
+    #[serde(rename = "px", skip_serializing_if = "Option::is_none")]
+    pub px: Option<String>,
 
-    #[serde(rename = "pxUsd")]
+    #[serde(rename = "pxUsd", skip_serializing_if = "Option::is_none")]
     pub px_usd: Option<String>,
 
     /// 以隐含波动率进行期权下单，必须指定 `pxVol`
     ///  * 例如：`pxVol = "0.2"` 表示 20% 的隐含波动率
     /// * 如果市场预期未来价格会大起大落，IV 就会上升；
     /// * 如果市场认为未来很平稳，IV 就会降低。
-    #[serde(rename = "pxVol")]
+    #[serde(rename = "pxVol", skip_serializing_if = "Option::is_none")]
     pub px_vol: Option<String>,
-    #[serde(rename = "reduceOnly")]
+    #[serde(rename = "reduceOnly", skip_serializing_if = "Option::is_none")]
     pub reduce_only: Option<bool>,
-    #[serde(rename = "tgtCcy")]
+    #[serde(rename = "tgtCcy", skip_serializing_if = "Option::is_none")]
     pub tgt_ccy: Option<QuantityUnit>,
-    #[serde(rename = "banAmend")]
+    #[serde(rename = "banAmend", skip_serializing_if = "Option::is_none")]
     pub ban_amend: Option<bool>,
-    #[serde(rename = "quickMgnType")]
+    #[serde(rename = "quickMgnType", skip_serializing_if = "Option::is_none")]
     pub quick_mgn_type: Option<bool>,
-    #[serde(rename = "stpId")]
+    #[serde(rename = "stpId", skip_serializing_if = "Option::is_none")]
     pub stp_id: Option<String>,
 
-    #[serde(rename = "stpMode")]
+    #[serde(rename = "stpMode", skip_serializing_if = "Option::is_none")]
     pub stp_mode: Option<SelfTradePreventionMode>,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+impl Instrument {
+    /// 必填参数，其余参数通过对应的方法设置
+    pub fn new(
+        inst_id: impl Into<String>,
+        td_mode: TradingMode,
+        side: Side,
+        ord_type: OrderType,
+        sz: impl Into<String>,
+    ) -> Self {
+        Self {
+            inst_id: inst_id.into(),
+            td_mode,
+            ccy: None,
+            cl_ord_id: None,
+            tag: None,
+            side,
+            pos_side: None,
+            ord_type,
+            sz: sz.into(),
+            px: None,
+            px_usd: None,
+            px_vol: None,
+            reduce_only: None,
+            tgt_ccy: None,
+            ban_amend: None,
+            quick_mgn_type: None,
+            stp_id: None,
+            stp_mode: None,
+        }
+    }
+
+    pub fn px(mut self, px: impl Into<String>) -> Self {
+        self.px = Some(px.into());
+        self
+    }
+
+    pub fn cl_ord_id(mut self, cl_ord_id: impl Into<String>) -> Self {
+        self.cl_ord_id = Some(cl_ord_id.into());
+        self
+    }
+
+    pub fn pos_side(mut self, pos_side: PositionSide) -> Self {
+        self.pos_side = Some(pos_side);
+        self
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+}
+
+impl TryFrom<&quant_schema::OrderRequest> for Instrument {
+    type Error = Error;
+
+    /// 币币使用现金模式，衍生品（产品ID多于两段，如 `BTC-USDT-SWAP`）使用全仓模式；
+    /// 币币市价单的数量按交易货币计算
+    fn try_from(order: &quant_schema::OrderRequest) -> Result<Self> {
+        let is_derivative = order.symbol.split('-').count() > 2;
+        let td_mode = if is_derivative {
+            TradingMode::Cross
+        } else {
+            TradingMode::Cash
+        };
+        let mut instrument = Instrument::new(
+            &order.symbol,
+            td_mode,
+            order.side.into(),
+            OrderType::from_schema(order.order_type, order.time_in_force),
+            order.quantity.to_string(),
+        );
+        if order.order_type == quant_schema::OrderType::Limit {
+            let price = order
+                .price
+                .ok_or(Error::Other("Limit orders require a price"))?;
+            instrument = instrument.px(price.to_string());
+        } else if !is_derivative {
+            instrument.tgt_ccy = Some(QuantityUnit::BaseCurrency);
+        }
+        if let Some(cl_ord_id) = &order.client_order_id {
+            instrument = instrument.cl_ord_id(cl_ord_id);
+        }
+        if order.reduce_only {
+            instrument = instrument.reduce_only(true);
+        }
+        Ok(instrument)
+    }
+}
+
+impl TryFrom<&Instrument> for quant_schema::OrderRequest {
+    type Error = Error;
+
+    /// 按计价货币下的市价单无法表示为交易货币数量，返回错误
+    fn try_from(instrument: &Instrument) -> Result<Self> {
+        if instrument.tgt_ccy == Some(QuantityUnit::QuoteCurrency) {
+            return Err(Error::Other("Quote currency quantities are not supported"));
+        }
+        let (order_type, time_in_force) = instrument.ord_type.to_schema()?;
+        let price = match (order_type, instrument.px.as_deref()) {
+            (quant_schema::OrderType::Limit, Some(px)) => Some(parse_number(px)?),
+            (quant_schema::OrderType::Limit, None) => {
+                return Err(Error::Other("Limit orders require a price"));
+            }
+            (quant_schema::OrderType::Market, _) => None,
+        };
+        Ok(quant_schema::OrderRequest {
+            symbol: instrument.inst_id.clone(),
+            side: instrument.side.into(),
+            order_type,
+            time_in_force,
+            quantity: parse_number(&instrument.sz)?,
+            price,
+            client_order_id: instrument.cl_ord_id.clone(),
+            reduce_only: instrument.reduce_only.unwrap_or(false),
+        })
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TradingMode {
     /// 逐仓模式 - 每个持仓独立管理保证金
     #[serde(rename = "isolated")]
@@ -88,7 +251,7 @@ pub enum TradingMode {
     SpotIsolated,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Side {
     #[serde(rename = "buy")]
     Buy,
@@ -97,7 +260,25 @@ pub enum Side {
     Sell,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+impl From<quant_schema::Side> for Side {
+    fn from(side: quant_schema::Side) -> Self {
+        match side {
+            quant_schema::Side::Buy => Side::Buy,
+            quant_schema::Side::Sell => Side::Sell,
+        }
+    }
+}
+
+impl From<Side> for quant_schema::Side {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Buy => quant_schema::Side::Buy,
+            Side::Sell => quant_schema::Side::Sell,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum PositionSide {
     #[default]
     #[serde(rename = "net")]
@@ -110,7 +291,7 @@ pub enum PositionSide {
     Short,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderType {
     /// 市价单 - 立即按市场价格成交
     /// * 立即成交，按当前市场最优价格
@@ -152,9 +333,49 @@ pub enum OrderType {
     /// 做市商保护+只做Maker - 期权专用组合
     #[serde(rename = "mmp_and_post_only")]
     MmpAndPostOnly,
+
+    /// 未识别的订单类型，如 `op_fok`、`elp`
+    /// * 只用于解析交易所返回的订单，不能用于下单
+    #[serde(other)]
+    Other,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl OrderType {
+    /// 由通用的订单类型和有效期得到 OKX 的订单类型
+    pub fn from_schema(
+        order_type: quant_schema::OrderType,
+        time_in_force: quant_schema::TimeInForce,
+    ) -> Self {
+        use quant_schema::TimeInForce;
+
+        match (order_type, time_in_force) {
+            (quant_schema::OrderType::Market, _) => OrderType::Market,
+            (quant_schema::OrderType::Limit, TimeInForce::Gtc) => OrderType::Limit,
+            (quant_schema::OrderType::Limit, TimeInForce::Ioc) => OrderType::ImmediateOrCancel,
+            (quant_schema::OrderType::Limit, TimeInForce::Fok) => OrderType::FillOrKill,
+            (quant_schema::OrderType::Limit, TimeInForce::PostOnly) => OrderType::PostOnly,
+        }
+    }
+
+    /// 对应的通用订单类型和有效期
+    /// * `optimal_limit_ioc` 视为市价单
+    /// * 做市商保护类订单按普通限价单 / 只做 Maker 单处理
+    /// * 未识别的订单类型无法对应，返回 `Error::Unsupported`
+    pub fn to_schema(&self) -> Result<(quant_schema::OrderType, quant_schema::TimeInForce)> {
+        use quant_schema::{OrderType as Kind, TimeInForce};
+
+        Ok(match self {
+            OrderType::Market | OrderType::OptimalLimitIoc => (Kind::Market, TimeInForce::Ioc),
+            OrderType::Limit | OrderType::MarketMakerProtection => (Kind::Limit, TimeInForce::Gtc),
+            OrderType::PostOnly | OrderType::MmpAndPostOnly => (Kind::Limit, TimeInForce::PostOnly),
+            OrderType::FillOrKill => (Kind::Limit, TimeInForce::Fok),
+            OrderType::ImmediateOrCancel => (Kind::Limit, TimeInForce::Ioc),
+            OrderType::Other => return Err(Error::Unsupported("Unrecognized OKX order type")),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QuantityUnit {
     /// 基础货币 - 交易的主要币种 (如 BTC)
     #[serde(rename = "base_ccy")]
@@ -165,7 +386,7 @@ pub enum QuantityUnit {
     QuoteCurrency,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SelfTradePreventionMode {
     /// 取消挂单方 - 当发生自成交时，取消作为Maker的订单
     #[serde(rename = "cancel_maker")]
@@ -180,29 +401,108 @@ pub enum SelfTradePreventionMode {
     CancelBoth,
 }
 
+/// 修改订单的参数，`ordId` 与 `clOrdId` 必须传一个
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AmendOrderArgs {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+
+    /// 修改失败时是否自动撤单
+    #[serde(rename = "cxlOnFail", skip_serializing_if = "Option::is_none")]
+    pub cxl_on_fail: Option<bool>,
+
+    #[serde(rename = "ordId", skip_serializing_if = "Option::is_none")]
+    pub ord_id: Option<String>,
+
+    #[serde(rename = "clOrdId", skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+
+    /// 用户自定义的修改事件id
+    #[serde(rename = "reqId", skip_serializing_if = "Option::is_none")]
+    pub req_id: Option<String>,
+
+    /// 修改后的数量（包含已成交数量）
+    #[serde(rename = "newSz", skip_serializing_if = "Option::is_none")]
+    pub new_sz: Option<String>,
+
+    /// 修改后的价格
+    #[serde(rename = "newPx", skip_serializing_if = "Option::is_none")]
+    pub new_px: Option<String>,
+}
+
+/// 撤单的参数，`ordId` 与 `clOrdId` 必须传一个
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CancelOrderArgs {
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+
+    #[serde(rename = "ordId", skip_serializing_if = "Option::is_none")]
+    pub ord_id: Option<String>,
+
+    #[serde(rename = "clOrdId", skip_serializing_if = "Option::is_none")]
+    pub cl_ord_id: Option<String>,
+}
+
+/// WebSocket 交易请求的响应
+/// * 例如：`{"id":"1512","op":"order","code":"0","msg":"","data":[{"ordId":"12345689","sCode":"0",...}],"inTime":"1695190491421339","outTime":"1695190491423240"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateOrderResponse {
     pub id: String,
-    pub op: String,
+    pub op: OrderOp,
 
     pub code: String,
     pub msg: String,
     pub data: Vec<OrderData>,
+
+    /// 网关收到请求的时间（微秒时间戳）
+    #[serde(rename = "inTime")]
+    pub in_time: Option<String>,
+
+    /// 网关发出响应的时间（微秒时间戳）
+    #[serde(rename = "outTime")]
+    pub out_time: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `OrderData` 只包含下单结果，订单的其余信息来自请求本身
+impl OrderData {
+    /// 交易所的确认信息
+    pub fn to_ack(&self, symbol: &str) -> Result<quant_schema::OrderAck> {
+        Ok(quant_schema::OrderAck {
+            exchange: quant_schema::Exchange::Okex,
+            symbol: symbol.to_string(),
+            order_id: self.ord_id.clone(),
+            client_order_id: self.cl_ord_id.clone().filter(|id| !id.is_empty()),
+            timestamp: parse_millis(&self.ts)?,
+        })
+    }
+
+    /// 转换为通用的订单，`sCode` 不为 `0` 时状态为 `OrderStatus::Rejected`
+    pub fn to_order(&self, request: &quant_schema::OrderRequest) -> Result<quant_schema::Order> {
+        let mut order = quant_schema::Order::new(request, &self.to_ack(&request.symbol)?);
+        if self.s_code != "0" {
+            order.status = quant_schema::OrderStatus::Rejected;
+        }
+        Ok(order)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderData {
     #[serde(rename = "ordId")]
     pub ord_id: String,
     #[serde(rename = "clOrdId")]
     pub cl_ord_id: Option<String>,
     pub tag: Option<String>,
+    #[serde(default)]
     pub ts: String,
     #[serde(rename = "sCode")]
     pub s_code: String,
     #[serde(rename = "sMsg")]
     pub s_msg: String,
+    /// 仅 WebSocket 下单返回
     #[serde(rename = "inTime")]
-    pub in_time: String,
+    pub in_time: Option<String>,
+    /// 仅 WebSocket 下单返回
     #[serde(rename = "outTime")]
-    pub out_time: String,
+    pub out_time: Option<String>,
 }
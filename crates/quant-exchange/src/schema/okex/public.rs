@@ -0,0 +1,172 @@
+use crate::error::{Error, Result};
+use crate::schema::okex::subscription::InstType;
+use crate::{parse_number, timestamp_from_millis};
+use chrono::{DateTime, Utc};
+use quant_schema::{BookUpdate, CanonicalSymbol, Exchange, MarketType, Price, Quantity, Symbol};
+use serde::{Deserialize, Serialize};
+
+/// 产品信息，`GET /api/v5/public/instruments` 和 `instruments` 频道推送的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentInfo {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    /// 交易品种，如 `BTC-USD`，仅适用于交割、永续、期权
+    #[serde(rename = "instFamily", default)]
+    pub inst_family: String,
+    /// 交易货币币种，仅适用于币币、币币杠杆
+    #[serde(rename = "baseCcy", default)]
+    pub base_ccy: String,
+    /// 计价货币币种，仅适用于币币、币币杠杆
+    #[serde(rename = "quoteCcy", default)]
+    pub quote_ccy: String,
+    /// 盈亏结算和保证金币种，仅适用于交割、永续、期权
+    #[serde(rename = "settleCcy", default)]
+    pub settle_ccy: String,
+    /// 合约面值，仅适用于交割、永续、期权
+    #[serde(rename = "ctVal", default)]
+    pub ct_val: String,
+    /// 合约面值计价币种
+    #[serde(rename = "ctValCcy", default)]
+    pub ct_val_ccy: String,
+    /// 上线时间（毫秒时间戳）
+    #[serde(rename = "listTime", default)]
+    pub list_time: String,
+    /// 到期时间（毫秒时间戳），仅适用于交割和期权
+    #[serde(rename = "expTime", default)]
+    pub exp_time: String,
+    /// 下单价格精度
+    #[serde(rename = "tickSz")]
+    pub tick_sz: String,
+    /// 下单数量精度
+    #[serde(rename = "lotSz")]
+    pub lot_sz: String,
+    /// 最小下单数量
+    #[serde(rename = "minSz")]
+    pub min_sz: String,
+    /// 产品状态：`live` / `suspend` / `preopen` / `test`
+    pub state: String,
+}
+
+/// 深度数据，`books`、`books5`、`bbo-tbt` 频道推送的数据
+/// 每档深度为 `[价格, 数量, 已弃用, 订单数量]`，`books` 增量数据中数量为 0 表示删除该档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookData {
+    pub asks: Vec<[String; 4]>,
+    pub bids: Vec<[String; 4]>,
+    /// 仅 `books5` 推送
+    #[serde(rename = "instId", default)]
+    pub inst_id: String,
+    /// 推送时间（毫秒时间戳）
+    pub ts: String,
+    /// 前 25 档的 CRC32 校验值，仅 `books` 推送
+    #[serde(default)]
+    pub checksum: Option<i32>,
+    /// 上一次推送的 `seqId`，全量数据为 -1
+    #[serde(rename = "prevSeqId", default)]
+    pub prev_seq_id: Option<i64>,
+    #[serde(rename = "seqId", default)]
+    pub seq_id: Option<i64>,
+}
+
+impl BookData {
+    /// 买方深度，价格从高到低
+    pub fn bids(&self) -> Result<Vec<(Price, Quantity)>> {
+        parse_levels(&self.bids)
+    }
+
+    /// 卖方深度，价格从低到高
+    pub fn asks(&self) -> Result<Vec<(Price, Quantity)>> {
+        parse_levels(&self.asks)
+    }
+
+    pub fn timestamp(&self) -> Result<DateTime<Utc>> {
+        let millis = self
+            .ts
+            .parse()
+            .map_err(|_| Error::Other("Invalid ts returned by exchange"))?;
+        timestamp_from_millis(millis)
+    }
+
+    /// 转换为通用的深度变化，`books5`、`bbo-tbt` 和 `books` 的全量推送为快照；没有 `seqId` 时以推送时间为序列号
+    pub fn book_update(&self, inst_id: &str, snapshot: bool) -> Result<BookUpdate> {
+        let timestamp = self.timestamp()?;
+        Ok(BookUpdate {
+            exchange: Exchange::Okex,
+            symbol: inst_id.to_string(),
+            sequence: self.seq_id.unwrap_or(timestamp.timestamp_millis()),
+            snapshot,
+            bids: self.bids()?,
+            asks: self.asks()?,
+            timestamp,
+        })
+    }
+}
+
+fn parse_levels(levels: &[[String; 4]]) -> Result<Vec<(Price, Quantity)>> {
+    levels
+        .iter()
+        .map(|[price, size, ..]| Ok((parse_number(price)?, parse_number(size)?)))
+        .collect()
+}
+
+impl From<InstType> for MarketType {
+    /// `ANY` 只用于订阅，按币币处理
+    fn from(inst_type: InstType) -> Self {
+        match inst_type {
+            InstType::Spot | InstType::Any => MarketType::Spot,
+            InstType::Margin => MarketType::Margin,
+            InstType::Swap => MarketType::Swap,
+            InstType::Futures => MarketType::Futures,
+            InstType::Option => MarketType::Option,
+        }
+    }
+}
+
+impl TryFrom<&InstrumentInfo> for Symbol {
+    type Error = Error;
+
+    /// 衍生品没有 `baseCcy` / `quoteCcy`，从 `instFamily`（如 `BTC-USDT`）中取得
+    fn try_from(info: &InstrumentInfo) -> Result<Self> {
+        let (base_asset, quote_asset) = if info.base_ccy.is_empty() {
+            let (base, quote) = info.inst_family.split_once('-').ok_or(Error::Other(
+                "Instrument has neither baseCcy nor instFamily",
+            ))?;
+            (base.to_string(), quote.to_string())
+        } else {
+            (info.base_ccy.clone(), info.quote_ccy.clone())
+        };
+        let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        let contract_value = non_empty(&info.ct_val)
+            .map(|value| parse_number(&value))
+            .transpose()?;
+        let expiry = non_empty(&info.exp_time)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| Error::Other("Invalid expTime returned by exchange"))
+                    .and_then(timestamp_from_millis)
+            })
+            .transpose()?;
+        let market_type = info.inst_type.into();
+        let name = CanonicalSymbol::from_native(&Exchange::Okex, market_type, &info.inst_id)?;
+        Ok(Symbol {
+            name: name.to_string(),
+            base_asset,
+            quote_asset,
+            exchange: Exchange::Okex,
+            exchange_symbol: info.inst_id.clone(),
+            market_type,
+            status: info.state.clone(),
+            min_qty: parse_number(&info.min_sz)?,
+            lot_size: parse_number(&info.lot_sz)?,
+            tick_size: parse_number(&info.tick_sz)?,
+            // OKX 没有最小下单金额限制
+            min_notional: quant_schema::Decimal::ZERO,
+            contract_value,
+            settle_asset: non_empty(&info.settle_ccy),
+            expiry,
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::{Error, Result};
+use crate::schema::okex::order::{CreateOrderResponse, OrderData};
+use serde::{Deserialize, Serialize};
+
+/// REST 接口统一的返回结构
+/// * 例如：`{"code":"0","msg":"","data":[...]}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiResponse<T> {
+    /// `0` 表示成功；批量接口中 `1` 表示全部失败，`2` 表示部分成功
+    pub code: String,
+    pub msg: String,
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+}
+
+impl<T> ApiResponse<T> {
+    /// 全部成功
+    pub const SUCCESS: &str = "0";
+    /// 操作全部失败，具体原因见每一项的 `sCode` / `sMsg`
+    pub const ALL_FAILED: &str = "1";
+    /// 批量操作部分成功
+    pub const PARTIAL_SUCCESS: &str = "2";
+
+    /// `code` 为 `0` 时返回 `data`，否则返回 `Error::Api`
+    pub fn into_result(self) -> Result<Vec<T>> {
+        if self.code == Self::SUCCESS {
+            Ok(self.data)
+        } else {
+            Err(Error::Api {
+                code: self.code,
+                msg: self.msg,
+            })
+        }
+    }
+}
+
+impl From<CreateOrderResponse> for ApiResponse<OrderData> {
+    fn from(response: CreateOrderResponse) -> Self {
+        Self {
+            code: response.code,
+            msg: response.msg,
+            data: response.data,
+        }
+    }
+}
+
+impl<T: ItemStatus> ApiResponse<T> {
+    /// 批量接口：`code` 为 `0` / `1` / `2` 时返回每一项，由调用方检查各自的 `sCode`
+    ///
+    /// `code` 为 `1` 或 `2` 但 `data` 为空时，仍按接口错误处理。
+    pub fn into_items(self) -> Result<Vec<T>> {
+        let per_item = self.code == Self::ALL_FAILED || self.code == Self::PARTIAL_SUCCESS;
+        if self.code == Self::SUCCESS || (per_item && !self.data.is_empty()) {
+            Ok(self.data)
+        } else {
+            Err(Error::Api {
+                code: self.code,
+                msg: self.msg,
+            })
+        }
+    }
+}
+
+/// 批量接口中每一项的处理结果
+pub trait ItemStatus {
+    fn s_code(&self) -> &str;
+
+    fn s_msg(&self) -> &str;
+
+    fn is_success(&self) -> bool {
+        self.s_code() == "0"
+    }
+
+    /// 成功返回 `Ok`，失败时把 `sCode` / `sMsg` 转换为 `Error::Api`
+    fn status(&self) -> Result<()> {
+        if self.is_success() {
+            Ok(())
+        } else {
+            Err(Error::Api {
+                code: self.s_code().to_string(),
+                msg: self.s_msg().to_string(),
+            })
+        }
+    }
+}
+
+impl ItemStatus for OrderData {
+    fn s_code(&self) -> &str {
+        &self.s_code
+    }
+
+    fn s_msg(&self) -> &str {
+        &self.s_msg
+    }
+}
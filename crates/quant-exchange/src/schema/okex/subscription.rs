@@ -1,6 +1,184 @@
+use crate::schema::okex::channel::OkexWsChannel;
+use crate::schema::okex::order::CreateOrderResponse;
+use serde::{Deserialize, Serialize};
+
 pub enum Channel {
     /// 无需登陆
     Public,
     /// 需要登陆
     Private,
 }
+
+/// 订阅操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubscribeOp {
+    #[serde(rename = "subscribe")]
+    Subscribe,
+
+    #[serde(rename = "unsubscribe")]
+    Unsubscribe,
+}
+
+/// 订阅/取消订阅请求
+/// * 例如：`{"id":"1512","op":"subscribe","args":[{"channel":"orders","instType":"ANY"}]}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribeRequest {
+    /// 消息的唯一标识，返回的事件中会带上同样的 `id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub op: SubscribeOp,
+    pub args: Vec<SubscribeArg>,
+}
+
+/// 产品类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InstType {
+    /// 币币
+    #[serde(rename = "SPOT")]
+    Spot,
+
+    /// 币币杠杆
+    #[serde(rename = "MARGIN")]
+    Margin,
+
+    /// 永续合约
+    #[serde(rename = "SWAP")]
+    Swap,
+
+    /// 交割合约
+    #[serde(rename = "FUTURES")]
+    Futures,
+
+    /// 期权
+    #[serde(rename = "OPTION")]
+    Option,
+
+    /// 全部产品类型
+    #[serde(rename = "ANY")]
+    Any,
+}
+
+impl InstType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            InstType::Spot => "SPOT",
+            InstType::Margin => "MARGIN",
+            InstType::Swap => "SWAP",
+            InstType::Futures => "FUTURES",
+            InstType::Option => "OPTION",
+            InstType::Any => "ANY",
+        }
+    }
+}
+
+/// 订阅参数，同时用于标识一个订阅
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubscribeArg {
+    pub channel: OkexWsChannel,
+
+    #[serde(rename = "instType", skip_serializing_if = "Option::is_none")]
+    pub inst_type: Option<InstType>,
+
+    /// 交易品种，如 `BTC-USD`，适用于交割/永续/期权
+    #[serde(rename = "instFamily", skip_serializing_if = "Option::is_none")]
+    pub inst_family: Option<String>,
+
+    /// 产品id，如 `BTC-USDT`
+    #[serde(rename = "instId", skip_serializing_if = "Option::is_none")]
+    pub inst_id: Option<String>,
+
+    /// 币种，如 `BTC`，用于 `account` 等频道
+    #[serde(rename = "ccy", skip_serializing_if = "Option::is_none")]
+    pub ccy: Option<String>,
+}
+
+impl SubscribeArg {
+    pub fn new(channel: OkexWsChannel) -> Self {
+        Self {
+            channel,
+            inst_type: None,
+            inst_family: None,
+            inst_id: None,
+            ccy: None,
+        }
+    }
+
+    pub fn inst_type(mut self, inst_type: InstType) -> Self {
+        self.inst_type = Some(inst_type);
+        self
+    }
+
+    pub fn inst_family(mut self, inst_family: impl Into<String>) -> Self {
+        self.inst_family = Some(inst_family.into());
+        self
+    }
+
+    pub fn inst_id(mut self, inst_id: impl Into<String>) -> Self {
+        self.inst_id = Some(inst_id.into());
+        self
+    }
+
+    pub fn ccy(mut self, ccy: impl Into<String>) -> Self {
+        self.ccy = Some(ccy.into());
+        self
+    }
+}
+
+/// 服务端返回的事件类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    #[serde(rename = "subscribe")]
+    Subscribe,
+
+    #[serde(rename = "unsubscribe")]
+    Unsubscribe,
+
+    #[serde(rename = "error")]
+    Error,
+
+    #[serde(rename = "login")]
+    Login,
+
+    /// 服务升级等通知，连接将在 30 秒后断开
+    #[serde(rename = "notice")]
+    Notice,
+
+    /// 连接数量等其它事件
+    #[serde(other)]
+    Other,
+}
+
+/// 订阅相关的事件
+/// * 成功：`{"id":"1512","event":"subscribe","arg":{"channel":"orders","instType":"ANY"},"connId":"a4d3ae55"}`
+/// * 失败：`{"id":"1512","event":"error","code":"60012","msg":"Invalid request","connId":"a4d3ae55"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribeEvent {
+    pub id: Option<String>,
+    pub event: EventKind,
+    pub arg: Option<SubscribeArg>,
+    pub code: Option<String>,
+    pub msg: Option<String>,
+    #[serde(rename = "connId")]
+    pub conn_id: Option<String>,
+}
+
+/// 频道推送的数据，`data` 的具体结构由频道决定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushData {
+    pub arg: SubscribeArg,
+
+    /// 深度频道的 `snapshot` / `update`
+    pub action: Option<String>,
+
+    pub data: Vec<serde_json::Value>,
+}
+
+/// WebSocket 收到的文本消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OkexWsMessage {
+    Event(SubscribeEvent),
+    /// 下单、改单、撤单请求的响应
+    Order(CreateOrderResponse),
+    Push(PushData),
+}
@@ -0,0 +1,299 @@
+use crate::error::{Error, Result};
+use crate::schema::okex::order::{OrderType, PositionSide, Side, TradingMode};
+use crate::schema::okex::subscription::InstType;
+use crate::{parse_number, timestamp_from_millis};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// 订单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderState {
+    /// 等待成交
+    #[serde(rename = "live")]
+    Live,
+
+    /// 部分成交
+    #[serde(rename = "partially_filled")]
+    PartiallyFilled,
+
+    /// 完全成交
+    #[serde(rename = "filled")]
+    Filled,
+
+    /// 撤单成功
+    #[serde(rename = "canceled")]
+    Canceled,
+
+    /// 做市商保护机制导致的自动撤单
+    #[serde(rename = "mmp_canceled")]
+    MmpCanceled,
+}
+
+/// 订单信息，`/api/v5/trade/order`、`orders-pending`、`orders-history` 的返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderDetail {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "ordId")]
+    pub ord_id: String,
+    #[serde(rename = "clOrdId", default)]
+    pub cl_ord_id: String,
+    #[serde(default)]
+    pub tag: String,
+    /// 委托价格，市价单为空字符串
+    pub px: String,
+    pub sz: String,
+    #[serde(rename = "ordType")]
+    pub ord_type: OrderType,
+    pub side: Side,
+    /// 币币订单为空
+    #[serde(rename = "posSide", default, deserialize_with = "empty_as_none")]
+    pub pos_side: Option<PositionSide>,
+    #[serde(rename = "tdMode")]
+    pub td_mode: TradingMode,
+    /// 累计成交数量
+    #[serde(rename = "accFillSz")]
+    pub acc_fill_sz: String,
+    /// 最新成交价格，没有成交时为空字符串
+    #[serde(rename = "fillPx", default)]
+    pub fill_px: String,
+    /// 成交均价，没有成交时为空字符串
+    #[serde(rename = "avgPx", default)]
+    pub avg_px: String,
+    pub state: OrderState,
+    /// 手续费，平台收取为负数
+    #[serde(default)]
+    pub fee: String,
+    #[serde(rename = "feeCcy", default)]
+    pub fee_ccy: String,
+    /// 是否只减仓，`true` / `false`
+    #[serde(rename = "reduceOnly", default)]
+    pub reduce_only: String,
+    /// 创建时间（毫秒时间戳）
+    #[serde(rename = "cTime")]
+    pub c_time: String,
+    /// 更新时间（毫秒时间戳）
+    #[serde(rename = "uTime")]
+    pub u_time: String,
+}
+
+/// 成交明细，`/api/v5/trade/fills` 的返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    #[serde(rename = "instType")]
+    pub inst_type: InstType,
+    #[serde(rename = "instId")]
+    pub inst_id: String,
+    #[serde(rename = "tradeId")]
+    pub trade_id: String,
+    #[serde(rename = "ordId")]
+    pub ord_id: String,
+    #[serde(rename = "clOrdId", default)]
+    pub cl_ord_id: String,
+    #[serde(rename = "billId")]
+    pub bill_id: String,
+    #[serde(default)]
+    pub tag: String,
+    #[serde(rename = "fillPx")]
+    pub fill_px: String,
+    #[serde(rename = "fillSz")]
+    pub fill_sz: String,
+    pub side: Side,
+    #[serde(rename = "posSide", default, deserialize_with = "empty_as_none")]
+    pub pos_side: Option<PositionSide>,
+    /// 流动性方向：`T` 为 taker，`M` 为 maker
+    #[serde(rename = "execType")]
+    pub exec_type: String,
+    #[serde(rename = "feeCcy")]
+    pub fee_ccy: String,
+    pub fee: String,
+    /// 成交时间（毫秒时间戳）
+    #[serde(rename = "fillTime", default)]
+    pub fill_time: String,
+    pub ts: String,
+}
+
+impl From<OrderState> for quant_schema::OrderStatus {
+    fn from(state: OrderState) -> Self {
+        match state {
+            OrderState::Live => quant_schema::OrderStatus::New,
+            OrderState::PartiallyFilled => quant_schema::OrderStatus::PartiallyFilled,
+            OrderState::Filled => quant_schema::OrderStatus::Filled,
+            OrderState::Canceled | OrderState::MmpCanceled => quant_schema::OrderStatus::Canceled,
+        }
+    }
+}
+
+impl TryFrom<&OrderDetail> for quant_schema::Order {
+    type Error = Error;
+
+    /// 按计价货币下的币币市价单，`quantity` 为计价货币数量
+    fn try_from(detail: &OrderDetail) -> Result<Self> {
+        let (order_type, time_in_force) = detail.ord_type.to_schema()?;
+        let price = match order_type {
+            quant_schema::OrderType::Limit => Some(parse_number(&detail.px)?),
+            quant_schema::OrderType::Market => None,
+        };
+        let filled_quantity: quant_schema::Quantity = parse_number(&detail.acc_fill_sz)?;
+        let average_price = if !filled_quantity.is_zero() {
+            Some(parse_number(&detail.avg_px)?)
+        } else {
+            None
+        };
+        Ok(quant_schema::Order {
+            exchange: quant_schema::Exchange::Okex,
+            symbol: detail.inst_id.clone(),
+            order_id: detail.ord_id.clone(),
+            client_order_id: Some(detail.cl_ord_id.clone()).filter(|id| !id.is_empty()),
+            side: detail.side.into(),
+            order_type,
+            time_in_force,
+            quantity: parse_number(&detail.sz)?,
+            price,
+            filled_quantity,
+            average_price,
+            status: detail.state.into(),
+            reduce_only: detail.reduce_only == "true",
+            created_at: parse_millis(&detail.c_time)?,
+            updated_at: parse_millis(&detail.u_time)?,
+        })
+    }
+}
+
+impl TryFrom<&Fill> for quant_schema::Fill {
+    type Error = Error;
+
+    /// OKX 的手续费以负数表示扣除，这里转换为正数表示成本
+    fn try_from(fill: &Fill) -> Result<Self> {
+        let timestamp = if fill.fill_time.is_empty() {
+            &fill.ts
+        } else {
+            &fill.fill_time
+        };
+        Ok(quant_schema::Fill {
+            exchange: quant_schema::Exchange::Okex,
+            symbol: fill.inst_id.clone(),
+            trade_id: fill.trade_id.clone(),
+            order_id: fill.ord_id.clone(),
+            client_order_id: Some(fill.cl_ord_id.clone()).filter(|id| !id.is_empty()),
+            side: fill.side.into(),
+            price: parse_number(&fill.fill_px)?,
+            quantity: parse_number(&fill.fill_sz)?,
+            fee: -parse_number(&fill.fee)?,
+            fee_asset: fill.fee_ccy.clone(),
+            is_maker: fill.exec_type == "M",
+            timestamp: parse_millis(timestamp)?,
+        })
+    }
+}
+
+/// 订单和成交查询的筛选条件，未设置的条件不会发送
+/// * `after` / `before` 为 `ordId`（成交查询为 `billId`）分页游标
+/// * `limit` 最大为 100，默认 100
+#[derive(Debug, Clone, Default)]
+pub struct OrderQuery {
+    pub inst_type: Option<InstType>,
+    pub inst_id: Option<String>,
+    pub ord_type: Option<OrderType>,
+    /// 仅用于订单查询
+    pub state: Option<OrderState>,
+    /// 仅用于成交查询
+    pub ord_id: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl OrderQuery {
+    pub fn inst_type(mut self, inst_type: InstType) -> Self {
+        self.inst_type = Some(inst_type);
+        self
+    }
+
+    pub fn inst_id(mut self, inst_id: impl Into<String>) -> Self {
+        self.inst_id = Some(inst_id.into());
+        self
+    }
+
+    pub fn ord_type(mut self, ord_type: OrderType) -> Self {
+        self.ord_type = Some(ord_type);
+        self
+    }
+
+    pub fn state(mut self, state: OrderState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    pub fn ord_id(mut self, ord_id: impl Into<String>) -> Self {
+        self.ord_id = Some(ord_id.into());
+        self
+    }
+
+    pub fn after(mut self, after: impl Into<String>) -> Self {
+        self.after = Some(after.into());
+        self
+    }
+
+    pub fn before(mut self, before: impl Into<String>) -> Self {
+        self.before = Some(before.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// 转换为请求的查询参数
+    pub fn to_params(&self) -> HashMap<String, String> {
+        let mut params = HashMap::new();
+        let mut insert = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                params.insert(key.to_string(), value);
+            }
+        };
+        insert("instType", self.inst_type.map(enum_str));
+        insert("instId", self.inst_id.clone());
+        insert("ordType", self.ord_type.map(enum_str));
+        insert("state", self.state.map(enum_str));
+        insert("ordId", self.ord_id.clone());
+        insert("after", self.after.clone());
+        insert("before", self.before.clone());
+        insert("limit", self.limit.map(|limit| limit.to_string()));
+        params
+    }
+}
+
+/// 枚举序列化后的字符串，如 `InstType::Spot` 为 `SPOT`
+fn enum_str<T: Serialize>(value: T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(value)) => value,
+        _ => String::new(),
+    }
+}
+
+pub(crate) fn parse_millis(value: &str) -> Result<DateTime<Utc>> {
+    let millis = value
+        .parse()
+        .map_err(|_| Error::Other("Invalid timestamp returned by exchange"))?;
+    timestamp_from_millis(millis)
+}
+
+/// OKX 用空字符串表示没有值
+fn empty_as_none<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    match value.as_deref() {
+        None | Some("") => Ok(None),
+        Some(value) => serde_json::from_value(serde_json::Value::String(value.to_string()))
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
@@ -0,0 +1,58 @@
+//! 测试用的本地 WebSocket 服务和数据构造
+
+use futures_util::{SinkExt, StreamExt};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+
+pub fn num<T: std::str::FromStr>(value: &str) -> T
+where
+    T::Err: std::fmt::Debug,
+{
+    value.parse().unwrap()
+}
+
+pub type ServerWs = WebSocketStream<TcpStream>;
+
+/// 启动本地 WebSocket 服务，每个连接交给 `handler` 处理，参数为连接序号（从 0 开始）
+pub async fn spawn_ws_server<F, Fut>(handler: F) -> String
+where
+    F: Fn(usize, ServerWs) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        let mut index = 0;
+        while let Ok((stream, _)) = listener.accept().await {
+            let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+                continue;
+            };
+            tokio::spawn(handler(index, ws));
+            index += 1;
+        }
+    });
+    format!("ws://{}", addr)
+}
+
+/// 第 n 个连接依次推送 `connections[n]` 中的消息，之后保持连接直到客户端断开
+pub async fn spawn_push_server(connections: Vec<Vec<(Duration, String)>>) -> String {
+    let connections = Arc::new(connections);
+    spawn_ws_server(move |index, mut ws| {
+        let connections = connections.clone();
+        async move {
+            for (delay, message) in connections.get(index).cloned().unwrap_or_default() {
+                tokio::time::sleep(delay).await;
+                if ws.send(Message::Text(message.into())).await.is_err() {
+                    return;
+                }
+            }
+            while let Some(Ok(_)) = ws.next().await {}
+        }
+    })
+    .await
+}
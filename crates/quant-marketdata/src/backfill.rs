@@ -0,0 +1,422 @@
+use crate::error::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use quant_exchange::error::ApiErrorKind;
+use quant_exchange::okex::OkexClient;
+use quant_exchange::schema::okex::market::{Bar, TradePaging};
+use quant_exchange::schema::okex::subscription::InstType;
+use quant_schema::{Candle, Exchange, Trade};
+use quant_storage::MarketDataStore;
+use std::future::Future;
+use std::ops::Range;
+use std::time::Duration;
+
+/// OKX 历史数据接口每页最多返回的条数
+const PAGE_LIMIT: usize = 100;
+
+/// 通过 REST 回补历史 K 线和成交，写入 quant-storage
+///
+/// 按时间从早到晚分段下载，每段下载完成后写入存储，中断后再次运行会从存储中最后一条数据之后继续。
+/// 每个请求之前等待 `request_interval`，触发限速时按指数退避重试。
+pub struct Backfill<'a> {
+    client: &'a OkexClient<'a>,
+    /// 两次请求的间隔，默认 100ms（历史接口限速 20次/2s）
+    request_interval: Duration,
+    /// 触发限速后的最大重试次数，默认 5 次
+    max_retries: u32,
+    /// 成交每段的时长，默认 10 分钟
+    trade_window: TimeDelta,
+}
+
+impl<'a> Backfill<'a> {
+    pub fn new(client: &'a OkexClient<'a>) -> Self {
+        Self {
+            client,
+            request_interval: Duration::from_millis(100),
+            max_retries: 5,
+            trade_window: TimeDelta::minutes(10),
+        }
+    }
+
+    pub fn request_interval(mut self, request_interval: Duration) -> Self {
+        self.request_interval = request_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn trade_window(mut self, trade_window: TimeDelta) -> Self {
+        self.trade_window = trade_window;
+        self
+    }
+
+    /// 回补开始时间在 `range` 内的已完结 K 线，`inst_type` 决定合约的成交量口径；返回写入的数量
+    pub async fn candles<S: MarketDataStore>(
+        &self,
+        store: &mut S,
+        inst_type: InstType,
+        inst_id: &str,
+        bar: Bar,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<usize> {
+        let interval = bar.interval();
+        let resume = store
+            .last_candle_time(&Exchange::Okex, inst_id, interval)?
+            .map(|last| last + interval.duration());
+        let mut start = resume.map_or(range.start, |resume| resume.max(range.start));
+        // 每段正好一页
+        let window = interval.duration() * PAGE_LIMIT as i32;
+        let mut written = 0;
+        while start < range.end {
+            let end = (start + window).min(range.end);
+            let data = self
+                .request(|| {
+                    self.client.get_history_candles(
+                        inst_id,
+                        bar,
+                        Some(end.timestamp_millis()),
+                        Some(start.timestamp_millis() - 1),
+                        Some(PAGE_LIMIT as u32),
+                    )
+                })
+                .await?;
+            let mut candles = Vec::with_capacity(data.len());
+            for candle in &data {
+                let candle = candle.to_candle(inst_id, inst_type.into(), bar)?;
+                if candle.closed && candle.open_time >= start && candle.open_time < end {
+                    candles.push(candle);
+                }
+            }
+            candles.sort_by_key(|candle: &Candle| candle.open_time);
+            written += store.write_candles(&candles)?;
+            start = end;
+        }
+        Ok(written)
+    }
+
+    /// 回补成交时间在 `range` 内的成交；返回写入的数量
+    pub async fn trades<S: MarketDataStore>(
+        &self,
+        store: &mut S,
+        inst_id: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<usize> {
+        // 每段完整写入，同一毫秒的成交不会被拆开，可以从最后一笔的下一毫秒继续
+        let resume = store
+            .last_trade_time(&Exchange::Okex, inst_id)?
+            .map(|last| last + TimeDelta::milliseconds(1));
+        let mut start = resume.map_or(range.start, |resume| resume.max(range.start));
+        let mut written = 0;
+        while start < range.end {
+            let end = (start + self.trade_window).min(range.end);
+            let trades = self.trade_window_pages(inst_id, start, end).await?;
+            written += store.write_trades(&trades)?;
+            start = end;
+        }
+        Ok(written)
+    }
+
+    /// 下载 `[start, end)` 内的全部成交，按时间升序返回
+    ///
+    /// 第一页按时间取 `end` 之前的成交，之后以上一页最后（最早）一笔的成交ID作为 `after` 按成交ID翻页，
+    /// 同一毫秒内超过一页的成交也不会遗漏；取到早于 `start` 的成交后停止。
+    async fn trade_window_pages(
+        &self,
+        inst_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Trade>> {
+        let before = start.timestamp_millis() - 1;
+        let mut after = end.timestamp_millis();
+        let mut paging = TradePaging::Timestamp;
+        let mut trades = Vec::new();
+        loop {
+            let data = self
+                .request(|| {
+                    self.client.get_history_trades(
+                        inst_id,
+                        paging,
+                        Some(after),
+                        (paging == TradePaging::Timestamp).then_some(before),
+                        Some(PAGE_LIMIT as u32),
+                    )
+                })
+                .await?;
+            let mut reached_start = false;
+            for trade in &data {
+                let trade = Trade::try_from(trade)?;
+                if trade.timestamp < start {
+                    reached_start = true;
+                } else if trade.timestamp < end {
+                    trades.push(trade);
+                }
+            }
+            let Some(last) = data.last() else { break };
+            if reached_start || data.len() < PAGE_LIMIT {
+                break;
+            }
+            paging = TradePaging::TradeId;
+            after = last
+                .trade_id
+                .parse()
+                .map_err(|_| quant_exchange::error::Error::Other("OKX tradeId is not numeric"))?;
+        }
+        // 各页依次更早，页内按时间倒序
+        trades.reverse();
+        Ok(trades)
+    }
+
+    /// 按请求间隔发送请求，触发限速时指数退避重试
+    async fn request<T, F, Fut>(&self, send: F) -> Result<Vec<T>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = quant_exchange::error::Result<Vec<T>>>,
+    {
+        let mut attempt = 0;
+        loop {
+            tokio::time::sleep(self.request_interval).await;
+            match send().await {
+                Err(err)
+                    if err.api_kind() == Some(ApiErrorKind::RateLimit)
+                        && attempt < self.max_retries =>
+                {
+                    attempt += 1;
+                    let backoff = self.request_interval.max(Duration::from_millis(100));
+                    tokio::time::sleep(backoff * 2u32.pow(attempt)).await;
+                }
+                result => return Ok(result?),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::config;
+    use quant_storage::MemoryStore;
+    use std::collections::HashMap;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    /// 2023-11-14 22:13:00 UTC，整分钟
+    const BASE: i64 = 1_699_999_980_000;
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(millis).unwrap()
+    }
+
+    /// 按 `after` / `before` / `limit` 从 `rows` 中倒序取一页，`rows` 为 (时间, 数据)
+    ///
+    /// `type=1` 时 `after` / `before` 与数据中的 `tradeId` 比较，否则与时间比较
+    fn page(request: &Request, rows: &[(i64, serde_json::Value)]) -> ResponseTemplate {
+        let params: HashMap<_, _> = request.url.query_pairs().into_owned().collect();
+        let param = |name: &str| params.get(name).map(|value| value.parse::<i64>().unwrap());
+        let after = param("after").unwrap_or(i64::MAX);
+        let before = param("before").unwrap_or(i64::MIN);
+        let limit = param("limit").unwrap_or(100) as usize;
+        let by_trade_id = param("type") == Some(1);
+        let data: Vec<_> = rows
+            .iter()
+            .rev()
+            .filter(|(ts, row)| {
+                let key = match by_trade_id {
+                    true => row["tradeId"].as_str().unwrap().parse().unwrap(),
+                    false => *ts,
+                };
+                key < after && key > before
+            })
+            .take(limit)
+            .map(|(_, row)| row.clone())
+            .collect();
+        ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({"code": "0", "msg": "", "data": data}))
+    }
+
+    #[tokio::test]
+    async fn test_candles_resume() {
+        let server = MockServer::start().await;
+        // 150 根 1 分钟 K 线，最后一根未完结
+        let rows: Vec<_> = (0..150)
+            .map(|i| {
+                let ts = BASE + i * 60_000;
+                let confirm = if i == 149 { "0" } else { "1" };
+                let row = serde_json::json!([
+                    ts.to_string(),
+                    "100",
+                    "101",
+                    "99",
+                    "100.5",
+                    "10",
+                    "10",
+                    "1005",
+                    confirm
+                ]);
+                (ts, row)
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/history-candles"))
+            .respond_with(move |request: &Request| page(request, &rows))
+            .mount(&server)
+            .await;
+        let config = config(&server.uri());
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let backfill = Backfill::new(&client).request_interval(Duration::ZERO);
+        let mut store = MemoryStore::new();
+
+        let written = backfill
+            .candles(
+                &mut store,
+                InstType::Spot,
+                "BTC-USDT",
+                Bar::M1,
+                at(BASE)..at(BASE + 120 * 60_000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(written, 120);
+        // 两段，每段一页
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        let written = backfill
+            .candles(
+                &mut store,
+                InstType::Spot,
+                "BTC-USDT",
+                Bar::M1,
+                at(BASE)..at(BASE + 200 * 60_000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(written, 29);
+        let interval = Bar::M1.interval();
+        assert_eq!(
+            store
+                .last_candle_time(&Exchange::Okex, "BTC-USDT", interval)
+                .unwrap(),
+            Some(at(BASE + 148 * 60_000))
+        );
+        let candles = store
+            .candles(
+                &Exchange::Okex,
+                "BTC-USDT",
+                interval,
+                at(BASE)..at(BASE + 200 * 60_000),
+            )
+            .unwrap();
+        assert_eq!(candles.len(), 149);
+        assert!(candles.iter().all(|candle| candle.closed));
+    }
+
+    #[tokio::test]
+    async fn test_trades_paging() {
+        let server = MockServer::start().await;
+        // 150 笔成交，每毫秒 3 笔，跨越分页边界
+        let rows: Vec<_> = (0..150)
+            .map(|i| {
+                let ts = BASE + i / 3;
+                let row = serde_json::json!({
+                    "instId": "BTC-USDT", "tradeId": i.to_string(), "px": "100", "sz": "1",
+                    "side": "buy", "ts": ts.to_string()
+                });
+                (ts, row)
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/history-trades"))
+            .respond_with(move |request: &Request| page(request, &rows))
+            .mount(&server)
+            .await;
+        let config = config(&server.uri());
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let backfill = Backfill::new(&client).request_interval(Duration::ZERO);
+        let mut store = MemoryStore::new();
+
+        let written = backfill
+            .trades(&mut store, "BTC-USDT", at(BASE)..at(BASE + 60_000))
+            .await
+            .unwrap();
+        assert_eq!(written, 150);
+        let trades = store
+            .trades(&Exchange::Okex, "BTC-USDT", at(BASE)..at(BASE + 60_000))
+            .unwrap();
+        let mut ids: Vec<i64> = trades
+            .iter()
+            .map(|trade| trade.trade_id.parse().unwrap())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (0..150).collect::<Vec<_>>());
+
+        // 已经回补过的部分不会再请求
+        let requests = server.received_requests().await.unwrap().len();
+        let written = backfill
+            .trades(&mut store, "BTC-USDT", at(BASE)..at(BASE + 50))
+            .await
+            .unwrap();
+        assert_eq!(written, 0);
+        assert_eq!(server.received_requests().await.unwrap().len(), requests);
+    }
+
+    #[tokio::test]
+    async fn test_trades_same_millisecond() {
+        let server = MockServer::start().await;
+        // 250 笔成交在同一毫秒，前后各有 5 笔
+        let rows: Vec<_> = (0..260)
+            .map(|i| {
+                let ts = match i {
+                    0..5 => BASE + i,
+                    5..255 => BASE + 10,
+                    _ => BASE + 20 + i,
+                };
+                let row = serde_json::json!({
+                    "instId": "BTC-USDT", "tradeId": (1000 + i).to_string(), "px": "100",
+                    "sz": "1", "side": "sell", "ts": ts.to_string()
+                });
+                (ts, row)
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/api/v5/market/history-trades"))
+            .respond_with(move |request: &Request| page(request, &rows))
+            .mount(&server)
+            .await;
+        let config = config(&server.uri());
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+        let backfill = Backfill::new(&client).request_interval(Duration::ZERO);
+        let mut store = MemoryStore::new();
+
+        let written = backfill
+            .trades(&mut store, "BTC-USDT", at(BASE + 1)..at(BASE + 60_000))
+            .await
+            .unwrap();
+        assert_eq!(written, 259);
+        let trades = store
+            .trades(&Exchange::Okex, "BTC-USDT", at(BASE)..at(BASE + 60_000))
+            .unwrap();
+        let ids: Vec<i64> = trades
+            .iter()
+            .map(|trade| trade.trade_id.parse().unwrap())
+            .collect();
+        assert_eq!(ids, (1001..1260).collect::<Vec<_>>());
+
+        let requests = server.received_requests().await.unwrap();
+        let types: Vec<_> = requests
+            .iter()
+            .map(|request| {
+                let (_, value) = request
+                    .url
+                    .query_pairs()
+                    .find(|(name, _)| name == "type")
+                    .unwrap();
+                value.into_owned()
+            })
+            .collect();
+        assert_eq!(types, ["2", "1", "1"]);
+    }
+}
@@ -0,0 +1,435 @@
+use crate::error::{Error, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use quant_schema::{
+    Bar, BarType, Decimal, Exchange, Interval, MarketEvent, Price, Quantity, Trade,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// 时间 K 线的检查周期，用于在没有成交时按时关闭 K 线
+const ADVANCE_INTERVAL_MS: u64 = 1000;
+
+/// 由逐笔成交聚合 K 线，支持时间、笔数、成交量和成交额 K 线
+///
+/// 时间 K 线以收到的最大成交时间减去 `lateness` 为水位线，结束时间不晚于水位线的 K 线才会关闭，
+/// 因此迟到不超过 `lateness` 的成交仍会计入所属的 K 线；K 线关闭后才到达的成交会被丢弃并计数。
+/// 开启 `fill_gaps` 后，没有成交的时间段会以上一根 K 线的收盘价补齐。
+///
+/// 笔数、成交量和成交额 K 线按到达顺序累计，达到阈值的那笔成交不会被拆分。
+#[derive(Debug)]
+pub struct BarAggregator {
+    bar_type: BarType,
+    lateness: TimeDelta,
+    fill_gaps: bool,
+    states: HashMap<(Exchange, String), SymbolState>,
+    late_trades: u64,
+}
+
+#[derive(Debug, Default)]
+struct SymbolState {
+    /// 时间 K 线：尚未关闭的 K 线，以开始时间为键
+    open: BTreeMap<DateTime<Utc>, BarBuilder>,
+    /// 时间 K 线：最后一根已关闭 K 线的结束时间和收盘价
+    last_closed: Option<(DateTime<Utc>, Price)>,
+    /// 收到的最大成交时间
+    latest: Option<DateTime<Utc>>,
+    /// 其它 K 线：正在累计的 K 线
+    current: Option<BarBuilder>,
+}
+
+#[derive(Debug, Clone)]
+struct BarBuilder {
+    first_trade: DateTime<Utc>,
+    last_trade: DateTime<Utc>,
+    open: Price,
+    high: Price,
+    low: Price,
+    close: Price,
+    volume: Quantity,
+    quote_volume: Decimal,
+    trade_count: u64,
+}
+
+impl BarBuilder {
+    fn new(trade: &Trade) -> Self {
+        Self {
+            first_trade: trade.timestamp,
+            last_trade: trade.timestamp,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.quantity,
+            quote_volume: trade.price * trade.quantity,
+            trade_count: 1,
+        }
+    }
+
+    /// 迟到的成交只影响最高、最低价和成交量，开盘价和收盘价按成交时间取
+    fn add(&mut self, trade: &Trade) {
+        if trade.timestamp < self.first_trade {
+            self.first_trade = trade.timestamp;
+            self.open = trade.price;
+        }
+        if trade.timestamp >= self.last_trade {
+            self.last_trade = trade.timestamp;
+            self.close = trade.price;
+        }
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.volume += trade.quantity;
+        self.quote_volume += trade.price * trade.quantity;
+        self.trade_count += 1;
+    }
+
+    fn is_full(&self, bar_type: &BarType) -> bool {
+        match bar_type {
+            BarType::Time(_) => false,
+            BarType::Tick(count) => self.trade_count >= *count,
+            BarType::Volume(volume) => self.volume >= *volume,
+            BarType::Dollar(value) => self.quote_volume >= *value,
+        }
+    }
+
+    fn build(
+        self,
+        key: &(Exchange, String),
+        bar_type: BarType,
+        open_time: DateTime<Utc>,
+        close_time: DateTime<Utc>,
+    ) -> Bar {
+        let vwap = if self.volume.is_zero() {
+            self.close
+        } else {
+            Price::new(self.quote_volume / self.volume.value())
+        };
+        Bar {
+            exchange: key.0.clone(),
+            symbol: key.1.clone(),
+            bar_type,
+            open_time,
+            close_time,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            quote_volume: self.quote_volume,
+            vwap,
+            trade_count: self.trade_count,
+        }
+    }
+}
+
+impl BarAggregator {
+    /// 时间周期或阈值为 0 时返回 `InvalidBarType`
+    pub fn new(bar_type: BarType) -> Result<Self> {
+        let valid = match bar_type {
+            BarType::Time(interval) => interval.as_secs() > 0,
+            BarType::Tick(count) => count > 0,
+            BarType::Volume(volume) => volume > Quantity::ZERO,
+            BarType::Dollar(value) => value > Decimal::ZERO,
+        };
+        if !valid {
+            return Err(Error::InvalidBarType(bar_type));
+        }
+        Ok(Self {
+            bar_type,
+            lateness: TimeDelta::zero(),
+            fill_gaps: false,
+            states: HashMap::new(),
+            late_trades: 0,
+        })
+    }
+
+    /// 时间 K 线等待迟到成交的时长，默认为 0
+    pub fn lateness(mut self, lateness: TimeDelta) -> Self {
+        self.lateness = lateness;
+        self
+    }
+
+    /// 时间 K 线是否为没有成交的时间段补齐 K 线，默认不补齐
+    pub fn fill_gaps(mut self, fill_gaps: bool) -> Self {
+        self.fill_gaps = fill_gaps;
+        self
+    }
+
+    pub fn bar_type(&self) -> BarType {
+        self.bar_type
+    }
+
+    /// 因所属 K 线已关闭而被丢弃的成交数量
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+
+    /// 处理一笔成交，返回因此关闭的 K 线，按时间排序
+    pub fn on_trade(&mut self, trade: &Trade) -> Vec<Bar> {
+        let key = (trade.exchange.clone(), trade.symbol.clone());
+        let bar_type = self.bar_type;
+        let state = self.states.entry(key.clone()).or_default();
+        let BarType::Time(interval) = bar_type else {
+            let builder = match state.current.as_mut() {
+                Some(builder) => {
+                    builder.add(trade);
+                    builder
+                }
+                None => state.current.insert(BarBuilder::new(trade)),
+            };
+            if !builder.is_full(&bar_type) {
+                return Vec::new();
+            }
+            let builder = state.current.take().unwrap();
+            let (open_time, close_time) = (builder.first_trade, builder.last_trade);
+            return vec![builder.build(&key, bar_type, open_time, close_time)];
+        };
+
+        let start = interval.bucket_start(trade.timestamp);
+        if let Some((closed_until, _)) = state.last_closed
+            && start < closed_until
+        {
+            self.late_trades += 1;
+            return Vec::new();
+        }
+        state
+            .open
+            .entry(start)
+            .and_modify(|builder| builder.add(trade))
+            .or_insert_with(|| BarBuilder::new(trade));
+        let latest = state
+            .latest
+            .map_or(trade.timestamp, |latest| latest.max(trade.timestamp));
+        state.latest = Some(latest);
+        close_time_bars(
+            state,
+            &key,
+            interval,
+            latest - self.lateness,
+            self.fill_gaps,
+        )
+    }
+
+    /// 时钟推进到 `now`，关闭结束时间不晚于 `now - lateness` 的时间 K 线；其它 K 线类型不受影响
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Vec<Bar> {
+        let BarType::Time(interval) = self.bar_type else {
+            return Vec::new();
+        };
+        let mut bars = Vec::new();
+        for (key, state) in self.states.iter_mut() {
+            bars.extend(close_time_bars(
+                state,
+                key,
+                interval,
+                now - self.lateness,
+                self.fill_gaps,
+            ));
+        }
+        bars.sort_by_key(|bar| bar.open_time);
+        bars
+    }
+
+    /// 从成交广播（`MarketDataService::subscribe_trades`）中聚合 K 线并广播，成交通道关闭时返回
+    ///
+    /// 处理不及时丢失的成交会被跳过；时间 K 线每秒按本地时钟检查一次是否需要关闭。
+    pub async fn run(
+        mut self,
+        mut trades: broadcast::Receiver<MarketEvent<Trade>>,
+        bars: broadcast::Sender<Bar>,
+    ) {
+        let mut ticker = tokio::time::interval(Duration::from_millis(ADVANCE_INTERVAL_MS));
+        loop {
+            let closed = tokio::select! {
+                trade = trades.recv() => match trade {
+                    Ok(event) => self.on_trade(&event.data),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                },
+                _ = ticker.tick() => self.advance(Utc::now()),
+            };
+            for bar in closed {
+                let _ = bars.send(bar);
+            }
+        }
+    }
+}
+
+/// 关闭结束时间不晚于 `watermark` 的 K 线，按需补齐中间没有成交的 K 线
+fn close_time_bars(
+    state: &mut SymbolState,
+    key: &(Exchange, String),
+    interval: Interval,
+    watermark: DateTime<Utc>,
+    fill_gaps: bool,
+) -> Vec<Bar> {
+    let bar_type = BarType::Time(interval);
+    let duration = interval.duration();
+    let mut bars = Vec::new();
+    let fill_until = |bars: &mut Vec<Bar>, state: &mut SymbolState, until: DateTime<Utc>| {
+        let Some((mut start, close)) = state.last_closed.filter(|_| fill_gaps) else {
+            return;
+        };
+        while start + duration <= until {
+            bars.push(Bar {
+                exchange: key.0.clone(),
+                symbol: key.1.clone(),
+                bar_type,
+                open_time: start,
+                close_time: start + duration,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: Quantity::ZERO,
+                quote_volume: Decimal::ZERO,
+                vwap: close,
+                trade_count: 0,
+            });
+            start += duration;
+            state.last_closed = Some((start, close));
+        }
+    };
+
+    while let Some(entry) = state.open.first_entry() {
+        let start = *entry.key();
+        if start + duration > watermark {
+            break;
+        }
+        let builder = entry.remove();
+        fill_until(&mut bars, state, start);
+        let close = builder.close;
+        bars.push(builder.build(key, bar_type, start, start + duration));
+        state.last_closed = Some((start + duration, close));
+    }
+    let mut until = interval.bucket_start(watermark);
+    if let Some((start, _)) = state.open.first_key_value() {
+        until = until.min(*start);
+    }
+    fill_until(&mut bars, state, until);
+    bars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::num;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn trade(seconds: i64, price: &str, quantity: &str) -> Trade {
+        Trade {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: seconds.to_string(),
+            price: num(price),
+            quantity: num(quantity),
+            side: quant_schema::Side::Buy,
+            timestamp: at(seconds),
+        }
+    }
+
+    #[test]
+    fn test_time_bars() {
+        // 1_700_000_000 是 10 秒的整数倍
+        let mut aggregator = BarAggregator::new(BarType::Time(Interval::seconds(10))).unwrap();
+        assert!(aggregator.on_trade(&trade(1, "100", "1")).is_empty());
+        assert!(aggregator.on_trade(&trade(5, "103", "1")).is_empty());
+        assert!(aggregator.on_trade(&trade(3, "99", "2")).is_empty());
+        assert!(aggregator.on_trade(&trade(8, "101", "1")).is_empty());
+
+        let bars = aggregator.on_trade(&trade(12, "102", "1"));
+        assert_eq!(bars.len(), 1);
+        let bar = &bars[0];
+        assert_eq!((bar.open_time, bar.close_time), (at(0), at(10)));
+        assert_eq!(
+            (bar.open, bar.high, bar.low, bar.close),
+            (num("100"), num("103"), num("99"), num("101"))
+        );
+        assert_eq!(bar.volume, num("5"));
+        assert_eq!(bar.vwap, num("100.4"));
+        assert_eq!(bar.trade_count, 4);
+
+        // 所属 K 线已关闭
+        assert!(aggregator.on_trade(&trade(9, "100", "1")).is_empty());
+        assert_eq!(aggregator.late_trades(), 1);
+
+        let bars = aggregator.advance(at(20));
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].close, num("102"));
+    }
+
+    #[test]
+    fn test_lateness_and_gap_filling() {
+        let mut aggregator = BarAggregator::new(BarType::Time(Interval::seconds(10)))
+            .unwrap()
+            .lateness(TimeDelta::seconds(3))
+            .fill_gaps(true);
+        aggregator.on_trade(&trade(1, "100", "1"));
+        assert!(aggregator.on_trade(&trade(11, "101", "1")).is_empty());
+        // 迟到 10 秒，但仍在等待时间内
+        assert!(aggregator.on_trade(&trade(9, "105", "1")).is_empty());
+
+        let bars = aggregator.on_trade(&trade(45, "110", "1"));
+        assert_eq!(bars.len(), 4);
+        assert_eq!(bars[0].high, num("105"));
+        assert_eq!(bars[0].close, num("105"));
+        assert_eq!(bars[1].open_time, at(10));
+        assert_eq!(bars[1].close, num("101"));
+        for bar in &bars[2..] {
+            assert_eq!(bar.trade_count, 0);
+            assert_eq!((bar.open, bar.close), (num("101"), num("101")));
+            assert!(bar.volume.is_zero());
+        }
+        assert_eq!(bars[3].close_time, at(40));
+
+        // 没有成交时由时钟关闭并补齐
+        let bars = aggregator.advance(at(73));
+        assert_eq!(
+            bars.iter().map(|bar| bar.open_time).collect::<Vec<_>>(),
+            vec![at(40), at(50), at(60)]
+        );
+        assert_eq!(bars[1].close, num("110"));
+        assert_eq!(aggregator.late_trades(), 0);
+    }
+
+    #[test]
+    fn test_threshold_bars() {
+        let mut ticks = BarAggregator::new(BarType::Tick(2)).unwrap();
+        assert!(ticks.on_trade(&trade(1, "100", "1")).is_empty());
+        let bars = ticks.on_trade(&trade(2, "102", "3"));
+        assert_eq!(bars.len(), 1);
+        assert_eq!((bars[0].open_time, bars[0].close_time), (at(1), at(2)));
+        assert_eq!(bars[0].vwap, num("101.5"));
+
+        let mut volume = BarAggregator::new(BarType::Volume(num("5"))).unwrap();
+        assert!(volume.on_trade(&trade(1, "100", "2")).is_empty());
+        assert!(volume.on_trade(&trade(2, "100", "2")).is_empty());
+        let bars = volume.on_trade(&trade(3, "100", "2"));
+        assert_eq!(bars[0].volume, num("6"));
+        assert!(volume.advance(at(100)).is_empty());
+
+        let mut dollar = BarAggregator::new(BarType::Dollar(num("1000"))).unwrap();
+        assert!(dollar.on_trade(&trade(1, "100", "5")).is_empty());
+        let bars = dollar.on_trade(&trade(2, "100", "5"));
+        assert_eq!(bars[0].quote_volume, num("1000"));
+        assert_eq!(bars[0].trade_count, 2);
+    }
+
+    #[test]
+    fn test_rejects_empty_bar_type() {
+        for bar_type in [
+            BarType::Time(Interval::seconds(0)),
+            BarType::Tick(0),
+            BarType::Volume(Quantity::ZERO),
+            BarType::Dollar(num("-1")),
+        ] {
+            assert!(matches!(
+                BarAggregator::new(bar_type),
+                Err(Error::InvalidBarType(rejected)) if rejected == bar_type
+            ));
+        }
+    }
+}
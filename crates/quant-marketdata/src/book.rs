@@ -0,0 +1,488 @@
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use quant_exchange::okex::OkexClient;
+use quant_exchange::schema::okex::channel::OkexWsChannel;
+use quant_exchange::schema::okex::public::BookData;
+use quant_exchange::schema::okex::subscription::{PushData, SubscribeArg};
+use quant_schema::{BookUpdate, Exchange, Price, Quantity, Side};
+use std::collections::BTreeMap;
+
+/// OKX 校验和覆盖的档位数
+const CHECKSUM_DEPTH: usize = 25;
+
+/// 本地 L2 订单簿
+///
+/// 消费 OKX `books` 频道的全量和增量推送，校验 `seqId` 连续性和 CRC32 校验和；
+/// `books5`、`bbo-tbt` 每次推送都是全量，直接替换。
+/// 校验失败时订单簿会被清空并返回错误，调用方需要通过 [`OrderBook::resync_okx`] 重新订阅以获取新的全量数据。
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    exchange: Exchange,
+    /// 交易所的产品ID，如 `BTC-USDT`
+    symbol: String,
+    /// 价格从低到高，最优买价为最后一个
+    bids: BTreeMap<Price, Quantity>,
+    /// 价格从低到高，最优卖价为第一个
+    asks: BTreeMap<Price, Quantity>,
+    seq_id: Option<i64>,
+    /// 是否已收到全量数据
+    synced: bool,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl OrderBook {
+    pub fn new(exchange: Exchange, symbol: impl Into<String>) -> Self {
+        Self {
+            exchange,
+            symbol: symbol.into(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            seq_id: None,
+            synced: false,
+            updated_at: None,
+        }
+    }
+
+    pub fn exchange(&self) -> &Exchange {
+        &self.exchange
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// 最近一次推送的 `seqId`
+    pub fn seq_id(&self) -> Option<i64> {
+        self.seq_id
+    }
+
+    /// 是否已收到全量数据且之后的增量都校验通过
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+
+    /// 清空订单簿，等待下一次全量数据
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.seq_id = None;
+        self.synced = false;
+    }
+
+    pub fn best_bid(&self) -> Option<(Price, Quantity)> {
+        self.bids
+            .iter()
+            .next_back()
+            .map(|(price, size)| (*price, *size))
+    }
+
+    pub fn best_ask(&self) -> Option<(Price, Quantity)> {
+        self.asks.iter().next().map(|(price, size)| (*price, *size))
+    }
+
+    /// 某一档的数量，`Side::Buy` 为买方，没有该档时为 0
+    pub fn level(&self, side: Side, price: Price) -> Quantity {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.get(&price).copied().unwrap_or(Quantity::ZERO)
+    }
+
+    pub fn mid_price(&self) -> Option<Price> {
+        let (bid, _) = self.best_bid()?;
+        let (ask, _) = self.best_ask()?;
+        Some(Price::new(
+            (bid.value() + ask.value()) / quant_schema::Decimal::TWO,
+        ))
+    }
+
+    pub fn spread(&self) -> Option<Price> {
+        let (bid, _) = self.best_bid()?;
+        let (ask, _) = self.best_ask()?;
+        Some(ask - bid)
+    }
+
+    /// 买方前 `levels` 档，价格从高到低
+    pub fn bids(&self, levels: usize) -> Vec<(Price, Quantity)> {
+        self.bids
+            .iter()
+            .rev()
+            .take(levels)
+            .map(|(price, size)| (*price, *size))
+            .collect()
+    }
+
+    /// 卖方前 `levels` 档，价格从低到高
+    pub fn asks(&self, levels: usize) -> Vec<(Price, Quantity)> {
+        self.asks
+            .iter()
+            .take(levels)
+            .map(|(price, size)| (*price, *size))
+            .collect()
+    }
+
+    /// 以 `side` 方向吃掉 `quantity` 的成交均价，买入消耗卖方深度，卖出消耗买方深度；深度不足时返回 `None`
+    pub fn vwap(&self, side: Side, quantity: Quantity) -> Option<Price> {
+        if quantity <= Quantity::ZERO {
+            return None;
+        }
+        let levels: Box<dyn Iterator<Item = (&Price, &Quantity)>> = match side {
+            Side::Buy => Box::new(self.asks.iter()),
+            Side::Sell => Box::new(self.bids.iter().rev()),
+        };
+        let mut remaining = quantity;
+        let mut notional = quant_schema::Decimal::ZERO;
+        for (price, size) in levels {
+            let filled = (*size).min(remaining);
+            notional += *price * filled;
+            remaining -= filled;
+            if remaining.is_zero() {
+                return Some(Price::new(notional / quantity.value()));
+            }
+        }
+        None
+    }
+
+    /// 处理深度频道的推送，其它频道或其它产品的推送会被忽略；返回是否更新了订单簿
+    pub fn apply_okx_push(&mut self, push: &PushData) -> Result<bool> {
+        if push.arg.inst_id.as_deref() != Some(self.symbol.as_str()) {
+            return Ok(false);
+        }
+        let incremental = match push.arg.channel {
+            OkexWsChannel::Books => push.action.as_deref() == Some("update"),
+            OkexWsChannel::Books5 | OkexWsChannel::BboTbt => false,
+            _ => return Ok(false),
+        };
+        for value in &push.data {
+            let data: BookData = serde_json::from_value(value.clone())?;
+            if incremental {
+                self.apply_okx_update(&data)?;
+            } else {
+                self.apply_okx_snapshot(&data)?;
+            }
+        }
+        Ok(!push.data.is_empty())
+    }
+
+    /// 全量数据，替换整个订单簿
+    pub fn apply_okx_snapshot(&mut self, data: &BookData) -> Result<()> {
+        self.clear();
+        self.apply_levels(data)?;
+        self.verify_checksum(data)?;
+        self.seq_id = data.seq_id;
+        self.synced = true;
+        Ok(())
+    }
+
+    /// 增量数据，`prevSeqId` 必须等于上一次推送的 `seqId`
+    pub fn apply_okx_update(&mut self, data: &BookData) -> Result<()> {
+        if !self.synced {
+            return Err(Error::BookNotSynced(self.symbol.clone()));
+        }
+        if let (Some(expected), Some(received)) = (self.seq_id, data.prev_seq_id)
+            && expected != received
+        {
+            self.clear();
+            return Err(Error::SequenceGap {
+                symbol: self.symbol.clone(),
+                expected,
+                received,
+            });
+        }
+        self.apply_levels(data)?;
+        self.verify_checksum(data)?;
+        self.seq_id = data.seq_id;
+        Ok(())
+    }
+
+    /// 回放存储的深度变化，快照替换整个订单簿；不校验序列号和校验和
+    pub fn apply_update(&mut self, update: &BookUpdate) {
+        if update.snapshot {
+            self.clear();
+            self.synced = true;
+        }
+        for (price, size) in &update.bids {
+            update_level(&mut self.bids, *price, *size);
+        }
+        for (price, size) in &update.asks {
+            update_level(&mut self.asks, *price, *size);
+        }
+        self.seq_id = Some(update.sequence);
+        self.updated_at = Some(update.timestamp);
+    }
+
+    /// 清空订单簿并重新订阅 `books` 频道，交易所会重新推送全量数据
+    pub async fn resync_okx(&mut self, client: &mut OkexClient<'_>) -> Result<()> {
+        self.clear();
+        let arg = SubscribeArg::new(OkexWsChannel::Books).inst_id(&self.symbol);
+        client.unsubscribe(vec![arg.clone()]).await?;
+        client.subscribe(vec![arg]).await?;
+        Ok(())
+    }
+
+    /// 先解析全部数据再修改订单簿，解析失败时订单簿保持不变
+    fn apply_levels(&mut self, data: &BookData) -> Result<()> {
+        let bids = data.bids()?;
+        let asks = data.asks()?;
+        let timestamp = data.timestamp()?;
+        for (price, size) in bids {
+            update_level(&mut self.bids, price, size);
+        }
+        for (price, size) in asks {
+            update_level(&mut self.asks, price, size);
+        }
+        self.updated_at = Some(timestamp);
+        Ok(())
+    }
+
+    fn verify_checksum(&mut self, data: &BookData) -> Result<()> {
+        let Some(expected) = data.checksum else {
+            return Ok(());
+        };
+        let computed = self.checksum();
+        if computed != expected {
+            self.clear();
+            return Err(Error::ChecksumMismatch {
+                symbol: self.symbol.clone(),
+                expected,
+                computed,
+            });
+        }
+        Ok(())
+    }
+
+    /// OKX 校验和：前 25 档买卖交替拼接为 `bid价格:bid数量:ask价格:ask数量:...`，取 CRC32 的有符号值
+    pub fn checksum(&self) -> i32 {
+        let bids = self.bids(CHECKSUM_DEPTH);
+        let asks = self.asks(CHECKSUM_DEPTH);
+        let mut fields = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+        for i in 0..CHECKSUM_DEPTH {
+            for level in [bids.get(i), asks.get(i)].into_iter().flatten() {
+                fields.push(level.0.to_string());
+                fields.push(level.1.to_string());
+            }
+        }
+        crc32(fields.join(":").as_bytes()) as i32
+    }
+}
+
+/// 数量为 0 时删除该档；价格的字符串精度会影响校验和，因此先删除再插入以使用最新推送的精度
+fn update_level(levels: &mut BTreeMap<Price, Quantity>, price: Price, size: Quantity) {
+    levels.remove(&price);
+    if !size.is_zero() {
+        levels.insert(price, size);
+    }
+}
+
+/// CRC-32 (IEEE 802.3)
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::num;
+    use serde_json::json;
+
+    fn push(channel: OkexWsChannel, action: Option<&str>, data: serde_json::Value) -> PushData {
+        PushData {
+            arg: SubscribeArg::new(channel).inst_id("BTC-USDT"),
+            action: action.map(str::to_string),
+            data: vec![data],
+        }
+    }
+
+    fn snapshot() -> PushData {
+        push(
+            OkexWsChannel::Books,
+            Some("snapshot"),
+            json!({
+                "asks": [["3366.8", "9", "0", "3"], ["3368", "8", "0", "4"]],
+                "bids": [["3366.1", "7", "0", "3"], ["3366", "6", "0", "4"]],
+                "ts": "1597026383085",
+                "checksum": -1881014294,
+                "prevSeqId": -1,
+                "seqId": 123456
+            }),
+        )
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_snapshot_and_update() {
+        let mut book = OrderBook::new(Exchange::Okex, "BTC-USDT");
+        assert!(matches!(
+            book.apply_okx_push(&push(
+                OkexWsChannel::Books,
+                Some("update"),
+                json!({"asks": [], "bids": [], "ts": "1", "prevSeqId": 1, "seqId": 2})
+            )),
+            Err(Error::BookNotSynced(_))
+        ));
+
+        assert!(book.apply_okx_push(&snapshot()).unwrap());
+        assert!(book.is_synced());
+        assert_eq!(book.best_bid(), Some((num("3366.1"), num("7"))));
+        assert_eq!(book.best_ask(), Some((num("3366.8"), num("9"))));
+        assert_eq!(book.spread(), Some(num("0.7")));
+        assert_eq!(book.mid_price(), Some(num("3366.45")));
+
+        let update = push(
+            OkexWsChannel::Books,
+            Some("update"),
+            json!({
+                "asks": [],
+                "bids": [["3365.9", "2", "0", "1"]],
+                "ts": "1597026383185",
+                "checksum": 829876515,
+                "prevSeqId": 123456,
+                "seqId": 123457
+            }),
+        );
+        book.apply_okx_push(&update).unwrap();
+        assert_eq!(book.seq_id(), Some(123457));
+        assert_eq!(
+            book.bids(5),
+            vec![
+                (num("3366.1"), num("7")),
+                (num("3366"), num("6")),
+                (num("3365.9"), num("2"))
+            ]
+        );
+
+        // 删除最优买价
+        let delete = push(
+            OkexWsChannel::Books,
+            Some("update"),
+            json!({
+                "asks": [],
+                "bids": [["3366.1", "0", "0", "0"]],
+                "ts": "1597026383285",
+                "prevSeqId": 123457,
+                "seqId": 123458
+            }),
+        );
+        book.apply_okx_push(&delete).unwrap();
+        assert_eq!(book.best_bid(), Some((num("3366"), num("6"))));
+
+        // 其它产品的推送被忽略
+        let mut other = snapshot();
+        other.arg.inst_id = Some("ETH-USDT".to_string());
+        assert!(!book.apply_okx_push(&other).unwrap());
+    }
+
+    #[test]
+    fn test_resync_required() {
+        let mut book = OrderBook::new(Exchange::Okex, "BTC-USDT");
+        book.apply_okx_push(&snapshot()).unwrap();
+        let gap = push(
+            OkexWsChannel::Books,
+            Some("update"),
+            json!({"asks": [], "bids": [], "ts": "1", "prevSeqId": 123450, "seqId": 123460}),
+        );
+        assert!(matches!(
+            book.apply_okx_push(&gap),
+            Err(Error::SequenceGap {
+                expected: 123456,
+                received: 123450,
+                ..
+            })
+        ));
+        assert!(!book.is_synced());
+        assert!(book.best_bid().is_none());
+
+        book.apply_okx_push(&snapshot()).unwrap();
+        let corrupted = push(
+            OkexWsChannel::Books,
+            Some("update"),
+            json!({
+                "asks": [["3367", "1", "0", "1"]],
+                "bids": [],
+                "ts": "1",
+                "checksum": 42,
+                "prevSeqId": 123456,
+                "seqId": 123457
+            }),
+        );
+        assert!(matches!(
+            book.apply_okx_push(&corrupted),
+            Err(Error::ChecksumMismatch { expected: 42, .. })
+        ));
+        assert!(!book.is_synced());
+
+        // 卖方数据无法解析时买方也不会被修改
+        book.apply_okx_push(&snapshot()).unwrap();
+        let malformed = push(
+            OkexWsChannel::Books,
+            Some("update"),
+            json!({
+                "asks": [["3367", "bad", "0", "1"]],
+                "bids": [["3366.5", "1", "0", "1"]],
+                "ts": "1",
+                "prevSeqId": 123456,
+                "seqId": 123457
+            }),
+        );
+        assert!(book.apply_okx_push(&malformed).is_err());
+        assert!(book.is_synced());
+        assert_eq!(book.best_bid(), Some((num("3366.1"), num("7"))));
+        assert_eq!(book.seq_id(), Some(123456));
+    }
+
+    #[test]
+    fn test_books5_and_vwap() {
+        let mut book = OrderBook::new(Exchange::Okex, "BTC-USDT");
+        book.apply_okx_push(&snapshot()).unwrap();
+        let books5 = push(
+            OkexWsChannel::Books5,
+            None,
+            json!({
+                "asks": [["100", "1", "0", "1"], ["101", "2", "0", "1"], ["103", "1", "0", "1"]],
+                "bids": [["99", "3", "0", "1"], ["98", "1", "0", "1"]],
+                "instId": "BTC-USDT",
+                "ts": "1597026383085",
+                "seqId": 10
+            }),
+        );
+        book.apply_okx_push(&books5).unwrap();
+        assert_eq!(book.asks(10).len(), 3);
+        assert_eq!(book.best_bid(), Some((num("99"), num("3"))));
+
+        assert_eq!(book.vwap(Side::Buy, num("2")), Some(num("100.5")));
+        assert_eq!(book.vwap(Side::Buy, num("4")), Some(num("101.25")));
+        assert_eq!(book.vwap(Side::Sell, num("4")), Some(num("98.75")));
+        assert_eq!(book.vwap(Side::Buy, num("5")), None);
+        assert_eq!(book.vwap(Side::Sell, Quantity::ZERO), None);
+    }
+}
@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Exchange(#[from] quant_exchange::error::Error),
+    #[error(transparent)]
+    Storage(#[from] quant_storage::error::Error),
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+    #[error("Order book {symbol} checksum mismatch: expected {expected}, computed {computed}")]
+    ChecksumMismatch {
+        symbol: String,
+        expected: i32,
+        computed: i32,
+    },
+    #[error("Order book {symbol} sequence gap: expected prevSeqId {expected}, received {received}")]
+    SequenceGap {
+        symbol: String,
+        expected: i64,
+        received: i64,
+    },
+    #[error("Instrument {0} is not loaded")]
+    UnknownSymbol(String),
+    #[error("Order book {0} received an update before the snapshot")]
+    BookNotSynced(String),
+    #[error("Bar type {0:?} needs a positive interval or threshold")]
+    InvalidBarType(quant_schema::BarType),
+}
@@ -0,0 +1,257 @@
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use quant_exchange::okex::OkexClient;
+use quant_exchange::schema::okex::channel::OkexWsChannel;
+use quant_exchange::schema::okex::public::InstrumentInfo;
+use quant_exchange::schema::okex::subscription::{InstType, PushData};
+use quant_schema::{Exchange, Symbol};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 产品信息缓存，以交易所和交易所的产品ID为键
+///
+/// 启动时通过 REST 全量加载，之后用 `instruments` 频道的推送增量更新。
+#[derive(Debug, Default)]
+pub struct InstrumentRegistry {
+    symbols: HashMap<(Exchange, String), Symbol>,
+    /// 最近一次全量加载的时间
+    loaded_at: Option<DateTime<Utc>>,
+    /// 累计跳过的无法解析或转换的产品数量
+    skipped: usize,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, exchange: &Exchange, exchange_symbol: &str) -> Option<&Symbol> {
+        self.symbols
+            .get(&(exchange.clone(), exchange_symbol.to_string()))
+    }
+
+    /// 某个交易所的全部产品
+    pub fn symbols<'a>(&'a self, exchange: &'a Exchange) -> impl Iterator<Item = &'a Symbol> {
+        self.symbols
+            .iter()
+            .filter(move |((key, _), _)| key == exchange)
+            .map(|(_, symbol)| symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// 新增或替换产品，返回被替换的旧值
+    pub fn insert(&mut self, symbol: Symbol) -> Option<Symbol> {
+        let key = (symbol.exchange.clone(), symbol.exchange_symbol.clone());
+        self.symbols.insert(key, symbol)
+    }
+
+    pub fn loaded_at(&self) -> Option<DateTime<Utc>> {
+        self.loaded_at
+    }
+
+    /// 累计跳过的产品数量，如新的产品类型或缺少面值的合约，这些产品不影响其它产品的加载
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// 从未加载或距上次全量加载超过 `max_age`
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        match self.loaded_at {
+            Some(loaded_at) => (Utc::now() - loaded_at).to_std().unwrap_or_default() > max_age,
+            None => true,
+        }
+    }
+
+    /// 加载一种产品类型，期权必须指定 `inst_family`；返回加载的数量，不含跳过的产品
+    pub async fn load_okx(
+        &mut self,
+        client: &OkexClient<'_>,
+        inst_type: InstType,
+        inst_family: Option<&str>,
+    ) -> Result<usize> {
+        let instruments = client.get_instruments(inst_type, inst_family).await?;
+        let count = self.upsert_okx(instruments.iter().map(Some));
+        self.loaded_at = Some(Utc::now());
+        Ok(count)
+    }
+
+    /// 加载币币、永续、交割，以及 `option_families` 中每个品种的期权；返回加载的数量
+    pub async fn load_okx_all(
+        &mut self,
+        client: &OkexClient<'_>,
+        option_families: &[&str],
+    ) -> Result<usize> {
+        let mut count = 0;
+        for inst_type in [InstType::Spot, InstType::Swap, InstType::Futures] {
+            count += self.load_okx(client, inst_type, None).await?;
+        }
+        for inst_family in option_families {
+            count += self
+                .load_okx(client, InstType::Option, Some(inst_family))
+                .await?;
+        }
+        Ok(count)
+    }
+
+    /// 处理 `instruments` 频道的推送，其它频道的推送会被忽略；返回更新的数量
+    pub fn apply_okx_push(&mut self, push: &PushData) -> usize {
+        if push.arg.channel != OkexWsChannel::Instruments {
+            return 0;
+        }
+        let instruments: Vec<_> = push
+            .data
+            .iter()
+            .map(|value| serde_json::from_value::<InstrumentInfo>(value.clone()).ok())
+            .collect();
+        self.upsert_okx(instruments.iter().map(Option::as_ref))
+    }
+
+    /// 无法解析（`None`）或无法转换为 `Symbol` 的产品计入 `skipped`
+    fn upsert_okx<'a>(
+        &mut self,
+        instruments: impl IntoIterator<Item = Option<&'a InstrumentInfo>>,
+    ) -> usize {
+        let mut count = 0;
+        for info in instruments {
+            match info.and_then(|info| Symbol::try_from(info).ok()) {
+                Some(symbol) => {
+                    self.insert(symbol);
+                    count += 1;
+                }
+                None => self.skipped += 1,
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::config;
+    use quant_exchange::schema::okex::subscription::SubscribeArg;
+    use quant_schema::{MarketType, Quantity};
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn spot(state: &str) -> serde_json::Value {
+        serde_json::json!({
+            "instType": "SPOT", "instId": "BTC-USDT", "instFamily": "", "baseCcy": "BTC",
+            "quoteCcy": "USDT", "settleCcy": "", "ctVal": "", "ctValCcy": "",
+            "listTime": "1606468572000", "expTime": "", "tickSz": "0.1", "lotSz": "0.00000001",
+            "minSz": "0.00001", "state": state
+        })
+    }
+
+    fn futures() -> serde_json::Value {
+        serde_json::json!({
+            "instType": "FUTURES", "instId": "BTC-USD-250328", "instFamily": "BTC-USD",
+            "baseCcy": "", "quoteCcy": "", "settleCcy": "BTC", "ctVal": "100",
+            "ctValCcy": "USD", "listTime": "1597026383085", "expTime": "1743148800000",
+            "tickSz": "0.1", "lotSz": "1", "minSz": "1", "state": "live"
+        })
+    }
+
+    async fn mount_instruments(server: &MockServer, inst_type: &str, data: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/api/v5/public/instruments"))
+            .and(query_param("instType", inst_type))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"code": "0", "msg": "", "data": data})),
+            )
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_load_okx_all() {
+        let server = MockServer::start().await;
+        mount_instruments(&server, "SPOT", serde_json::json!([spot("live")])).await;
+        let mut swap = futures();
+        swap["instType"] = "SWAP".into();
+        swap["instId"] = "BTC-USD-SWAP".into();
+        swap["instFamily"] = "".into();
+        mount_instruments(&server, "SWAP", serde_json::json!([swap])).await;
+        mount_instruments(&server, "FUTURES", serde_json::json!([futures()])).await;
+        let config = config(&server.uri());
+        let http_client = reqwest::Client::new();
+        let client = OkexClient::new(&http_client, &config);
+
+        let mut registry = InstrumentRegistry::new();
+        assert!(registry.is_stale(Duration::from_secs(60)));
+        // 缺少品种的永续合约无法转换，跳过后不影响其它产品
+        assert_eq!(registry.load_okx_all(&client, &[]).await.unwrap(), 2);
+        assert_eq!(registry.skipped(), 1);
+        assert!(!registry.is_stale(Duration::from_secs(60)));
+
+        let btc = registry.get(&Exchange::Okex, "BTC-USDT").unwrap();
+        assert_eq!(btc.name, "BTC/USDT");
+        assert_eq!(btc.market_type, MarketType::Spot);
+        assert_eq!(btc.tick_size, "0.1".parse().unwrap());
+        assert_eq!(btc.min_qty, "0.00001".parse().unwrap());
+        assert_eq!(
+            btc.round_quantity("0.123456789".parse().unwrap()),
+            "0.12345678".parse::<Quantity>().unwrap()
+        );
+        assert!(btc.expiry.is_none());
+
+        let future = registry.get(&Exchange::Okex, "BTC-USD-250328").unwrap();
+        assert_eq!(
+            (future.base_asset.as_str(), future.quote_asset.as_str()),
+            ("BTC", "USD")
+        );
+        assert_eq!(future.contract_value, Some(100.into()));
+        assert_eq!(future.settle_asset.as_deref(), Some("BTC"));
+        assert_eq!(future.expiry.unwrap().timestamp_millis(), 1743148800000);
+        assert!(future.is_derivative());
+        assert_eq!(future.name, "BTC/USD-250328");
+        assert_eq!(
+            future
+                .canonical()
+                .unwrap()
+                .to_native(&Exchange::Binance)
+                .unwrap(),
+            "BTCUSD_250328"
+        );
+
+        // 期权必须指定品种
+        assert!(
+            registry
+                .load_okx(&client, InstType::Option, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_apply_instruments_push() {
+        let mut registry = InstrumentRegistry::new();
+        let push = PushData {
+            arg: SubscribeArg::new(OkexWsChannel::Instruments).inst_type(InstType::Spot),
+            action: None,
+            data: vec![spot("suspend"), serde_json::json!({"instType": "EVENTS"})],
+        };
+        assert_eq!(registry.apply_okx_push(&push), 1);
+        assert_eq!(registry.skipped(), 1);
+        assert_eq!(
+            registry.get(&Exchange::Okex, "BTC-USDT").unwrap().status,
+            "suspend"
+        );
+
+        let other = PushData {
+            arg: SubscribeArg::new(OkexWsChannel::Books).inst_id("BTC-USDT"),
+            action: Some("snapshot".to_string()),
+            data: vec![serde_json::json!({})],
+        };
+        assert_eq!(registry.apply_okx_push(&other), 0);
+        assert_eq!(registry.symbols(&Exchange::Okex).count(), 1);
+    }
+}
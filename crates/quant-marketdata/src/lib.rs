@@ -1,14 +1,9 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
-
+pub mod backfill;
+pub mod bar;
+pub mod book;
+pub mod error;
+pub mod instrument;
+pub mod service;
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+mod test_util;
+pub mod ticker;
@@ -0,0 +1,535 @@
+use crate::error::{Error, Result};
+use crate::instrument::InstrumentRegistry;
+use crate::ticker::TickerStats;
+use quant_exchange::okex::{OkexClient, OkexWsEvent};
+use quant_exchange::schema::okex::channel::OkexWsChannel;
+use quant_exchange::schema::okex::market::{
+    CandleData, FundingRateData, IndexTickerData, MarkPriceData, OpenInterestData, TickerData,
+    TradeData,
+};
+use quant_exchange::schema::okex::public::BookData;
+use quant_exchange::schema::okex::subscription::{OkexWsMessage, PushData};
+use quant_schema::{
+    Candle, Exchange, ExchangeTime, FundingRate, IndexPrice, MarkPrice, MarketEvent, OpenInterest,
+    ReceiveTime, Ticker, Trade,
+};
+use serde::de::DeserializeOwned;
+use tokio::sync::broadcast;
+
+/// 行情服务：解析公共频道的推送，转换为统一的行情类型后广播给订阅者
+///
+/// 每种行情一个广播通道，数据包装为 `MarketEvent`，带有交易所时间、本地接收时间和全部通道共用的递增序号。
+/// 订阅者处理不及时会丢失最旧的数据（`RecvError::Lagged`）。
+/// 没有订阅者时数据直接丢弃。
+pub struct MarketDataService {
+    instruments: InstrumentRegistry,
+    tickers: broadcast::Sender<MarketEvent<Ticker>>,
+    trades: broadcast::Sender<MarketEvent<Trade>>,
+    candles: broadcast::Sender<MarketEvent<Candle>>,
+    funding_rates: broadcast::Sender<MarketEvent<FundingRate>>,
+    mark_prices: broadcast::Sender<MarketEvent<MarkPrice>>,
+    index_prices: broadcast::Sender<MarketEvent<IndexPrice>>,
+    open_interest: broadcast::Sender<MarketEvent<OpenInterest>>,
+    /// 由 `trades` 和 `bbo-tbt` 频道计算的滚动 24 小时统计
+    ticker_stats: TickerStats,
+    /// 最后一个广播的事件的序号
+    sequence: u64,
+    /// `run_okx` 中处理失败而丢弃的推送和消息数量
+    dropped_pushes: u64,
+    /// 最近一次丢弃推送或消息的原因
+    last_push_error: Option<Error>,
+}
+
+impl MarketDataService {
+    /// `capacity` 为每个广播通道缓存的数据条数
+    pub fn new(instruments: InstrumentRegistry, capacity: usize) -> Self {
+        Self {
+            instruments,
+            tickers: broadcast::channel(capacity).0,
+            trades: broadcast::channel(capacity).0,
+            candles: broadcast::channel(capacity).0,
+            funding_rates: broadcast::channel(capacity).0,
+            mark_prices: broadcast::channel(capacity).0,
+            index_prices: broadcast::channel(capacity).0,
+            open_interest: broadcast::channel(capacity).0,
+            ticker_stats: TickerStats::new(),
+            sequence: 0,
+            dropped_pushes: 0,
+            last_push_error: None,
+        }
+    }
+
+    pub fn instruments(&self) -> &InstrumentRegistry {
+        &self.instruments
+    }
+
+    pub fn instruments_mut(&mut self) -> &mut InstrumentRegistry {
+        &mut self.instruments
+    }
+
+    pub fn ticker_stats(&self) -> &TickerStats {
+        &self.ticker_stats
+    }
+
+    /// `run_okx` 中处理失败而丢弃的推送和消息数量
+    pub fn dropped_pushes(&self) -> u64 {
+        self.dropped_pushes
+    }
+
+    /// 最近一次丢弃推送或消息的原因
+    pub fn last_push_error(&self) -> Option<&Error> {
+        self.last_push_error.as_ref()
+    }
+
+    /// 本地计算的 24 小时统计，字段口径与交易所无关；产品未加载或没有收到成交时返回 `None`
+    pub fn local_ticker(&self, exchange: &Exchange, exchange_symbol: &str) -> Option<Ticker> {
+        let symbol = self.instruments.get(exchange, exchange_symbol)?;
+        self.ticker_stats.ticker(symbol)
+    }
+
+    pub fn subscribe_tickers(&self) -> broadcast::Receiver<MarketEvent<Ticker>> {
+        self.tickers.subscribe()
+    }
+
+    pub fn subscribe_trades(&self) -> broadcast::Receiver<MarketEvent<Trade>> {
+        self.trades.subscribe()
+    }
+
+    pub fn subscribe_candles(&self) -> broadcast::Receiver<MarketEvent<Candle>> {
+        self.candles.subscribe()
+    }
+
+    pub fn subscribe_funding_rates(&self) -> broadcast::Receiver<MarketEvent<FundingRate>> {
+        self.funding_rates.subscribe()
+    }
+
+    pub fn subscribe_mark_prices(&self) -> broadcast::Receiver<MarketEvent<MarkPrice>> {
+        self.mark_prices.subscribe()
+    }
+
+    pub fn subscribe_index_prices(&self) -> broadcast::Receiver<MarketEvent<IndexPrice>> {
+        self.index_prices.subscribe()
+    }
+
+    pub fn subscribe_open_interest(&self) -> broadcast::Receiver<MarketEvent<OpenInterest>> {
+        self.open_interest.subscribe()
+    }
+
+    /// 持续读取 WebSocket 消息并分发，直到连接出错，如重连次数用完（重连由 `OkexClient` 处理）
+    ///
+    /// 处理失败的推送、无法解析的消息和订阅失败的事件被丢弃并计入 [`MarketDataService::dropped_pushes`]，
+    /// 不影响之后的推送；例如订阅 `tickers`、`candle` 前没有加载对应的产品信息时，这些推送会因
+    /// `Error::UnknownSymbol` 被丢弃。
+    pub async fn run_okx(&mut self, client: &mut OkexClient<'_>) -> Result<()> {
+        loop {
+            match client.next_event().await {
+                Ok(OkexWsEvent::Message(OkexWsMessage::Push(push))) => {
+                    let received = client.last_received().unwrap_or_else(ReceiveTime::now);
+                    self.dispatch_okx_push(&push, received);
+                }
+                Ok(_) => {}
+                Err(err) if err.is_message_error() => self.drop_push(err.into()),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// 分发一条推送，失败时丢弃并记录原因
+    fn dispatch_okx_push(&mut self, push: &PushData, received: ReceiveTime) {
+        if let Err(err) = self.handle_okx_push(push, received) {
+            self.drop_push(err);
+        }
+    }
+
+    fn drop_push(&mut self, err: Error) {
+        self.dropped_pushes += 1;
+        self.last_push_error = Some(err);
+    }
+
+    /// 分发一条推送，`instruments` 频道用于更新产品信息，`bbo-tbt` 频道用于更新本地 24 小时统计，其它频道会被忽略；返回广播的数据条数
+    ///
+    /// `received` 为推送消息从连接中读取的时间，见 `OkexClient::last_received`。
+    pub fn handle_okx_push(&mut self, push: &PushData, received: ReceiveTime) -> Result<usize> {
+        match &push.arg.channel {
+            OkexWsChannel::Instruments => {
+                self.instruments.apply_okx_push(push);
+                Ok(0)
+            }
+            OkexWsChannel::Tickers => {
+                let tickers = parse_data::<TickerData>(push)?
+                    .iter()
+                    .map(|data| {
+                        let symbol = self
+                            .instruments
+                            .get(&Exchange::Okex, &data.inst_id)
+                            .ok_or_else(|| Error::UnknownSymbol(data.inst_id.clone()))?;
+                        Ok(data.to_ticker(symbol.clone())?)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(publish(
+                    &self.tickers,
+                    envelope(&mut self.sequence, tickers, received),
+                ))
+            }
+            OkexWsChannel::Trades => {
+                let trades = convert::<TradeData, Trade>(push)?;
+                for trade in &trades {
+                    self.ticker_stats.on_trade(trade);
+                }
+                Ok(publish(
+                    &self.trades,
+                    envelope(&mut self.sequence, trades, received),
+                ))
+            }
+            OkexWsChannel::BboTbt => {
+                let inst_id = push.arg.inst_id.as_deref().unwrap_or_default();
+                for data in parse_data::<BookData>(push)? {
+                    if let (Some((bid, _)), Some((ask, _))) =
+                        (data.bids()?.first(), data.asks()?.first())
+                    {
+                        self.ticker_stats.on_book_top(
+                            &Exchange::Okex,
+                            inst_id,
+                            *bid,
+                            *ask,
+                            data.timestamp()?,
+                        );
+                    }
+                }
+                Ok(0)
+            }
+            OkexWsChannel::Candle(bar) => {
+                let inst_id = push.arg.inst_id.as_deref().unwrap_or_default();
+                let market_type = self
+                    .instruments
+                    .get(&Exchange::Okex, inst_id)
+                    .ok_or_else(|| Error::UnknownSymbol(inst_id.to_string()))?
+                    .market_type;
+                let candles = parse_data::<CandleData>(push)?
+                    .iter()
+                    .map(|data| data.to_candle(inst_id, market_type, *bar))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok(publish(
+                    &self.candles,
+                    envelope(&mut self.sequence, candles, received),
+                ))
+            }
+            OkexWsChannel::FundingRate => {
+                let rates = convert::<FundingRateData, FundingRate>(push)?;
+                Ok(publish(
+                    &self.funding_rates,
+                    envelope(&mut self.sequence, rates, received),
+                ))
+            }
+            OkexWsChannel::MarkPrice => {
+                let prices = convert::<MarkPriceData, MarkPrice>(push)?;
+                Ok(publish(
+                    &self.mark_prices,
+                    envelope(&mut self.sequence, prices, received),
+                ))
+            }
+            OkexWsChannel::IndexTickers => {
+                let prices = convert::<IndexTickerData, IndexPrice>(push)?;
+                Ok(publish(
+                    &self.index_prices,
+                    envelope(&mut self.sequence, prices, received),
+                ))
+            }
+            OkexWsChannel::OpenInterest => {
+                let open_interest = convert::<OpenInterestData, OpenInterest>(push)?;
+                Ok(publish(
+                    &self.open_interest,
+                    envelope(&mut self.sequence, open_interest, received),
+                ))
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+/// 包装为 `MarketEvent`，依次分配序号
+fn envelope<T: ExchangeTime>(
+    sequence: &mut u64,
+    items: Vec<T>,
+    received: ReceiveTime,
+) -> Vec<MarketEvent<T>> {
+    items
+        .into_iter()
+        .map(|data| {
+            *sequence += 1;
+            MarketEvent::new(Exchange::Okex, data, received, *sequence)
+        })
+        .collect()
+}
+
+fn parse_data<T: DeserializeOwned>(push: &PushData) -> Result<Vec<T>> {
+    push.data
+        .iter()
+        .map(|value| Ok(serde_json::from_value(value.clone())?))
+        .collect()
+}
+
+/// 解析推送数据并转换为统一的行情类型
+fn convert<T, U>(push: &PushData) -> Result<Vec<U>>
+where
+    T: DeserializeOwned,
+    for<'a> U: TryFrom<&'a T, Error = quant_exchange::error::Error>,
+{
+    parse_data::<T>(push)?
+        .iter()
+        .map(|data| Ok(U::try_from(data)?))
+        .collect()
+}
+
+/// 没有订阅者时 `send` 返回错误，此时丢弃数据
+fn publish<T>(sender: &broadcast::Sender<T>, items: Vec<T>) -> usize {
+    let count = items.len();
+    for item in items {
+        let _ = sender.send(item);
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_exchange::schema::okex::market::Bar;
+    use quant_exchange::schema::okex::subscription::SubscribeArg;
+    use serde_json::json;
+
+    fn push(channel: OkexWsChannel, inst_id: &str, data: serde_json::Value) -> PushData {
+        PushData {
+            arg: SubscribeArg::new(channel).inst_id(inst_id),
+            action: None,
+            data: vec![data],
+        }
+    }
+
+    fn service() -> MarketDataService {
+        let mut service = MarketDataService::new(InstrumentRegistry::new(), 16);
+        let instrument = push(
+            OkexWsChannel::Instruments,
+            "BTC-USDT",
+            json!({
+                "instType": "SPOT", "instId": "BTC-USDT", "baseCcy": "BTC", "quoteCcy": "USDT",
+                "tickSz": "0.1", "lotSz": "0.00000001", "minSz": "0.00001", "state": "live"
+            }),
+        );
+        service
+            .handle_okx_push(&instrument, ReceiveTime::now())
+            .unwrap();
+        service
+    }
+
+    #[test]
+    fn test_ticker_fan_out() {
+        let mut service = service();
+        let mut first = service.subscribe_tickers();
+        let mut second = service.subscribe_tickers();
+        let ticker = push(
+            OkexWsChannel::Tickers,
+            "BTC-USDT",
+            json!({
+                "instType": "SPOT", "instId": "BTC-USDT", "last": "9999.99", "lastSz": "0.1",
+                "askPx": "9999.99", "askSz": "11", "bidPx": "8888.88", "bidSz": "5",
+                "open24h": "9000", "high24h": "10000", "low24h": "8888.88",
+                "volCcy24h": "2222", "vol24h": "2222", "sodUtc0": "2222", "sodUtc8": "2222",
+                "ts": "1597026383085"
+            }),
+        );
+        assert_eq!(
+            service
+                .handle_okx_push(&ticker, ReceiveTime::now())
+                .unwrap(),
+            1
+        );
+        for receiver in [&mut first, &mut second] {
+            let ticker = receiver.try_recv().unwrap().data;
+            assert_eq!(ticker.symbol.name, "BTC/USDT");
+            assert_eq!(ticker.bid_price, "8888.88".parse().unwrap());
+            assert_eq!(ticker.price_change_24h, "999.99".parse().unwrap());
+            assert_eq!(ticker.price_change_percent_24h, "11.111".parse().unwrap());
+        }
+
+        let mut unknown = ticker.clone();
+        unknown.data[0]["instId"] = json!("ETH-USDT");
+        assert!(matches!(
+            service.handle_okx_push(&unknown, ReceiveTime::now()),
+            Err(Error::UnknownSymbol(_))
+        ));
+
+        // 处理失败的推送被丢弃并计数，之后的推送照常分发
+        service.dispatch_okx_push(&unknown, ReceiveTime::now());
+        service.dispatch_okx_push(&ticker, ReceiveTime::now());
+        assert_eq!(service.dropped_pushes(), 1);
+        assert!(matches!(
+            service.last_push_error(),
+            Some(Error::UnknownSymbol(_))
+        ));
+        assert!(first.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_public_streams() {
+        let mut service = service();
+        let mut trades = service.subscribe_trades();
+        let mut candles = service.subscribe_candles();
+        let mut mark_prices = service.subscribe_mark_prices();
+        let mut index_prices = service.subscribe_index_prices();
+        let mut open_interest = service.subscribe_open_interest();
+
+        service
+            .handle_okx_push(
+                &push(
+                    OkexWsChannel::Trades,
+                    "BTC-USDT",
+                    json!({"instId": "BTC-USDT", "tradeId": "1", "px": "42219.9", "sz": "0.1", "side": "buy", "ts": "1630048897897"}),
+                ),
+                ReceiveTime::now(),
+            )
+            .unwrap();
+        let trade = trades.try_recv().unwrap();
+        assert_eq!(trade.exchange, Exchange::Okex);
+        assert_eq!(trade.sequence, 1);
+        assert_eq!(trade.data.trade_id, "1");
+        assert!(trade.latency().unwrap() > chrono::TimeDelta::zero());
+
+        service
+            .handle_okx_push(
+                &push(
+                    OkexWsChannel::Candle(Bar::M1),
+                    "BTC-USDT",
+                    json!([
+                        "1597026383085",
+                        "8533.02",
+                        "8553.74",
+                        "8527.17",
+                        "8548.26",
+                        "45247",
+                        "529.5858061",
+                        "529.5858061",
+                        "0"
+                    ]),
+                ),
+                ReceiveTime::now(),
+            )
+            .unwrap();
+        let candle = candles.try_recv().unwrap();
+        assert_eq!(candle.sequence, 2);
+        assert_eq!(candle.latency(), None);
+        let candle = candle.data;
+        assert_eq!(candle.symbol, "BTC-USDT");
+        assert_eq!(candle.volume, "45247".parse().unwrap());
+        assert!(!candle.closed);
+
+        service
+            .handle_okx_push(
+                &push(
+                    OkexWsChannel::MarkPrice,
+                    "BTC-USDT-SWAP",
+                    json!({"instType": "SWAP", "instId": "BTC-USDT-SWAP", "markPx": "42310.6", "ts": "1630049139746"}),
+                ),
+                ReceiveTime::now(),
+            )
+            .unwrap();
+        assert_eq!(
+            mark_prices.try_recv().unwrap().data.price,
+            "42310.6".parse().unwrap()
+        );
+
+        service
+            .handle_okx_push(
+                &push(
+                    OkexWsChannel::IndexTickers,
+                    "BTC-USDT",
+                    json!({"instId": "BTC-USDT", "idxPx": "0.1", "high24h": "0.5", "low24h": "0.1", "open24h": "0.1", "sodUtc0": "0.1", "sodUtc8": "0.1", "ts": "1597026383085"}),
+                ),
+                ReceiveTime::now(),
+            )
+            .unwrap();
+        assert_eq!(index_prices.try_recv().unwrap().data.symbol, "BTC-USDT");
+
+        service
+            .handle_okx_push(
+                &push(
+                    OkexWsChannel::OpenInterest,
+                    "BTC-USDT-SWAP",
+                    json!({"instType": "SWAP", "instId": "BTC-USDT-SWAP", "oi": "5000", "oiCcy": "555.55", "oiUsd": "50000", "ts": "1597026383085"}),
+                ),
+                ReceiveTime::now(),
+            )
+            .unwrap();
+        assert_eq!(
+            open_interest.try_recv().unwrap().data.contracts,
+            "5000".parse().unwrap()
+        );
+
+        // 其它频道的推送被忽略
+        assert_eq!(
+            service
+                .handle_okx_push(
+                    &push(OkexWsChannel::Books, "BTC-USDT", json!({})),
+                    ReceiveTime::now(),
+                )
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_okx_skips_bad_messages() {
+        use futures_util::{SinkExt, StreamExt};
+        use quant_exchange::schema::okex::channel::WsChannelType;
+        use tokio_tungstenite::tungstenite::Message;
+
+        // 订阅请求回复错误，然后推送一条无法解析的消息和一笔成交
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let Some(Ok(Message::Text(text))) = ws.next().await else {
+                return;
+            };
+            let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+            let replies = [
+                json!({"id": request["id"], "event": "error", "code": "60018", "msg": "doesn't exist"})
+                    .to_string(),
+                "not json".to_string(),
+                json!({
+                    "arg": {"channel": "trades", "instId": "BTC-USDT"},
+                    "data": [{"instId": "BTC-USDT", "tradeId": "1", "px": "100", "sz": "1", "side": "buy", "ts": "1630048897897"}]
+                })
+                .to_string(),
+            ];
+            for reply in replies {
+                ws.send(Message::Text(reply.into())).await.unwrap();
+            }
+            std::future::pending::<()>().await;
+        });
+
+        let config = crate::test_util::ws_config(&url);
+        let http_client = reqwest::Client::new();
+        let mut client = OkexClient::new(&http_client, &config);
+        client.connect(WsChannelType::Public).await.unwrap();
+        client
+            .subscribe(vec![
+                SubscribeArg::new(OkexWsChannel::Trades).inst_id("INVALID"),
+            ])
+            .await
+            .unwrap();
+
+        let mut service = service();
+        let mut trades = service.subscribe_trades();
+        let trade = tokio::select! {
+            result = service.run_okx(&mut client) => panic!("run_okx returned {result:?}"),
+            trade = trades.recv() => trade.unwrap(),
+        };
+        assert_eq!(trade.data.trade_id, "1");
+        assert_eq!(service.dropped_pushes(), 2);
+        assert!(matches!(
+            service.last_push_error(),
+            Some(Error::Exchange(quant_exchange::error::Error::SerdeError(_)))
+        ));
+    }
+}
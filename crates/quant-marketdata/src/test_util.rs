@@ -0,0 +1,58 @@
+//! 测试用的配置和数据构造
+
+use quant_config::{Config, Credentials, ExchangeConfig, GlobalConfig, OkexConfig};
+use quant_schema::Exchange;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub fn num<T: std::str::FromStr>(value: &str) -> T
+where
+    T::Err: std::fmt::Debug,
+{
+    value.parse().unwrap()
+}
+
+/// 只包含 OKX 的配置，REST 请求发往 `http_url`
+pub fn config(http_url: &str) -> Config {
+    okex_config(http_url, &[])
+}
+
+/// 只包含 OKX 的配置，public、private、business 频道都连接到 `ws_url`
+pub fn ws_config(ws_url: &str) -> Config {
+    okex_config("", &vec![ws_url.to_string(); 3])
+}
+
+fn okex_config(http_url: &str, ws_urls: &[String]) -> Config {
+    let okex = OkexConfig {
+        credentials: Credentials {
+            api_key: String::new(),
+            secret_key: String::new(),
+            passphrase: String::new(),
+            use_testnet: Some(false),
+            protocol: None,
+        },
+        http_urls: vec![http_url.to_string()],
+        ws_urls: ws_urls.to_vec(),
+        timeout: None,
+        retry_count: None,
+        retry_delay: None,
+        testnet_http_urls: None,
+        testnet_ws_urls: None,
+        ip_blacks: vec![],
+    };
+    Config {
+        exchanges: Mutex::new(HashMap::from([(
+            Exchange::Okex,
+            ExchangeConfig::Okex(okex),
+        )])),
+        default_exchange: Some(Exchange::Okex),
+        global_config: GlobalConfig {
+            timeout: 5000,
+            retry_count: 3,
+            log_level: None,
+            retry_delay: 10,
+            use_testnet: false,
+            protocol: None,
+        },
+    }
+}
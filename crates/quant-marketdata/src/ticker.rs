@@ -0,0 +1,412 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use quant_schema::{Decimal, Exchange, Interval, Price, Quantity, Symbol, Ticker, Trade};
+use std::collections::{HashMap, VecDeque};
+
+/// 滚动 24 小时行情统计，由逐笔成交和盘口最优价计算 `Ticker` 的全部字段
+///
+/// 交易所推送的 ticker 字段不一致（如 OKX 没有成交笔数和成交均价），统一由本地计算保证各交易所口径相同。
+/// 成交按 `resolution` 聚合到时间桶中，窗口边界的精度为一个桶；早于窗口的成交会被丢弃。
+#[derive(Debug)]
+pub struct TickerStats {
+    window: TimeDelta,
+    resolution: Interval,
+    states: HashMap<(Exchange, String), SymbolStats>,
+}
+
+/// 单个产品的统计状态
+#[derive(Debug, Default)]
+struct SymbolStats {
+    /// 按开始时间升序
+    buckets: VecDeque<Bucket>,
+    /// 最后一个移出窗口的桶的收盘价
+    prev_close: Option<Price>,
+    bid: Price,
+    ask: Price,
+    /// 最后一笔成交或盘口的时间
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// 一个时间桶内成交的汇总
+#[derive(Debug, Clone)]
+struct Bucket {
+    start: DateTime<Utc>,
+    first_at: DateTime<Utc>,
+    last_at: DateTime<Utc>,
+    open: Price,
+    high: Price,
+    low: Price,
+    close: Price,
+    /// 成交数量之和（币币为交易货币，衍生品为张数）
+    quantity: Decimal,
+    /// 价格 * 数量之和
+    value: Decimal,
+    /// 数量 / 价格之和，用于币本位合约换算成交易货币
+    inverse_value: Decimal,
+    count: u64,
+}
+
+impl Bucket {
+    fn new(start: DateTime<Utc>, trade: &Trade) -> Self {
+        let quantity = trade.quantity.value();
+        Bucket {
+            start,
+            first_at: trade.timestamp,
+            last_at: trade.timestamp,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            quantity,
+            value: trade.price * trade.quantity,
+            inverse_value: inverse(trade.price, quantity),
+            count: 1,
+        }
+    }
+
+    /// 合并一笔成交，迟到的成交也会按时间更新开盘价和收盘价
+    fn update(&mut self, trade: &Trade) {
+        if trade.timestamp < self.first_at {
+            self.first_at = trade.timestamp;
+            self.open = trade.price;
+        }
+        if trade.timestamp >= self.last_at {
+            self.last_at = trade.timestamp;
+            self.close = trade.price;
+        }
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        let quantity = trade.quantity.value();
+        self.quantity += quantity;
+        self.value += trade.price * trade.quantity;
+        self.inverse_value += inverse(trade.price, quantity);
+        self.count += 1;
+    }
+}
+
+impl Default for TickerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TickerStats {
+    pub fn new() -> Self {
+        Self {
+            window: TimeDelta::hours(24),
+            resolution: Interval::minutes(1),
+            states: HashMap::new(),
+        }
+    }
+
+    /// 统计窗口，默认 24 小时
+    pub fn window(mut self, window: TimeDelta) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// 时间桶的大小，默认 1 分钟
+    pub fn resolution(mut self, resolution: Interval) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// 处理一笔成交，完全早于窗口的迟到成交会被忽略
+    pub fn on_trade(&mut self, trade: &Trade) {
+        let window = self.window;
+        let resolution = self.resolution;
+        let state = self
+            .states
+            .entry((trade.exchange.clone(), trade.symbol.clone()))
+            .or_default();
+        let start = resolution.bucket_start(trade.timestamp);
+        if let Some(now) = state.updated_at
+            && start + resolution.duration() <= now - window
+        {
+            return;
+        }
+        match state
+            .buckets
+            .iter_mut()
+            .rev()
+            .find(|bucket| bucket.start <= start)
+        {
+            Some(bucket) if bucket.start == start => bucket.update(trade),
+            _ => {
+                let index = state.buckets.partition_point(|bucket| bucket.start < start);
+                state.buckets.insert(index, Bucket::new(start, trade));
+            }
+        }
+        state.updated_at = state.updated_at.max(Some(trade.timestamp));
+        if let Some(now) = state.updated_at {
+            state.evict(now - window, resolution);
+        }
+    }
+
+    /// 更新盘口最优价
+    pub fn on_book_top(
+        &mut self,
+        exchange: &Exchange,
+        symbol: &str,
+        bid: Price,
+        ask: Price,
+        timestamp: DateTime<Utc>,
+    ) {
+        let state = self
+            .states
+            .entry((exchange.clone(), symbol.to_string()))
+            .or_default();
+        state.bid = bid;
+        state.ask = ask;
+        state.updated_at = state.updated_at.max(Some(timestamp));
+    }
+
+    /// 截止到最后一次更新的统计，没有收到过成交时返回 `None`
+    pub fn ticker(&self, symbol: &Symbol) -> Option<Ticker> {
+        let updated_at = self.state(symbol)?.updated_at?;
+        self.ticker_at(symbol, updated_at)
+    }
+
+    /// 截止到 `now` 的统计，窗口内没有成交时成交量为 0，价格均为最新成交价
+    pub fn ticker_at(&self, symbol: &Symbol, now: DateTime<Utc>) -> Option<Ticker> {
+        let state = self.state(symbol)?;
+        let from = now - self.window;
+        let resolution = self.resolution.duration();
+        let index = state
+            .buckets
+            .partition_point(|bucket| bucket.start + resolution <= from);
+        let buckets = || state.buckets.range(index..);
+        // 窗口开始前的最后一个桶
+        let previous = index.checked_sub(1).and_then(|i| state.buckets.get(i));
+        let last_price = buckets()
+            .next_back()
+            .or(previous)
+            .map(|bucket| bucket.close)?;
+        let prev_close = previous.map(|bucket| bucket.close).or(state.prev_close);
+
+        let open_24h = buckets().next().map_or(last_price, |bucket| bucket.open);
+        let high_24h = buckets()
+            .map(|bucket| bucket.high)
+            .max()
+            .unwrap_or(last_price);
+        let low_24h = buckets()
+            .map(|bucket| bucket.low)
+            .min()
+            .unwrap_or(last_price);
+        let quantity: Decimal = buckets().map(|bucket| bucket.quantity).sum();
+        let value: Decimal = buckets().map(|bucket| bucket.value).sum();
+        let inverse_value: Decimal = buckets().map(|bucket| bucket.inverse_value).sum();
+        let count_24h = buckets().map(|bucket| bucket.count).sum();
+        let (volume_24h, quote_volumn_24h) = volumes(symbol, quantity, value, inverse_value);
+
+        let price_change_24h = last_price.value() - open_24h.value();
+        let price_change_percent_24h = if open_24h.is_zero() {
+            Decimal::ZERO
+        } else {
+            price_change_24h / open_24h.value() * Decimal::ONE_HUNDRED
+        };
+        let weighted_avg_price_24h = if volume_24h.is_zero() {
+            last_price
+        } else {
+            Price::new(quote_volumn_24h / volume_24h.value())
+        };
+        Some(Ticker {
+            symbol: symbol.clone(),
+            exchange: exchange_name(&symbol.exchange).to_string(),
+            timestamp: now,
+            last_price,
+            bid_price: state.bid,
+            ask_price: state.ask,
+            volume_24h,
+            quote_volumn_24h,
+            high_24h,
+            low_24h,
+            open_24h,
+            close_24h: last_price,
+            price_change_24h,
+            price_change_percent_24h,
+            weighted_avg_price_24h,
+            prev_close_price: prev_close.unwrap_or(open_24h),
+            count_24h,
+        })
+    }
+
+    fn state(&self, symbol: &Symbol) -> Option<&SymbolStats> {
+        self.states
+            .get(&(symbol.exchange.clone(), symbol.exchange_symbol.clone()))
+    }
+}
+
+impl SymbolStats {
+    /// 移出完全早于 `from` 的桶
+    fn evict(&mut self, from: DateTime<Utc>, resolution: Interval) {
+        // 至少保留一个桶，以便窗口内没有成交时仍有最新成交价
+        while self.buckets.len() > 1
+            && let Some(first) = self.buckets.front()
+            && first.start + resolution.duration() <= from
+        {
+            self.prev_close = self.buckets.pop_front().map(|bucket| bucket.close);
+        }
+    }
+}
+
+/// 换算为交易货币的成交量和计价货币的成交额
+///
+/// U 本位合约面值以交易货币计，币本位合约（结算货币为交易货币）面值以计价货币计。
+fn volumes(
+    symbol: &Symbol,
+    quantity: Decimal,
+    value: Decimal,
+    inverse_value: Decimal,
+) -> (Quantity, Decimal) {
+    match symbol.contract_value {
+        Some(contract_value) if symbol.is_derivative() => {
+            if symbol.settle_asset.as_deref() == Some(symbol.base_asset.as_str()) {
+                (
+                    Quantity::new(inverse_value * contract_value),
+                    quantity * contract_value,
+                )
+            } else {
+                (
+                    Quantity::new(quantity * contract_value),
+                    value * contract_value,
+                )
+            }
+        }
+        _ => (Quantity::new(quantity), value),
+    }
+}
+
+fn inverse(price: Price, quantity: Decimal) -> Decimal {
+    if price.is_zero() {
+        Decimal::ZERO
+    } else {
+        quantity / price.value()
+    }
+}
+
+fn exchange_name(exchange: &Exchange) -> &'static str {
+    match exchange {
+        Exchange::Okex => "okex",
+        Exchange::Binance => "binance",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_schema::{MarketType, Side};
+
+    fn symbol(exchange_symbol: &str, market_type: MarketType) -> Symbol {
+        let derivative = market_type != MarketType::Spot;
+        let inverse = exchange_symbol.starts_with("BTC-USD-");
+        Symbol {
+            name: exchange_symbol.to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: if inverse { "USD" } else { "USDT" }.to_string(),
+            exchange: Exchange::Okex,
+            exchange_symbol: exchange_symbol.to_string(),
+            market_type,
+            status: "live".to_string(),
+            min_qty: Quantity::ZERO,
+            lot_size: Quantity::ZERO,
+            tick_size: Price::ZERO,
+            min_notional: Decimal::ZERO,
+            contract_value: derivative.then(|| {
+                if inverse {
+                    Decimal::ONE_HUNDRED
+                } else {
+                    "0.01".parse().unwrap()
+                }
+            }),
+            settle_asset: derivative.then(|| if inverse { "BTC" } else { "USDT" }.to_string()),
+            expiry: None,
+        }
+    }
+
+    fn trade(symbol: &str, minutes: i64, price: &str, quantity: &str) -> Trade {
+        Trade {
+            exchange: Exchange::Okex,
+            symbol: symbol.to_string(),
+            trade_id: minutes.to_string(),
+            price: price.parse().unwrap(),
+            quantity: quantity.parse().unwrap(),
+            side: Side::Buy,
+            timestamp: DateTime::from_timestamp(minutes * 60, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let spot = symbol("BTC-USDT", MarketType::Spot);
+        let mut stats = TickerStats::new();
+        assert!(stats.ticker(&spot).is_none());
+
+        stats.on_trade(&trade("BTC-USDT", 0, "100", "1"));
+        stats.on_trade(&trade("BTC-USDT", 60, "120", "1"));
+        stats.on_trade(&trade("BTC-USDT", 600, "90", "2"));
+        let at = DateTime::from_timestamp(600 * 60, 0).unwrap();
+        stats.on_book_top(
+            &Exchange::Okex,
+            "BTC-USDT",
+            "89".parse().unwrap(),
+            "91".parse().unwrap(),
+            at,
+        );
+        let ticker = stats.ticker(&spot).unwrap();
+        assert_eq!(ticker.count_24h, 3);
+        assert_eq!(ticker.open_24h, "100".parse().unwrap());
+        assert_eq!(ticker.high_24h, "120".parse().unwrap());
+        assert_eq!(ticker.low_24h, "90".parse().unwrap());
+        assert_eq!(ticker.volume_24h, "4".parse().unwrap());
+        assert_eq!(ticker.quote_volumn_24h, "400".parse::<Decimal>().unwrap());
+        assert_eq!(ticker.weighted_avg_price_24h, "100".parse().unwrap());
+        assert_eq!(ticker.price_change_24h, "-10".parse::<Decimal>().unwrap());
+        assert_eq!(
+            ticker.price_change_percent_24h,
+            "-10".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(ticker.bid_price, "89".parse().unwrap());
+        assert_eq!(ticker.prev_close_price, "100".parse().unwrap());
+
+        // 第一笔成交移出窗口
+        stats.on_trade(&trade("BTC-USDT", 24 * 60 + 30, "95", "1"));
+        let ticker = stats.ticker(&spot).unwrap();
+        assert_eq!(ticker.count_24h, 3);
+        assert_eq!(ticker.open_24h, "120".parse().unwrap());
+        assert_eq!(ticker.prev_close_price, "100".parse().unwrap());
+        assert_eq!(ticker.last_price, "95".parse().unwrap());
+
+        // 迟到的成交计入所在的桶，早于窗口的被忽略
+        stats.on_trade(&trade("BTC-USDT", 300, "130", "1"));
+        stats.on_trade(&trade("BTC-USDT", 1, "1", "1"));
+        let ticker = stats.ticker(&spot).unwrap();
+        assert_eq!(ticker.count_24h, 4);
+        assert_eq!(ticker.high_24h, "130".parse().unwrap());
+        assert_eq!(ticker.last_price, "95".parse().unwrap());
+
+        // 窗口内没有成交
+        let later = DateTime::from_timestamp(3 * 24 * 3600, 0).unwrap();
+        let ticker = stats.ticker_at(&spot, later).unwrap();
+        assert_eq!(ticker.count_24h, 0);
+        assert!(ticker.volume_24h.is_zero());
+        assert_eq!(ticker.open_24h, "95".parse().unwrap());
+        assert_eq!(ticker.prev_close_price, "95".parse().unwrap());
+    }
+
+    #[test]
+    fn test_contract_volume() {
+        let mut stats = TickerStats::new();
+        stats.on_trade(&trade("BTC-USDT-SWAP", 0, "50000", "10"));
+        let swap = symbol("BTC-USDT-SWAP", MarketType::Swap);
+        let ticker = stats.ticker(&swap).unwrap();
+        assert_eq!(ticker.volume_24h, "0.1".parse().unwrap());
+        assert_eq!(ticker.quote_volumn_24h, "5000".parse::<Decimal>().unwrap());
+
+        stats.on_trade(&trade("BTC-USD-250328", 0, "50000", "10"));
+        let futures = symbol("BTC-USD-250328", MarketType::Futures);
+        let ticker = stats.ticker(&futures).unwrap();
+        assert_eq!(ticker.volume_24h, "0.02".parse().unwrap());
+        assert_eq!(ticker.quote_volumn_24h, "1000".parse::<Decimal>().unwrap());
+        assert_eq!(ticker.weighted_avg_price_24h, "50000".parse().unwrap());
+    }
+}
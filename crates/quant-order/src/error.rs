@@ -0,0 +1,16 @@
+use quant_schema::Exchange;
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Exchange(#[from] quant_exchange::error::Error),
+    #[error("No trading venue registered for {0:?}")]
+    UnknownExchange(Exchange),
+    #[error("Order {0} is not tracked")]
+    UnknownOrder(String),
+    #[error("Fill {0} has already been applied")]
+    DuplicateFill(String),
+}
@@ -0,0 +1,6 @@
+pub mod error;
+pub mod router;
+pub mod tracker;
+
+pub use router::OrderRouter;
+pub use tracker::OrderTracker;
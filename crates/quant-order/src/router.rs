@@ -0,0 +1,235 @@
+use crate::error::{Error, Result};
+use crate::tracker::OrderTracker;
+use chrono::{DateTime, Utc};
+use quant_exchange::TradingVenue;
+use quant_schema::{Exchange, Fill, Order, OrderAck, OrderId, OrderRequest, OrderStatus};
+use std::collections::HashMap;
+
+/// 订单路由：把订单请求发送到对应交易所的 `TradingVenue`，并跟踪订单状态
+///
+/// 没有指定客户端订单ID的请求会自动分配一个，订单状态见 [`OrderTracker`]。
+pub struct OrderRouter<'a> {
+    venues: HashMap<Exchange, Box<dyn TradingVenue + Send + 'a>>,
+    tracker: OrderTracker,
+}
+
+impl Default for OrderRouter<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> OrderRouter<'a> {
+    pub fn new() -> Self {
+        Self {
+            venues: HashMap::new(),
+            tracker: OrderTracker::new(),
+        }
+    }
+
+    /// 注册交易所，同一交易所重复注册时替换旧的
+    pub fn add_venue(&mut self, venue: impl TradingVenue + Send + 'a) {
+        self.venues.insert(venue.exchange(), Box::new(venue));
+    }
+
+    /// 已注册的交易所
+    pub fn exchanges(&self) -> impl Iterator<Item = &Exchange> {
+        self.venues.keys()
+    }
+
+    pub fn venue_mut(
+        &mut self,
+        exchange: &Exchange,
+    ) -> Result<&mut (dyn TradingVenue + Send + 'a)> {
+        match self.venues.get_mut(exchange) {
+            Some(venue) => Ok(venue.as_mut()),
+            None => Err(Error::UnknownExchange(exchange.clone())),
+        }
+    }
+
+    /// 下单，返回交易所接受后的订单；失败时订单以 `Rejected` 状态记录
+    pub async fn submit(
+        &mut self,
+        exchange: &Exchange,
+        mut request: OrderRequest,
+    ) -> Result<Order> {
+        request
+            .client_order_id
+            .get_or_insert_with(new_client_order_id);
+        let ack = match self.venue_mut(exchange) {
+            Ok(venue) => venue.submit_order(&request).await.map_err(Error::from),
+            Err(err) => Err(err),
+        };
+        let ack = match ack {
+            Ok(ack) => ack,
+            Err(err) => {
+                self.tracker
+                    .insert(Order::rejected(exchange.clone(), &request, Utc::now()));
+                return Err(err);
+            }
+        };
+        let order = Order::new(&request, &ack);
+        self.tracker.insert(order.clone());
+        Ok(order)
+    }
+
+    /// 撤单，成功后订单状态改为 `Canceled`
+    pub async fn cancel(
+        &mut self,
+        exchange: &Exchange,
+        symbol: &str,
+        order_id: &OrderId,
+    ) -> Result<OrderAck> {
+        let ack = self.venue_mut(exchange)?.cancel(symbol, order_id).await?;
+        self.tracker.on_canceled(exchange, order_id, ack.timestamp);
+        Ok(ack)
+    }
+
+    /// 处理成交回报，返回更新后的订单
+    pub fn apply_fill(&mut self, fill: &Fill) -> Result<&Order> {
+        self.tracker.apply_fill(fill)
+    }
+
+    /// 处理交易所推送的订单状态变化，见 [`OrderTracker::on_status`]
+    pub fn on_status(
+        &mut self,
+        exchange: &Exchange,
+        order_id: &OrderId,
+        status: OrderStatus,
+        at: DateTime<Utc>,
+    ) -> Option<&Order> {
+        self.tracker.on_status(exchange, order_id, status, at)
+    }
+
+    pub fn tracker(&self) -> &OrderTracker {
+        &self.tracker
+    }
+}
+
+/// 32 位字母数字，满足 OKX `clOrdId` 和 Binance `newClientOrderId` 的格式要求
+pub fn new_client_order_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use quant_schema::{Balance, Position, Side};
+
+    /// 接受所有请求的交易所，订单ID依次递增
+    #[derive(Default)]
+    struct MockVenue {
+        submitted: Vec<OrderRequest>,
+    }
+
+    #[async_trait]
+    impl TradingVenue for MockVenue {
+        fn exchange(&self) -> Exchange {
+            Exchange::Okex
+        }
+
+        async fn submit_order(
+            &mut self,
+            order: &OrderRequest,
+        ) -> quant_exchange::error::Result<OrderAck> {
+            self.submitted.push(order.clone());
+            Ok(OrderAck {
+                exchange: Exchange::Okex,
+                symbol: order.symbol.clone(),
+                order_id: self.submitted.len().to_string(),
+                client_order_id: order.client_order_id.clone(),
+                timestamp: Utc::now(),
+            })
+        }
+
+        async fn cancel(
+            &mut self,
+            symbol: &str,
+            order_id: &OrderId,
+        ) -> quant_exchange::error::Result<OrderAck> {
+            let order_id = match order_id {
+                OrderId::Exchange(id) | OrderId::Client(id) => id.clone(),
+            };
+            Ok(OrderAck {
+                exchange: Exchange::Okex,
+                symbol: symbol.to_string(),
+                order_id,
+                client_order_id: None,
+                timestamp: Utc::now(),
+            })
+        }
+
+        async fn balances(&mut self) -> quant_exchange::error::Result<Vec<Balance>> {
+            Ok(Vec::new())
+        }
+
+        async fn positions(&mut self) -> quant_exchange::error::Result<Vec<Position>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe_book(&mut self, _symbol: &str) -> quant_exchange::error::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_fill_cancel() {
+        let mut router = OrderRouter::new();
+        router.add_venue(MockVenue::default());
+        let request = OrderRequest::limit(
+            "BTC-USDT",
+            Side::Buy,
+            "2".parse().unwrap(),
+            "100".parse().unwrap(),
+        );
+        let order = router.submit(&Exchange::Okex, request).await.unwrap();
+        let client_order_id = order.client_order_id.clone().unwrap();
+        assert_eq!(client_order_id.len(), 32);
+        assert_eq!(order.order_id, "1");
+
+        // 只带交易所订单ID的成交
+        let fill = Fill {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: "t1".to_string(),
+            order_id: "1".to_string(),
+            client_order_id: None,
+            side: Side::Buy,
+            price: "100".parse().unwrap(),
+            quantity: "1".parse().unwrap(),
+            fee: Default::default(),
+            fee_asset: "BTC".to_string(),
+            is_maker: true,
+            timestamp: Utc::now(),
+        };
+        let order = router.apply_fill(&fill).unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(router.tracker().open_orders().count(), 1);
+
+        router
+            .cancel(
+                &Exchange::Okex,
+                "BTC-USDT",
+                &OrderId::Exchange("1".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            router.tracker().order(&client_order_id).unwrap().status,
+            OrderStatus::Canceled
+        );
+        assert_eq!(router.tracker().open_orders().count(), 0);
+
+        let request = OrderRequest::market("BTCUSDT", Side::Sell, "1".parse().unwrap())
+            .client_order_id("rejected");
+        assert!(matches!(
+            router.submit(&Exchange::Binance, request).await,
+            Err(Error::UnknownExchange(Exchange::Binance))
+        ));
+        assert_eq!(
+            router.tracker().order("rejected").unwrap().status,
+            OrderStatus::Rejected
+        );
+    }
+}
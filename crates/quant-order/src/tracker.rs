@@ -0,0 +1,213 @@
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use quant_schema::{Exchange, Fill, Order, OrderId, OrderStatus};
+use std::collections::{HashMap, HashSet};
+
+/// 订单状态，以客户端订单ID为键，成交回报按客户端订单ID或交易所订单ID匹配
+#[derive(Debug, Default, Clone)]
+pub struct OrderTracker {
+    orders: HashMap<String, Order>,
+    /// 交易所订单ID到客户端订单ID
+    exchange_ids: HashMap<(Exchange, String), String>,
+    /// 已处理的成交ID，包括不属于任何订单的成交
+    trade_ids: HashSet<(Exchange, String)>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 跟踪一个订单，没有客户端订单ID的订单以交易所订单ID为键
+    pub fn insert(&mut self, order: Order) {
+        let client_order_id = order
+            .client_order_id
+            .clone()
+            .unwrap_or_else(|| order.order_id.clone());
+        // 被拒绝的订单没有交易所订单ID
+        if !order.order_id.is_empty() {
+            self.exchange_ids.insert(
+                (order.exchange.clone(), order.order_id.clone()),
+                client_order_id.clone(),
+            );
+        }
+        self.orders.insert(client_order_id, order);
+    }
+
+    pub fn order(&self, client_order_id: &str) -> Option<&Order> {
+        self.orders.get(client_order_id)
+    }
+
+    /// 按交易所订单ID或客户端订单ID查找订单
+    pub fn find(&self, exchange: &Exchange, order_id: &OrderId) -> Option<&Order> {
+        self.client_order_id(exchange, order_id)
+            .and_then(|id| self.orders.get(&id))
+    }
+
+    /// 未完成的订单
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.values().filter(|order| order.status.is_open())
+    }
+
+    /// 撤单成功，未完成的订单状态改为 `Canceled`
+    pub fn on_canceled(&mut self, exchange: &Exchange, order_id: &OrderId, at: DateTime<Utc>) {
+        self.on_status(exchange, order_id, OrderStatus::Canceled, at);
+    }
+
+    /// 交易所推送的状态变化，如撤单、过期；只更新未完成的订单，返回更新后的订单
+    pub fn on_status(
+        &mut self,
+        exchange: &Exchange,
+        order_id: &OrderId,
+        status: OrderStatus,
+        at: DateTime<Utc>,
+    ) -> Option<&Order> {
+        let id = self.client_order_id(exchange, order_id)?;
+        let order = self.orders.get_mut(&id)?;
+        if !order.status.is_open() {
+            return None;
+        }
+        order.status = status;
+        order.updated_at = at;
+        Some(order)
+    }
+
+    /// 处理成交回报，返回更新后的订单
+    ///
+    /// 同一成交ID再次推送时返回 `DuplicateFill`，没有成交ID的成交不去重
+    pub fn apply_fill(&mut self, fill: &Fill) -> Result<&Order> {
+        if !fill.trade_id.is_empty()
+            && !self
+                .trade_ids
+                .insert((fill.exchange.clone(), fill.trade_id.clone()))
+        {
+            return Err(Error::DuplicateFill(fill.trade_id.clone()));
+        }
+        let client_order_id = fill
+            .client_order_id
+            .clone()
+            .filter(|id| self.orders.contains_key(id))
+            .or_else(|| {
+                self.exchange_ids
+                    .get(&(fill.exchange.clone(), fill.order_id.clone()))
+                    .cloned()
+            })
+            .ok_or_else(|| Error::UnknownOrder(fill.order_id.clone()))?;
+        let order = self
+            .orders
+            .get_mut(&client_order_id)
+            .ok_or(Error::UnknownOrder(client_order_id))?;
+        order.apply_fill(fill);
+        Ok(order)
+    }
+
+    fn client_order_id(&self, exchange: &Exchange, order_id: &OrderId) -> Option<String> {
+        match order_id {
+            OrderId::Client(id) => Some(id.clone()),
+            OrderId::Exchange(id) => self
+                .exchange_ids
+                .get(&(exchange.clone(), id.clone()))
+                .cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_schema::{Decimal, OrderRequest, Side};
+
+    fn order(client_order_id: &str, order_id: &str) -> Order {
+        let request = OrderRequest::limit(
+            "BTC-USDT",
+            Side::Buy,
+            "2".parse().unwrap(),
+            "100".parse().unwrap(),
+        )
+        .client_order_id(client_order_id);
+        let mut order = Order::rejected(Exchange::Okex, &request, DateTime::UNIX_EPOCH);
+        order.order_id = order_id.to_string();
+        order.status = OrderStatus::New;
+        order
+    }
+
+    fn fill(trade_id: &str, client_order_id: Option<&str>, order_id: &str) -> Fill {
+        Fill {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: trade_id.to_string(),
+            order_id: order_id.to_string(),
+            client_order_id: client_order_id.map(str::to_string),
+            side: Side::Buy,
+            price: "100".parse().unwrap(),
+            quantity: "1".parse().unwrap(),
+            fee: Decimal::ZERO,
+            fee_asset: "USDT".to_string(),
+            is_maker: true,
+            timestamp: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_apply_fill() {
+        let mut tracker = OrderTracker::new();
+        tracker.insert(order("c1", "1"));
+
+        // 按客户端订单ID匹配
+        let updated = tracker.apply_fill(&fill("t1", Some("c1"), "1")).unwrap();
+        assert_eq!(updated.status, OrderStatus::PartiallyFilled);
+        // 重复推送的成交不再计入
+        assert!(matches!(
+            tracker.apply_fill(&fill("t1", Some("c1"), "1")),
+            Err(Error::DuplicateFill(id)) if id == "t1"
+        ));
+        // 没有客户端订单ID时按交易所订单ID匹配
+        let updated = tracker.apply_fill(&fill("t2", None, "1")).unwrap();
+        assert_eq!(updated.status, OrderStatus::Filled);
+        assert_eq!(updated.filled_quantity, "2".parse().unwrap());
+        assert_eq!(tracker.open_orders().count(), 0);
+
+        assert!(matches!(
+            tracker.apply_fill(&fill("t3", None, "9")),
+            Err(Error::UnknownOrder(id)) if id == "9"
+        ));
+        assert!(matches!(
+            tracker.apply_fill(&fill("t3", None, "9")),
+            Err(Error::DuplicateFill(_))
+        ));
+    }
+
+    #[test]
+    fn test_on_status() {
+        let mut tracker = OrderTracker::new();
+        tracker.insert(order("c1", "1"));
+        tracker.insert(order("c2", "2"));
+
+        let exchange = Exchange::Okex;
+        let at = DateTime::UNIX_EPOCH + chrono::TimeDelta::seconds(1);
+        tracker.on_canceled(&exchange, &OrderId::Exchange("1".to_string()), at);
+        let order = tracker.order("c1").unwrap();
+        assert_eq!(
+            (order.status, order.updated_at),
+            (OrderStatus::Canceled, at)
+        );
+        // 已完成的订单不再更新
+        assert!(
+            tracker
+                .on_status(
+                    &exchange,
+                    &OrderId::Client("c1".to_string()),
+                    OrderStatus::Expired,
+                    at
+                )
+                .is_none()
+        );
+        assert_eq!(
+            tracker
+                .find(&exchange, &OrderId::Client("c2".to_string()))
+                .map(|order| order.order_id.as_str()),
+            Some("2")
+        );
+        assert_eq!(tracker.open_orders().count(), 1);
+    }
+}
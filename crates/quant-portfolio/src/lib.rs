@@ -0,0 +1,327 @@
+use quant_schema::{
+    Balance, Decimal, Exchange, Fill, Position, PositionSide, Price, Quantity, Side, Symbol,
+};
+use std::collections::HashMap;
+
+/// 账户视图：各交易所的余额和持仓
+///
+/// 启动时用交易所返回的余额和持仓初始化，之后按成交增量更新持仓。
+/// 持仓按单向持仓模式计算，同一产品只有一个方向，反向成交先平仓再开仓。
+/// 合约的盈亏按 U 本位计算，为价差 × 张数 × 面值，面值取自 [`Portfolio::set_symbol`] 登记的产品信息。
+///
+/// 以计价货币收取的手续费从已实现盈亏中扣除，以基础货币收取的（如 OKX 现货买入）按成交价换算后扣除；
+/// 其它币种或没有登记产品信息时无法换算，按币种累计，见 [`Portfolio::fee`]。
+#[derive(Debug, Default, Clone)]
+pub struct Portfolio {
+    positions: HashMap<(Exchange, String), Position>,
+    balances: HashMap<(Exchange, String), Balance>,
+    /// 平仓产生的已实现盈亏减去手续费，以计价货币计
+    realized_pnl: HashMap<(Exchange, String), Decimal>,
+    /// 无法换算成计价货币的手续费，以 (交易所, 币种) 为键
+    fees: HashMap<(Exchange, String), Decimal>,
+    /// 产品信息，用于取得合约面值和手续费的换算方式
+    symbols: HashMap<(Exchange, String), Symbol>,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn position(&self, exchange: &Exchange, symbol: &str) -> Option<&Position> {
+        self.positions.get(&(exchange.clone(), symbol.to_string()))
+    }
+
+    /// 带方向的持仓数量，多头为正、空头为负，没有持仓时为 0
+    pub fn net_quantity(&self, exchange: &Exchange, symbol: &str) -> Quantity {
+        self.position(exchange, symbol)
+            .map_or(Quantity::ZERO, signed_quantity)
+    }
+
+    /// 全部持仓
+    pub fn positions(&self) -> impl Iterator<Item = (&Exchange, &Position)> {
+        self.positions
+            .iter()
+            .map(|((exchange, _), position)| (exchange, position))
+    }
+
+    pub fn balance(&self, exchange: &Exchange, asset: &str) -> Option<&Balance> {
+        self.balances.get(&(exchange.clone(), asset.to_string()))
+    }
+
+    /// 每张合约的面值，现货和没有登记的产品为 1
+    pub fn multiplier(&self, exchange: &Exchange, symbol: &str) -> Decimal {
+        self.symbols
+            .get(&(exchange.clone(), symbol.to_string()))
+            .and_then(|symbol| symbol.contract_value.filter(|_| symbol.is_derivative()))
+            .unwrap_or(Decimal::ONE)
+    }
+
+    /// 某个币种累计的、没有计入已实现盈亏的手续费，负数为返佣
+    pub fn fee(&self, exchange: &Exchange, asset: &str) -> Decimal {
+        self.fees
+            .get(&(exchange.clone(), asset.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// 已实现盈亏，已扣除能换算成计价货币的手续费
+    pub fn realized_pnl(&self, exchange: &Exchange, symbol: &str) -> Decimal {
+        self.realized_pnl
+            .get(&(exchange.clone(), symbol.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// 登记产品信息，之后的成交按合约面值计算已实现盈亏，并按计价货币和基础货币换算手续费
+    pub fn set_symbol(&mut self, symbol: Symbol) {
+        self.symbols.insert(
+            (symbol.exchange.clone(), symbol.exchange_symbol.clone()),
+            symbol,
+        );
+    }
+
+    /// 用交易所返回的持仓替换某个交易所的全部持仓
+    pub fn set_positions(&mut self, exchange: &Exchange, positions: Vec<Position>) {
+        self.positions.retain(|(key, _), _| key != exchange);
+        for position in positions {
+            self.positions
+                .insert((exchange.clone(), position.symbol.clone()), position);
+        }
+    }
+
+    /// 用交易所返回的余额替换某个交易所的全部余额
+    pub fn set_balances(&mut self, exchange: &Exchange, balances: Vec<Balance>) {
+        self.balances.retain(|(key, _), _| key != exchange);
+        for balance in balances {
+            self.balances
+                .insert((exchange.clone(), balance.asset.clone()), balance);
+        }
+    }
+
+    /// 按成交更新持仓数量、开仓均价和已实现盈亏，手续费从已实现盈亏中扣除
+    ///
+    /// 不检查重复的成交，调用方需要按成交ID去重
+    pub fn apply_fill(&mut self, fill: &Fill) {
+        let key = (fill.exchange.clone(), fill.symbol.clone());
+        let multiplier = self.multiplier(&fill.exchange, &fill.symbol);
+        self.apply_fee(fill);
+        let fill_quantity = match fill.side {
+            Side::Buy => fill.quantity,
+            Side::Sell => -fill.quantity,
+        };
+        let (current, entry_price, leverage) = match self.positions.get(&key) {
+            Some(position) => (
+                signed_quantity(position),
+                position.entry_price,
+                position.leverage,
+            ),
+            None => (Quantity::ZERO, Price::ZERO, None),
+        };
+        let next = current + fill_quantity;
+
+        let same_direction =
+            current.is_zero() || (current > Quantity::ZERO) == (fill.side == Side::Buy);
+        let entry_price = if same_direction {
+            // 加仓，按数量加权计算开仓均价
+            let total = current.abs() + fill.quantity;
+            Price::new((entry_price * current.abs() + fill.price * fill.quantity) / total.value())
+        } else {
+            let closed = current.abs().min(fill.quantity);
+            let pnl = (fill.price - entry_price) * closed * multiplier;
+            let pnl = if current > Quantity::ZERO { pnl } else { -pnl };
+            *self.realized_pnl.entry(key.clone()).or_default() += pnl;
+            if (next > Quantity::ZERO) == (current > Quantity::ZERO) {
+                entry_price
+            } else {
+                // 反手，剩余部分按成交价开仓
+                fill.price
+            }
+        };
+
+        if next.is_zero() {
+            self.positions.remove(&key);
+            return;
+        }
+        let position = self.positions.entry(key).or_insert_with(|| Position {
+            symbol: fill.symbol.clone(),
+            side: PositionSide::Long,
+            quantity: Quantity::ZERO,
+            entry_price,
+            unrealized_pnl: Decimal::ZERO,
+            leverage,
+        });
+        position.side = if next > Quantity::ZERO {
+            PositionSide::Long
+        } else {
+            PositionSide::Short
+        };
+        position.quantity = next.abs();
+        position.entry_price = entry_price;
+    }
+}
+
+impl Portfolio {
+    /// 手续费换算成计价货币后从已实现盈亏中扣除，无法换算的按币种累计
+    fn apply_fee(&mut self, fill: &Fill) {
+        let key = (fill.exchange.clone(), fill.symbol.clone());
+        let fee = self.symbols.get(&key).and_then(|symbol| {
+            if fill.fee_asset == symbol.quote_asset {
+                Some(fill.fee)
+            } else if fill.fee_asset == symbol.base_asset {
+                Some(fill.fee * fill.price.value())
+            } else {
+                None
+            }
+        });
+        match fee {
+            Some(fee) => *self.realized_pnl.entry(key).or_default() -= fee,
+            None => {
+                *self
+                    .fees
+                    .entry((fill.exchange.clone(), fill.fee_asset.clone()))
+                    .or_default() += fill.fee
+            }
+        }
+    }
+}
+
+fn signed_quantity(position: &Position) -> Quantity {
+    match position.side {
+        PositionSide::Long => position.quantity,
+        PositionSide::Short => -position.quantity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_schema::MarketType;
+
+    fn fill(side: Side, price: &str, quantity: &str) -> Fill {
+        Fill {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT-SWAP".to_string(),
+            trade_id: String::new(),
+            order_id: String::new(),
+            client_order_id: None,
+            side,
+            price: price.parse().unwrap(),
+            quantity: quantity.parse().unwrap(),
+            fee: Decimal::ZERO,
+            fee_asset: "USDT".to_string(),
+            is_maker: false,
+            timestamp: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_apply_fill() {
+        let mut portfolio = Portfolio::new();
+        let exchange = Exchange::Okex;
+        portfolio.apply_fill(&fill(Side::Buy, "100", "1"));
+        portfolio.apply_fill(&fill(Side::Buy, "130", "2"));
+        let position = portfolio.position(&exchange, "BTC-USDT-SWAP").unwrap();
+        assert_eq!(position.quantity, "3".parse().unwrap());
+        assert_eq!(position.entry_price, "120".parse().unwrap());
+
+        // 平仓 1，剩余多头开仓均价不变
+        portfolio.apply_fill(&fill(Side::Sell, "150", "1"));
+        assert_eq!(
+            portfolio.net_quantity(&exchange, "BTC-USDT-SWAP"),
+            "2".parse().unwrap()
+        );
+        assert_eq!(
+            portfolio.realized_pnl(&exchange, "BTC-USDT-SWAP"),
+            "30".parse::<Decimal>().unwrap()
+        );
+
+        // 反手为空头
+        portfolio.apply_fill(&fill(Side::Sell, "110", "3"));
+        let position = portfolio.position(&exchange, "BTC-USDT-SWAP").unwrap();
+        assert_eq!(position.side, PositionSide::Short);
+        assert_eq!(position.quantity, "1".parse().unwrap());
+        assert_eq!(position.entry_price, "110".parse().unwrap());
+        assert_eq!(
+            portfolio.realized_pnl(&exchange, "BTC-USDT-SWAP"),
+            "10".parse::<Decimal>().unwrap()
+        );
+
+        portfolio.apply_fill(&fill(Side::Buy, "100", "1"));
+        assert!(portfolio.position(&exchange, "BTC-USDT-SWAP").is_none());
+        assert_eq!(
+            portfolio.realized_pnl(&exchange, "BTC-USDT-SWAP"),
+            "20".parse::<Decimal>().unwrap()
+        );
+    }
+
+    fn symbol(exchange_symbol: &str, market_type: MarketType, contract_value: &str) -> Symbol {
+        Symbol {
+            name: "BTC/USDT".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USDT".to_string(),
+            exchange: Exchange::Okex,
+            exchange_symbol: exchange_symbol.to_string(),
+            market_type,
+            status: "live".to_string(),
+            min_qty: Quantity::ZERO,
+            lot_size: Quantity::ZERO,
+            tick_size: Price::ZERO,
+            min_notional: Decimal::ZERO,
+            contract_value: Some(contract_value.parse().unwrap()),
+            settle_asset: None,
+            expiry: None,
+        }
+    }
+
+    #[test]
+    fn test_multiplier_and_fees() {
+        let mut portfolio = Portfolio::new();
+        let exchange = Exchange::Okex;
+        portfolio.set_symbol(symbol("BTC-USDT-SWAP", MarketType::Swap, "0.01"));
+        let mut open = fill(Side::Buy, "100", "10");
+        open.fee = "0.5".parse().unwrap();
+        portfolio.apply_fill(&open);
+        assert_eq!(
+            portfolio.realized_pnl(&exchange, "BTC-USDT-SWAP"),
+            "-0.5".parse::<Decimal>().unwrap()
+        );
+
+        // 10 张 × 0.01 × 价差 20 = 2，返佣 0.1
+        let mut close = fill(Side::Sell, "120", "10");
+        close.fee = "-0.1".parse().unwrap();
+        portfolio.apply_fill(&close);
+        assert!(portfolio.position(&exchange, "BTC-USDT-SWAP").is_none());
+        assert_eq!(
+            portfolio.realized_pnl(&exchange, "BTC-USDT-SWAP"),
+            "1.6".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(portfolio.multiplier(&exchange, "BTC-USDT"), Decimal::ONE);
+    }
+
+    #[test]
+    fn test_base_asset_fees() {
+        let mut portfolio = Portfolio::new();
+        let exchange = Exchange::Okex;
+        // 现货忽略面值
+        portfolio.set_symbol(symbol("BTC-USDT", MarketType::Spot, "100"));
+        let mut buy = fill(Side::Buy, "20000", "1");
+        buy.symbol = "BTC-USDT".to_string();
+        buy.fee = "0.001".parse().unwrap();
+        buy.fee_asset = "BTC".to_string();
+        portfolio.apply_fill(&buy);
+        // 0.001 BTC 按成交价换算为 20 USDT
+        assert_eq!(
+            portfolio.realized_pnl(&exchange, "BTC-USDT"),
+            "-20".parse::<Decimal>().unwrap()
+        );
+        assert!(portfolio.fee(&exchange, "BTC").is_zero());
+
+        // 没有登记的产品无法换算，按币种累计
+        buy.symbol = "ETH-USDT".to_string();
+        buy.fee_asset = "ETH".to_string();
+        portfolio.apply_fill(&buy);
+        assert!(portfolio.realized_pnl(&exchange, "ETH-USDT").is_zero());
+        assert_eq!(portfolio.fee(&exchange, "ETH"), "0.001".parse().unwrap());
+    }
+}
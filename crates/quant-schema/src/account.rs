@@ -0,0 +1,51 @@
+use crate::{Price, Quantity};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Balance of a single asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    /// Asset name, e.g. "BTC"
+    pub asset: String,
+
+    /// Amount available for trading
+    pub free: Decimal,
+
+    /// Amount frozen by open orders or margin
+    pub locked: Decimal,
+}
+
+impl Balance {
+    pub fn total(&self) -> Decimal {
+        self.free + self.locked
+    }
+}
+
+/// Direction of a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PositionSide {
+    Long,
+    Short,
+}
+
+/// Open derivatives or margin position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    /// Instrument id on the exchange, e.g. "BTC-USDT-SWAP"
+    pub symbol: String,
+
+    pub side: PositionSide,
+
+    /// Position size, always positive
+    pub quantity: Quantity,
+
+    /// Average entry price
+    pub entry_price: Price,
+
+    /// Unrealized profit and loss in the settlement asset
+    pub unrealized_pnl: Decimal,
+
+    /// Leverage reported by the exchange, `None` when it is not reported or
+    /// the position is tracked locally from fills
+    pub leverage: Option<Decimal>,
+}
@@ -0,0 +1,260 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Direction used when snapping a value to a tick or lot size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero, e.g. bid prices and order quantities
+    Down,
+
+    /// Away from zero, e.g. ask prices
+    Up,
+
+    /// To the nearest step, halfway values round away from zero
+    Nearest,
+}
+
+impl Rounding {
+    fn strategy(&self) -> RoundingStrategy {
+        match self {
+            Rounding::Down => RoundingStrategy::ToZero,
+            Rounding::Up => RoundingStrategy::AwayFromZero,
+            Rounding::Nearest => RoundingStrategy::MidpointAwayFromZero,
+        }
+    }
+}
+
+/// Round `value` to a multiple of `step`, a non-positive step leaves the value unchanged
+fn round_to_step(value: Decimal, step: Decimal, rounding: Rounding) -> Decimal {
+    if step <= Decimal::ZERO {
+        return value;
+    }
+    let steps = (value / step).round_dp_with_strategy(0, rounding.strategy());
+    (steps * step).normalize()
+}
+
+macro_rules! decimal_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(Decimal);
+
+        impl $name {
+            pub const ZERO: Self = Self(Decimal::ZERO);
+
+            pub const fn new(value: Decimal) -> Self {
+                Self(value)
+            }
+
+            /// The underlying decimal value
+            pub fn value(&self) -> Decimal {
+                self.0
+            }
+
+            pub fn is_zero(&self) -> bool {
+                self.0.is_zero()
+            }
+
+            pub fn abs(&self) -> Self {
+                Self(self.0.abs())
+            }
+        }
+
+        impl From<Decimal> for $name {
+            fn from(value: Decimal) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for Decimal {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = rust_decimal::Error;
+
+            /// Parses exchange strings such as "25000.5", exponent notation is accepted as well
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Decimal::from_str(value)
+                    .or_else(|_| Decimal::from_scientific(value))
+                    .map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl Mul<Decimal> for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: Decimal) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+
+        impl Div<Decimal> for $name {
+            type Output = Self;
+
+            fn div(self, rhs: Decimal) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+
+        impl std::iter::Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, Add::add)
+            }
+        }
+    };
+}
+
+decimal_newtype!(
+    /// Exact price, serialized as a string like the exchanges do
+    Price
+);
+
+decimal_newtype!(
+    /// Exact quantity in base asset or contracts, serialized as a string like the exchanges do
+    Quantity
+);
+
+impl Price {
+    /// Snap to a multiple of `tick_size`
+    pub fn round_to_tick(&self, tick_size: Price, rounding: Rounding) -> Price {
+        Price(round_to_step(self.0, tick_size.0, rounding))
+    }
+}
+
+impl Quantity {
+    /// Snap down to a multiple of `lot_size`, never exceeding the requested quantity
+    pub fn round_to_lot(&self, lot_size: Quantity) -> Quantity {
+        Quantity(round_to_step(self.0, lot_size.0, Rounding::Down))
+    }
+}
+
+/// Notional value of `quantity` at `price`
+impl Mul<Quantity> for Price {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Quantity) -> Decimal {
+        self.0 * rhs.0
+    }
+}
+
+/// Notional value of `quantity` at `price`
+impl Mul<Price> for Quantity {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Price) -> Decimal {
+        self.0 * rhs.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(value: &str) -> Price {
+        value.parse().unwrap()
+    }
+
+    fn quantity(value: &str) -> Quantity {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        // 0.1 + 0.2 drifts with f64
+        assert_eq!(price("0.1") + price("0.2"), price("0.3"));
+        assert_eq!(
+            price("25000.5") * quantity("0.003"),
+            Decimal::new(750015, 4)
+        );
+        assert_eq!(
+            [quantity("0.1"), quantity("0.2")]
+                .into_iter()
+                .sum::<Quantity>(),
+            quantity("0.3")
+        );
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        let tick = price("0.5");
+        assert_eq!(
+            price("101.3").round_to_tick(tick, Rounding::Down),
+            price("101")
+        );
+        assert_eq!(
+            price("101.3").round_to_tick(tick, Rounding::Up),
+            price("101.5")
+        );
+        assert_eq!(
+            price("101.25").round_to_tick(tick, Rounding::Nearest),
+            price("101.5")
+        );
+        assert_eq!(
+            price("101.3").round_to_tick(Price::ZERO, Rounding::Down),
+            price("101.3")
+        );
+        assert_eq!(
+            quantity("1.23456").round_to_lot(quantity("0.001")),
+            quantity("1.234")
+        );
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let value: Price = serde_json::from_str("\"25000.10\"").unwrap();
+        assert_eq!(value, price("25000.1"));
+        assert_eq!(serde_json::to_string(&value).unwrap(), "\"25000.10\"");
+        assert_eq!(quantity("1e-5"), quantity("0.00001"));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    BookUpdate, Candle, Exchange, FundingRate, IndexPrice, MarkPrice, OpenInterest, Ticker, Trade,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Nanoseconds elapsed on the monotonic clock since the first call in this process
+///
+/// Unlike the wall clock it never jumps backwards, so differences between two readings are
+/// reliable for measuring latency, but values are only comparable within the same process.
+pub fn monotonic_nanos() -> u64 {
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    ORIGIN.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// Local time a message was read from the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiveTime {
+    /// Wall clock, UTC format, comparable with exchange timestamps
+    pub wall: DateTime<Utc>,
+
+    /// Monotonic clock, see [`monotonic_nanos`]
+    pub monotonic: u64,
+}
+
+impl ReceiveTime {
+    pub fn now() -> Self {
+        Self {
+            wall: Utc::now(),
+            monotonic: monotonic_nanos(),
+        }
+    }
+
+    /// Monotonic time elapsed since `earlier`, zero if `earlier` is later
+    pub fn since(&self, earlier: &ReceiveTime) -> Duration {
+        Duration::from_nanos(self.monotonic.saturating_sub(earlier.monotonic))
+    }
+}
+
+/// Time the exchange stamped on an event, if any
+pub trait ExchangeTime {
+    fn exchange_time(&self) -> Option<DateTime<Utc>>;
+}
+
+/// Normalized market data event with its source and timing metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketEvent<T> {
+    /// Exchange the event came from
+    pub exchange: Exchange,
+
+    /// Time stamped by the exchange, `None` if the exchange doesn't provide one
+    pub exchange_time: Option<DateTime<Utc>>,
+
+    /// Local receive time of the message carrying the event
+    pub received: ReceiveTime,
+
+    /// Local sequence number, strictly increasing across all events of one feed
+    pub sequence: u64,
+
+    pub data: T,
+}
+
+impl<T: ExchangeTime> MarketEvent<T> {
+    pub fn new(exchange: Exchange, data: T, received: ReceiveTime, sequence: u64) -> Self {
+        Self {
+            exchange,
+            exchange_time: data.exchange_time(),
+            received,
+            sequence,
+            data,
+        }
+    }
+}
+
+impl<T> MarketEvent<T> {
+    /// Feed latency from the exchange timestamp to the local wall clock receive time
+    ///
+    /// May be negative when the local clock is behind the exchange clock.
+    pub fn latency(&self) -> Option<TimeDelta> {
+        self.exchange_time.map(|time| self.received.wall - time)
+    }
+
+    /// Replace the payload, keeping the metadata
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> MarketEvent<U> {
+        MarketEvent {
+            exchange: self.exchange,
+            exchange_time: self.exchange_time,
+            received: self.received,
+            sequence: self.sequence,
+            data: f(self.data),
+        }
+    }
+}
+
+impl ExchangeTime for Ticker {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
+}
+
+impl ExchangeTime for Trade {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
+}
+
+impl ExchangeTime for BookUpdate {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
+}
+
+/// Candle pushes only carry the open time, which is not the time of the update
+impl ExchangeTime for Candle {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+}
+
+impl ExchangeTime for FundingRate {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        self.timestamp
+    }
+}
+
+impl ExchangeTime for MarkPrice {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
+}
+
+impl ExchangeTime for IndexPrice {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
+}
+
+impl ExchangeTime for OpenInterest {
+    fn exchange_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Side;
+
+    #[test]
+    fn test_market_event() {
+        let timestamp = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let trade = Trade {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: "1".to_string(),
+            price: "100".parse().unwrap(),
+            quantity: "1".parse().unwrap(),
+            side: Side::Buy,
+            timestamp,
+        };
+        let first = ReceiveTime::now();
+        let received = ReceiveTime {
+            wall: timestamp + TimeDelta::milliseconds(25),
+            monotonic: first.monotonic + 1_000,
+        };
+        let event = MarketEvent::new(Exchange::Okex, trade, received, 7);
+        assert_eq!(event.latency(), Some(TimeDelta::milliseconds(25)));
+        assert_eq!(received.since(&first), Duration::from_micros(1));
+        assert_eq!(first.since(&received), Duration::ZERO);
+
+        let event = event.map(|trade| trade.price);
+        assert_eq!(event.sequence, 7);
+        assert_eq!(event.exchange_time, Some(timestamp));
+        assert_eq!(event.data, "100".parse().unwrap());
+    }
+}
@@ -1,12 +1,31 @@
+pub mod account;
+pub mod decimal;
+pub mod event;
+pub mod market;
+pub mod order;
+pub mod symbol;
+
+pub use account::{Balance, Position, PositionSide};
+pub use decimal::{Price, Quantity, Rounding};
+pub use event::{ExchangeTime, MarketEvent, ReceiveTime, monotonic_nanos};
+pub use market::{
+    Bar, BarType, BookUpdate, Candle, FundingRate, IndexPrice, Interval, MarkPrice, OpenInterest,
+    Trade,
+};
+pub use order::{
+    Fill, Order, OrderAck, OrderId, OrderRequest, OrderStatus, OrderType, Side, TimeInForce,
+};
+pub use symbol::{CanonicalSymbol, ContractType, OptionType, SymbolError};
+
 use chrono::{DateTime, Utc};
+pub use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 /// Trading symbol information
 /// This struct represents a trading symbol, which includes its name and other relevant details.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
-    /// Name for the symbol, e.g., "BTC/USD"
+    /// Canonical name shared by all exchanges, e.g., "BTC/USDT" or "BTC/USDT-PERP", see [`CanonicalSymbol`]
     pub name: String,
 
     /// Base asset of the symbol, e.g., "BTC" for Bitcoin
@@ -19,24 +38,80 @@ pub struct Symbol {
     /// For example, in "BTC/USD", "USD" is the quote asset.
     pub quote_asset: String,
 
-    /// TODO: Add more fields as needed, such as exchange, market type, etc.
-    /// Symbol status, e.g., "TRADING", "HALT", "LIQUIDATING"
+    /// Exchange listing this symbol
+    pub exchange: Exchange,
+
+    /// Instrument id on the exchange, e.g., "BTC-USDT-SWAP" on OKX or "BTCUSDT" on Binance
+    pub exchange_symbol: String,
+
+    /// Market type, e.g., spot or perpetual swap (`instType` on OKX)
+    pub market_type: MarketType,
+
+    /// Symbol status as reported by the exchange, e.g., "TRADING", "HALT", "LIQUIDATING" on Binance
+    /// or "live", "suspend", "preopen" on OKX
     pub status: String,
 
     /// Minimum quantity for trading this symbol
-    /// This is the minimum amount of the base asset that can be traded.
+    /// For spot symbols this is the minimum amount of the base asset that can be traded.
     /// For example, if the minimum quantity is 0.001 BTC, then min_qty must be multiple of 0.001.
-    pub min_qty: f64,
+    /// For derivatives it is a number of contracts, multiply by `contract_value` for the base amount.
+    pub min_qty: Quantity,
+
+    /// Lot size for trading this symbol, in the same unit as `min_qty`
+    /// Order quantities must be multiples of the lot size, which may be smaller than `min_qty`.
+    pub lot_size: Quantity,
 
     /// Tick size for trading this symbol
     /// This is the smallest price increment that can be used when placing orders.
     /// For example, if the tick size is 0.01 USD, then orders must be placed in multiples of 0.01 USD.
-    pub tick_size: f64,
+    pub tick_size: Price,
 
     /// Minimum notional value for trading this symbol
     ///  This is the minimum total value of an order, calculated as `min_qty * tick_size`.
     /// For example, if the minimum notional is 10 USD, then the total value of an order must be at least 10 USD.
-    pub min_notional: f64,
+    pub min_notional: Decimal,
+
+    /// Contract value for derivatives, e.g., 0.01 BTC per contract for OKX "BTC-USDT-SWAP"
+    pub contract_value: Option<Decimal>,
+
+    /// Settlement asset for derivatives, e.g., "USDT" for linear and "BTC" for inverse contracts
+    pub settle_asset: Option<String>,
+
+    /// Expiry time for futures and options, UTC format
+    pub expiry: Option<DateTime<Utc>>,
+}
+
+impl Symbol {
+    /// Canonical identifier parsed from the exchange instrument id
+    pub fn canonical(&self) -> Result<CanonicalSymbol, SymbolError> {
+        CanonicalSymbol::from_native(&self.exchange, self.market_type, &self.exchange_symbol)
+    }
+
+    /// Snap a price to the symbol's tick size
+    pub fn round_price(&self, price: Price, rounding: Rounding) -> Price {
+        price.round_to_tick(self.tick_size, rounding)
+    }
+
+    /// Snap a quantity down to a multiple of the symbol's lot size, or minimum quantity if the lot size is unknown
+    pub fn round_quantity(&self, quantity: Quantity) -> Quantity {
+        let lot_size = if self.lot_size.is_zero() {
+            self.min_qty
+        } else {
+            self.lot_size
+        };
+        quantity.round_to_lot(lot_size)
+    }
+
+    /// Whether the symbol is a futures, swap or option contract
+    pub fn is_derivative(&self) -> bool {
+        self.market_type.is_derivative()
+    }
+
+    /// Whether an order of `quantity` at `price` satisfies the minimum quantity and notional,
+    /// `quantity` is in the same unit as `min_qty`
+    pub fn meets_minimums(&self, price: Price, quantity: Quantity) -> bool {
+        quantity >= self.min_qty && price * quantity >= self.min_notional
+    }
 }
 
 /// Ticker data
@@ -53,56 +128,83 @@ pub struct Ticker {
     pub timestamp: DateTime<Utc>,
 
     /// Last price for the symbol
-    pub last_price: f64,
+    pub last_price: Price,
 
     /// Bid price for the symbol
     /// This is the highest price that a buyer is willing to pay for the base asset.
     /// For example, if the bid price is 50000 USD, then buyers are willing to pay up to 50000 USD for 1 BTC.
-    pub bid_price: f64,
+    pub bid_price: Price,
 
     /// Ask price for the symbol
     /// This is the lowest price that a seller is willing to accept for the base asset.
     /// For example, if the ask price is 50010 USD, then sellers are willing to sell 1 BTC for at least 50010 USD.
-    pub ask_price: f64,
+    pub ask_price: Price,
 
     /// Volume for the symbol over the last 24 hours
-    pub volume_24h: f64,
+    pub volume_24h: Quantity,
 
     ///  Quote volume for the symbol over the last 24 hours
-    pub quote_volumn_24h: f64,
+    pub quote_volumn_24h: Decimal,
 
     /// 24-hour high price for the symbol
-    pub high_24h: f64,
+    pub high_24h: Price,
 
     /// 24-hour low price for the symbol
-    pub low_24h: f64,
+    pub low_24h: Price,
 
     /// 24-hour open price for the symbol
-    pub open_24h: f64,
+    pub open_24h: Price,
 
     /// 24-hour close price for the symbol
-    pub close_24h: f64,
+    pub close_24h: Price,
 
-    /// 24-hour price change percentage
-    pub price_change_24h: f64,
+    /// 24-hour price change, negative when the price fell
+    pub price_change_24h: Decimal,
 
     /// 24-hour price change in percentage(absolute value)
-    pub price_change_percent_24h: f64,
+    pub price_change_percent_24h: Decimal,
 
     /// weight average price(WAP) for the symbol over the last 24 hours
     ///  This is the average price of the base asset over the last 24 hours, weighted by volume.
     /// For example, if the WAP is 50000 USD, then the average price of 1 BTC over the last 24 hours is 50000 USD.
     /// It is calculated as the sum of (price * volume) / total volume.
     /// This is useful for understanding the average price at which the asset has traded over the last 24 hours.
-    pub weighted_avg_price_24h: f64,
+    pub weighted_avg_price_24h: Price,
 
     /// Previous close price for the symbol
-    pub prev_close_price: f64,
+    pub prev_close_price: Price,
 
     /// count of trades in the last 24 hours
     pub count_24h: u64,
 }
 
+/// Market type of a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MarketType {
+    Spot,
+
+    /// Spot trading with borrowed funds
+    Margin,
+
+    /// Perpetual swap
+    Swap,
+
+    /// Dated futures
+    Futures,
+
+    Option,
+}
+
+impl MarketType {
+    /// Whether the market trades futures, swap or option contracts
+    pub fn is_derivative(&self) -> bool {
+        matches!(
+            self,
+            MarketType::Swap | MarketType::Futures | MarketType::Option
+        )
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum Exchange {
     #[default]
@@ -0,0 +1,307 @@
+use crate::{Exchange, Price, Quantity, Side};
+use chrono::{DateTime, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Time interval of a candle, stored in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Interval(u64);
+
+impl Interval {
+    pub const fn seconds(seconds: u64) -> Self {
+        Self(seconds)
+    }
+
+    pub const fn minutes(minutes: u64) -> Self {
+        Self(minutes * 60)
+    }
+
+    pub const fn hours(hours: u64) -> Self {
+        Self(hours * 3600)
+    }
+
+    pub const fn days(days: u64) -> Self {
+        Self(days * 86400)
+    }
+
+    pub const fn weeks(weeks: u64) -> Self {
+        Self(weeks * 604800)
+    }
+
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        TimeDelta::seconds(self.0 as i64)
+    }
+
+    /// Start of the interval containing `timestamp`, aligned to the Unix epoch
+    pub fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        if self.0 == 0 {
+            return timestamp;
+        }
+        let millis = self.0 as i64 * 1000;
+        let start = timestamp.timestamp_millis().div_euclid(millis) * millis;
+        DateTime::from_timestamp_millis(start).unwrap_or(timestamp)
+    }
+}
+
+/// Formats with the largest unit that divides the interval, e.g. "7s", "15m", "4h", "1d"
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = [(604800, "w"), (86400, "d"), (3600, "h"), (60, "m")];
+        match units
+            .iter()
+            .find(|(seconds, _)| self.0 > 0 && self.0.is_multiple_of(*seconds))
+        {
+            Some((seconds, unit)) => write!(f, "{}{}", self.0 / seconds, unit),
+            None => write!(f, "{}s", self.0),
+        }
+    }
+}
+
+/// Public trade printed on the exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub exchange: Exchange,
+
+    /// Instrument id on the exchange, e.g. "BTC-USDT"
+    pub symbol: String,
+
+    pub trade_id: String,
+
+    pub price: Price,
+
+    /// Quantity in base asset (or contracts for derivatives)
+    pub quantity: Quantity,
+
+    /// Side of the taker
+    pub side: Side,
+
+    /// Execution time, UTC format
+    pub timestamp: DateTime<Utc>,
+}
+
+/// L2 order book change, a level with zero quantity is removed from the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookUpdate {
+    pub exchange: Exchange,
+
+    /// Instrument id on the exchange, e.g. "BTC-USDT"
+    pub symbol: String,
+
+    /// Exchange sequence number, e.g. `seqId` on OKX, increasing within a symbol
+    pub sequence: i64,
+
+    /// Whether the update is a full snapshot replacing the whole book
+    pub snapshot: bool,
+
+    /// Changed bid levels as (price, quantity)
+    pub bids: Vec<(Price, Quantity)>,
+
+    /// Changed ask levels as (price, quantity)
+    pub asks: Vec<(Price, Quantity)>,
+
+    /// Exchange time of the update, UTC format
+    pub timestamp: DateTime<Utc>,
+}
+
+/// OHLCV candle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub exchange: Exchange,
+
+    /// Instrument id on the exchange, e.g. "BTC-USDT"
+    pub symbol: String,
+
+    pub interval: Interval,
+
+    /// Start of the candle, UTC format
+    pub open_time: DateTime<Utc>,
+
+    pub open: Price,
+
+    pub high: Price,
+
+    pub low: Price,
+
+    pub close: Price,
+
+    /// Traded quantity in base asset
+    pub volume: Quantity,
+
+    /// Traded value in quote asset
+    pub quote_volume: Decimal,
+
+    /// Whether the candle is complete, the last candle of a stream keeps updating until it closes
+    pub closed: bool,
+}
+
+/// How trades are grouped into bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BarType {
+    /// Fixed time buckets aligned to the Unix epoch
+    Time(Interval),
+
+    /// A bar every n trades
+    Tick(u64),
+
+    /// A bar once the traded quantity reaches the threshold
+    Volume(Quantity),
+
+    /// A bar once the traded quote value reaches the threshold
+    Dollar(Decimal),
+}
+
+/// OHLCV bar with volume weighted average price, built from trades or converted from a candle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bar {
+    pub exchange: Exchange,
+
+    /// Instrument id on the exchange, e.g. "BTC-USDT"
+    pub symbol: String,
+
+    pub bar_type: BarType,
+
+    /// Start of the bar, the bucket start for time bars and the first trade otherwise, UTC format
+    pub open_time: DateTime<Utc>,
+
+    /// End of the bar, the bucket end for time bars and the last trade otherwise, UTC format
+    pub close_time: DateTime<Utc>,
+
+    pub open: Price,
+
+    pub high: Price,
+
+    pub low: Price,
+
+    pub close: Price,
+
+    /// Traded quantity in base asset (or contracts for derivatives)
+    pub volume: Quantity,
+
+    /// Traded value in quote asset
+    pub quote_volume: Decimal,
+
+    /// Volume weighted average price, the close price when nothing traded
+    pub vwap: Price,
+
+    /// Number of trades, zero for gap-filled bars and bars converted from exchange candles
+    pub trade_count: u64,
+}
+
+impl From<&Candle> for Bar {
+    fn from(candle: &Candle) -> Self {
+        let vwap = if candle.volume.is_zero() {
+            candle.close
+        } else {
+            Price::new(candle.quote_volume / candle.volume.value())
+        };
+        Bar {
+            exchange: candle.exchange.clone(),
+            symbol: candle.symbol.clone(),
+            bar_type: BarType::Time(candle.interval),
+            open_time: candle.open_time,
+            close_time: candle.open_time + candle.interval.duration(),
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+            quote_volume: candle.quote_volume,
+            vwap,
+            trade_count: 0,
+        }
+    }
+}
+
+/// Funding rate of a perpetual swap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub exchange: Exchange,
+
+    pub symbol: String,
+
+    /// Rate charged at `funding_time`, positive values mean longs pay shorts
+    pub rate: Decimal,
+
+    /// Forecast rate for the following period, if the exchange publishes one
+    pub next_rate: Option<Decimal>,
+
+    /// Settlement time of `rate`, UTC format
+    pub funding_time: DateTime<Utc>,
+
+    pub next_funding_time: Option<DateTime<Utc>>,
+
+    /// Time of the update, UTC format; `None` when the exchange does not send one
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Mark price used for margin and liquidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkPrice {
+    pub exchange: Exchange,
+
+    pub symbol: String,
+
+    pub price: Price,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Index price aggregated from several spot markets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPrice {
+    pub exchange: Exchange,
+
+    /// Index id, e.g. "BTC-USDT" on OKX
+    pub symbol: String,
+
+    pub price: Price,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Open interest of a derivative
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenInterest {
+    pub exchange: Exchange,
+
+    pub symbol: String,
+
+    /// Open interest in contracts
+    pub contracts: Quantity,
+
+    /// Open interest in base asset
+    pub base_amount: Quantity,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(Interval::seconds(7).to_string(), "7s");
+        assert_eq!(Interval::seconds(180).to_string(), "3m");
+        assert_eq!(Interval::hours(4).to_string(), "4h");
+        assert_eq!(Interval::days(7).to_string(), "1w");
+
+        let timestamp = DateTime::from_timestamp_millis(1_700_000_123_456).unwrap();
+        assert_eq!(
+            Interval::minutes(1)
+                .bucket_start(timestamp)
+                .timestamp_millis(),
+            1_700_000_100_000
+        );
+        assert_eq!(
+            Interval::seconds(7).bucket_start(timestamp).timestamp() % 7,
+            0
+        );
+    }
+}
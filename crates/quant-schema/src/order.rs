@@ -0,0 +1,358 @@
+use crate::{Exchange, Price, Quantity};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Order side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    /// The opposite side, e.g. the side used to close a position
+    pub fn opposite(&self) -> Self {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+/// Order type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderType {
+    /// Executes immediately at the best available price, `price` is ignored
+    Market,
+
+    /// Executes at `price` or better
+    Limit,
+}
+
+/// How long an order stays active before it is executed or expires
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Good till canceled
+    #[default]
+    Gtc,
+
+    /// Immediate or cancel, any unfilled part is canceled
+    Ioc,
+
+    /// Fill or kill, the order is canceled unless it fills completely
+    Fok,
+
+    /// Maker only, the order is rejected if it would trade immediately
+    PostOnly,
+}
+
+/// Venue-neutral order request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRequest {
+    /// Instrument id on the target exchange, e.g. "BTC-USDT" on OKX or "BTCUSDT" on Binance
+    pub symbol: String,
+
+    pub side: Side,
+
+    pub order_type: OrderType,
+
+    pub time_in_force: TimeInForce,
+
+    /// Order quantity in base asset (or contracts for derivatives)
+    pub quantity: Quantity,
+
+    /// Limit price, required for `OrderType::Limit`
+    pub price: Option<Price>,
+
+    /// Client assigned order id, echoed back by the exchange
+    pub client_order_id: Option<String>,
+
+    /// Only reduce an existing position, never increase or flip it
+    pub reduce_only: bool,
+}
+
+impl OrderRequest {
+    /// Market order
+    pub fn market(symbol: impl Into<String>, side: Side, quantity: Quantity) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            order_type: OrderType::Market,
+            time_in_force: TimeInForce::Ioc,
+            quantity,
+            price: None,
+            client_order_id: None,
+            reduce_only: false,
+        }
+    }
+
+    /// Good-till-canceled limit order
+    pub fn limit(symbol: impl Into<String>, side: Side, quantity: Quantity, price: Price) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            quantity,
+            price: Some(price),
+            client_order_id: None,
+            reduce_only: false,
+        }
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+}
+
+/// Identifies an existing order, either by exchange order id or client order id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderId {
+    Exchange(String),
+    Client(String),
+}
+
+/// Exchange acknowledgement of an order request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAck {
+    pub exchange: Exchange,
+
+    pub symbol: String,
+
+    /// Order id assigned by the exchange
+    pub order_id: String,
+
+    pub client_order_id: Option<String>,
+
+    /// Time the exchange accepted the request, UTC format
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Lifecycle state of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// Accepted by the exchange and waiting to be filled
+    New,
+
+    PartiallyFilled,
+
+    Filled,
+
+    Canceled,
+
+    /// Refused by the exchange, e.g. insufficient balance or invalid price
+    Rejected,
+
+    /// Expired by time-in-force rules, e.g. an unfilled IOC order
+    Expired,
+}
+
+impl OrderStatus {
+    /// Whether the order can still be filled
+    pub fn is_open(&self) -> bool {
+        matches!(self, OrderStatus::New | OrderStatus::PartiallyFilled)
+    }
+}
+
+/// Venue-neutral view of an order and its execution progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub exchange: Exchange,
+
+    pub symbol: String,
+
+    /// Order id assigned by the exchange, empty if the order was rejected before being assigned one
+    pub order_id: String,
+
+    pub client_order_id: Option<String>,
+
+    pub side: Side,
+
+    pub order_type: OrderType,
+
+    pub time_in_force: TimeInForce,
+
+    /// Original order quantity
+    pub quantity: Quantity,
+
+    /// Limit price, `None` for market orders
+    pub price: Option<Price>,
+
+    /// Accumulated filled quantity
+    pub filled_quantity: Quantity,
+
+    /// Average fill price, `None` until the first fill
+    pub average_price: Option<Price>,
+
+    pub status: OrderStatus,
+
+    pub reduce_only: bool,
+
+    /// Creation time, UTC format
+    pub created_at: DateTime<Utc>,
+
+    /// Time of the last status change, UTC format
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Order {
+    /// A freshly accepted order built from the request and the exchange acknowledgement
+    pub fn new(request: &OrderRequest, ack: &OrderAck) -> Self {
+        Self {
+            exchange: ack.exchange.clone(),
+            symbol: request.symbol.clone(),
+            order_id: ack.order_id.clone(),
+            client_order_id: ack
+                .client_order_id
+                .clone()
+                .or_else(|| request.client_order_id.clone()),
+            side: request.side,
+            order_type: request.order_type,
+            time_in_force: request.time_in_force,
+            quantity: request.quantity,
+            price: request.price,
+            filled_quantity: Quantity::ZERO,
+            average_price: None,
+            status: OrderStatus::New,
+            reduce_only: request.reduce_only,
+            created_at: ack.timestamp,
+            updated_at: ack.timestamp,
+        }
+    }
+
+    /// An order refused before the exchange assigned it an id
+    pub fn rejected(exchange: Exchange, request: &OrderRequest, at: DateTime<Utc>) -> Self {
+        Self {
+            exchange,
+            symbol: request.symbol.clone(),
+            order_id: String::new(),
+            client_order_id: request.client_order_id.clone(),
+            side: request.side,
+            order_type: request.order_type,
+            time_in_force: request.time_in_force,
+            quantity: request.quantity,
+            price: request.price,
+            filled_quantity: Quantity::ZERO,
+            average_price: None,
+            status: OrderStatus::Rejected,
+            reduce_only: request.reduce_only,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    /// Quantity still waiting to be filled
+    pub fn remaining_quantity(&self) -> Quantity {
+        (self.quantity - self.filled_quantity).max(Quantity::ZERO)
+    }
+
+    /// Apply a fill belonging to this order, updating the filled quantity, average price and status
+    pub fn apply_fill(&mut self, fill: &Fill) {
+        let filled = self.filled_quantity + fill.quantity;
+        let notional = self.average_price.unwrap_or(Price::ZERO) * self.filled_quantity
+            + fill.price * fill.quantity;
+        self.filled_quantity = filled;
+        self.average_price = (!filled.is_zero()).then(|| Price::new(notional / filled.value()));
+        self.status = if self.remaining_quantity() > Quantity::ZERO {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Filled
+        };
+        self.updated_at = fill.timestamp;
+    }
+}
+
+/// A single execution of an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub exchange: Exchange,
+
+    pub symbol: String,
+
+    /// Trade id assigned by the exchange
+    pub trade_id: String,
+
+    pub order_id: String,
+
+    pub client_order_id: Option<String>,
+
+    pub side: Side,
+
+    pub price: Price,
+
+    pub quantity: Quantity,
+
+    /// Fee charged for this fill, positive values are costs and negative values are rebates
+    pub fee: Decimal,
+
+    /// Asset the fee is charged in
+    pub fee_asset: String,
+
+    /// Whether this fill added liquidity to the book
+    pub is_maker: bool,
+
+    /// Execution time, UTC format
+    pub timestamp: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(price: &str, quantity: &str) -> Fill {
+        Fill {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: "1".to_string(),
+            order_id: "100".to_string(),
+            client_order_id: None,
+            side: Side::Buy,
+            price: price.parse().unwrap(),
+            quantity: quantity.parse().unwrap(),
+            fee: Decimal::ZERO,
+            fee_asset: "BTC".to_string(),
+            is_maker: true,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_apply_fill() {
+        let request = OrderRequest::limit(
+            "BTC-USDT",
+            Side::Buy,
+            "2".parse().unwrap(),
+            "100".parse().unwrap(),
+        );
+        let ack = OrderAck {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            order_id: "100".to_string(),
+            client_order_id: None,
+            timestamp: Utc::now(),
+        };
+        let mut order = Order::new(&request, &ack);
+        assert_eq!(order.status, OrderStatus::New);
+
+        order.apply_fill(&fill("100", "0.5"));
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.remaining_quantity(), "1.5".parse().unwrap());
+
+        order.apply_fill(&fill("98", "1.5"));
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert!(!order.status.is_open());
+        assert_eq!(order.average_price, Some("98.5".parse().unwrap()));
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{Exchange, MarketType};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Quote assets recognised when splitting concatenated ids such as Binance "BTCUSDT",
+/// longer assets come first so "FDUSD" wins over "USD"
+const QUOTE_ASSETS: &[&str] = &[
+    "FDUSD", "USDT", "USDC", "TUSD", "BUSD", "DAI", "BTC", "ETH", "BNB", "EUR", "TRY", "BRL",
+    "JPY", "USD",
+];
+
+/// Date format used by both OKX and Binance in dated contract ids, e.g. "250328"
+const EXPIRY_FORMAT: &str = "%y%m%d";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SymbolError {
+    #[error("Invalid symbol: {0}")]
+    Invalid(String),
+    #[error("{symbol} can not be traded on {exchange:?}")]
+    Unsupported { exchange: Exchange, symbol: String },
+}
+
+/// Option right
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+/// Contract kind of a canonical symbol
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContractType {
+    Spot,
+
+    /// Perpetual swap, linear when quoted in a stablecoin and inverse when quoted in "USD"
+    Perpetual,
+
+    /// Dated futures
+    Futures {
+        expiry: NaiveDate,
+    },
+
+    Option {
+        expiry: NaiveDate,
+        strike: Decimal,
+        option_type: OptionType,
+    },
+}
+
+/// Exchange-independent instrument identifier
+///
+/// The string form is `BASE/QUOTE` followed by a suffix for derivatives:
+/// * spot: "BTC/USDT"
+/// * perpetual swap: "BTC/USDT-PERP"
+/// * dated futures: "BTC/USD-250328"
+/// * option: "BTC/USD-250328-50000-C"
+///
+/// Margin trading uses the spot symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CanonicalSymbol {
+    pub base: String,
+    pub quote: String,
+    pub contract: ContractType,
+}
+
+impl CanonicalSymbol {
+    pub fn spot(base: impl Into<String>, quote: impl Into<String>) -> Self {
+        Self::new(base, quote, ContractType::Spot)
+    }
+
+    pub fn perpetual(base: impl Into<String>, quote: impl Into<String>) -> Self {
+        Self::new(base, quote, ContractType::Perpetual)
+    }
+
+    pub fn futures(base: impl Into<String>, quote: impl Into<String>, expiry: NaiveDate) -> Self {
+        Self::new(base, quote, ContractType::Futures { expiry })
+    }
+
+    pub fn new(base: impl Into<String>, quote: impl Into<String>, contract: ContractType) -> Self {
+        Self {
+            base: base.into().to_uppercase(),
+            quote: quote.into().to_uppercase(),
+            contract,
+        }
+    }
+
+    /// Market type on the exchange, spot symbols map to `MarketType::Spot`
+    pub fn market_type(&self) -> MarketType {
+        match self.contract {
+            ContractType::Spot => MarketType::Spot,
+            ContractType::Perpetual => MarketType::Swap,
+            ContractType::Futures { .. } => MarketType::Futures,
+            ContractType::Option { .. } => MarketType::Option,
+        }
+    }
+
+    /// Instrument id on `exchange`
+    /// * OKX: "BTC-USDT", "BTC-USDT-SWAP", "BTC-USD-250328", "BTC-USD-250328-50000-C"
+    /// * Binance: "BTCUSDT" for spot and USDⓈ-M perpetuals, "BTCUSD_PERP" for COIN-M perpetuals,
+    ///   "BTCUSDT_250328" for futures and "BTC-250328-50000-C" for USDT settled options
+    pub fn to_native(&self, exchange: &Exchange) -> Result<String, SymbolError> {
+        let (base, quote) = (&self.base, &self.quote);
+        match exchange {
+            Exchange::Okex => Ok(match &self.contract {
+                ContractType::Spot => format!("{base}-{quote}"),
+                ContractType::Perpetual => format!("{base}-{quote}-SWAP"),
+                ContractType::Futures { expiry } => {
+                    format!("{base}-{quote}-{}", expiry.format(EXPIRY_FORMAT))
+                }
+                ContractType::Option {
+                    expiry,
+                    strike,
+                    option_type,
+                } => format!(
+                    "{base}-{quote}-{}-{}-{}",
+                    expiry.format(EXPIRY_FORMAT),
+                    strike.normalize(),
+                    option_suffix(*option_type)
+                ),
+            }),
+            Exchange::Binance => match &self.contract {
+                ContractType::Spot => Ok(format!("{base}{quote}")),
+                ContractType::Perpetual if quote == "USD" => Ok(format!("{base}USD_PERP")),
+                ContractType::Perpetual => Ok(format!("{base}{quote}")),
+                ContractType::Futures { expiry } => {
+                    Ok(format!("{base}{quote}_{}", expiry.format(EXPIRY_FORMAT)))
+                }
+                ContractType::Option {
+                    expiry,
+                    strike,
+                    option_type,
+                } if quote == "USDT" => Ok(format!(
+                    "{base}-{}-{}-{}",
+                    expiry.format(EXPIRY_FORMAT),
+                    strike.normalize(),
+                    option_suffix(*option_type)
+                )),
+                ContractType::Option { .. } => Err(SymbolError::Unsupported {
+                    exchange: exchange.clone(),
+                    symbol: self.to_string(),
+                }),
+            },
+        }
+    }
+
+    /// Parse an instrument id of `exchange`
+    ///
+    /// OKX ids are self-describing, `market_type` is only used for Binance where spot and
+    /// USDⓈ-M perpetual share the same id, e.g. "BTCUSDT".
+    pub fn from_native(
+        exchange: &Exchange,
+        market_type: MarketType,
+        native: &str,
+    ) -> Result<Self, SymbolError> {
+        let invalid = || SymbolError::Invalid(native.to_string());
+        match exchange {
+            Exchange::Okex => {
+                let parts: Vec<&str> = native.split('-').collect();
+                let contract = match parts[..] {
+                    [_, _] => ContractType::Spot,
+                    [_, _, "SWAP"] => ContractType::Perpetual,
+                    [_, _, expiry] => ContractType::Futures {
+                        expiry: parse_expiry(expiry).ok_or_else(invalid)?,
+                    },
+                    [_, _, expiry, strike, option_type] => ContractType::Option {
+                        expiry: parse_expiry(expiry).ok_or_else(invalid)?,
+                        strike: strike.parse().map_err(|_| invalid())?,
+                        option_type: parse_option_type(option_type).ok_or_else(invalid)?,
+                    },
+                    _ => return Err(invalid()),
+                };
+                non_empty_pair(parts[0], parts[1])
+                    .map(|(base, quote)| Self::new(base, quote, contract))
+                    .ok_or_else(invalid)
+            }
+            Exchange::Binance => match market_type {
+                MarketType::Spot | MarketType::Margin => split_concatenated(native)
+                    .map(|(base, quote)| Self::spot(base, quote))
+                    .ok_or_else(invalid),
+                MarketType::Swap => native
+                    .strip_suffix("_PERP")
+                    .map_or_else(|| split_concatenated(native), split_concatenated)
+                    .map(|(base, quote)| Self::perpetual(base, quote))
+                    .ok_or_else(invalid),
+                MarketType::Futures => {
+                    let (pair, expiry) = native.split_once('_').ok_or_else(invalid)?;
+                    let expiry = parse_expiry(expiry).ok_or_else(invalid)?;
+                    split_concatenated(pair)
+                        .map(|(base, quote)| Self::futures(base, quote, expiry))
+                        .ok_or_else(invalid)
+                }
+                MarketType::Option => match native.split('-').collect::<Vec<_>>()[..] {
+                    [base, expiry, strike, option_type] if !base.is_empty() => Ok(Self::new(
+                        base,
+                        "USDT",
+                        ContractType::Option {
+                            expiry: parse_expiry(expiry).ok_or_else(invalid)?,
+                            strike: strike.parse().map_err(|_| invalid())?,
+                            option_type: parse_option_type(option_type).ok_or_else(invalid)?,
+                        },
+                    )),
+                    _ => Err(invalid()),
+                },
+            },
+        }
+    }
+}
+
+impl fmt::Display for CanonicalSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)?;
+        match &self.contract {
+            ContractType::Spot => Ok(()),
+            ContractType::Perpetual => write!(f, "-PERP"),
+            ContractType::Futures { expiry } => write!(f, "-{}", expiry.format(EXPIRY_FORMAT)),
+            ContractType::Option {
+                expiry,
+                strike,
+                option_type,
+            } => write!(
+                f,
+                "-{}-{}-{}",
+                expiry.format(EXPIRY_FORMAT),
+                strike.normalize(),
+                option_suffix(*option_type)
+            ),
+        }
+    }
+}
+
+impl FromStr for CanonicalSymbol {
+    type Err = SymbolError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || SymbolError::Invalid(value.to_string());
+        let (base, rest) = value.split_once('/').ok_or_else(invalid)?;
+        let parts: Vec<&str> = rest.split('-').collect();
+        let contract = match parts[1..] {
+            [] => ContractType::Spot,
+            ["PERP"] => ContractType::Perpetual,
+            [expiry] => ContractType::Futures {
+                expiry: parse_expiry(expiry).ok_or_else(invalid)?,
+            },
+            [expiry, strike, option_type] => ContractType::Option {
+                expiry: parse_expiry(expiry).ok_or_else(invalid)?,
+                strike: strike.parse().map_err(|_| invalid())?,
+                option_type: parse_option_type(option_type).ok_or_else(invalid)?,
+            },
+            _ => return Err(invalid()),
+        };
+        non_empty_pair(base, parts[0])
+            .map(|(base, quote)| Self::new(base, quote, contract))
+            .ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<String> for CanonicalSymbol {
+    type Error = SymbolError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<CanonicalSymbol> for String {
+    fn from(value: CanonicalSymbol) -> Self {
+        value.to_string()
+    }
+}
+
+fn option_suffix(option_type: OptionType) -> &'static str {
+    match option_type {
+        OptionType::Call => "C",
+        OptionType::Put => "P",
+    }
+}
+
+fn parse_option_type(value: &str) -> Option<OptionType> {
+    match value {
+        "C" => Some(OptionType::Call),
+        "P" => Some(OptionType::Put),
+        _ => None,
+    }
+}
+
+fn parse_expiry(value: &str) -> Option<NaiveDate> {
+    if value.len() != 6 {
+        return None;
+    }
+    NaiveDate::parse_from_str(value, EXPIRY_FORMAT).ok()
+}
+
+fn non_empty_pair<'a>(base: &'a str, quote: &'a str) -> Option<(&'a str, &'a str)> {
+    (!base.is_empty() && !quote.is_empty()).then_some((base, quote))
+}
+
+/// Split ids without a separator such as "BTCUSDT" by the known quote assets
+fn split_concatenated(value: &str) -> Option<(&str, &str)> {
+    QUOTE_ASSETS.iter().find_map(|quote| {
+        value
+            .strip_suffix(quote)
+            .filter(|base| !base.is_empty())
+            .map(|base| (base, *quote))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expiry() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 28).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_string() {
+        let option = CanonicalSymbol::new(
+            "btc",
+            "usd",
+            ContractType::Option {
+                expiry: expiry(),
+                strike: Decimal::new(50000, 0),
+                option_type: OptionType::Call,
+            },
+        );
+        for (symbol, text) in [
+            (CanonicalSymbol::spot("BTC", "USDT"), "BTC/USDT"),
+            (CanonicalSymbol::perpetual("BTC", "USDT"), "BTC/USDT-PERP"),
+            (
+                CanonicalSymbol::futures("BTC", "USD", expiry()),
+                "BTC/USD-250328",
+            ),
+            (option, "BTC/USD-250328-50000-C"),
+        ] {
+            assert_eq!(symbol.to_string(), text);
+            assert_eq!(text.parse::<CanonicalSymbol>().unwrap(), symbol);
+        }
+        assert!("BTCUSDT".parse::<CanonicalSymbol>().is_err());
+        assert!("BTC/USDT-2503".parse::<CanonicalSymbol>().is_err());
+    }
+
+    #[test]
+    fn test_okex_native() {
+        for (native, canonical) in [
+            ("BTC-USDT", "BTC/USDT"),
+            ("BTC-USDT-SWAP", "BTC/USDT-PERP"),
+            ("BTC-USD-250328", "BTC/USD-250328"),
+            ("BTC-USD-250328-50000-P", "BTC/USD-250328-50000-P"),
+        ] {
+            let symbol =
+                CanonicalSymbol::from_native(&Exchange::Okex, MarketType::Spot, native).unwrap();
+            assert_eq!(symbol.to_string(), canonical);
+            assert_eq!(symbol.to_native(&Exchange::Okex).unwrap(), native);
+        }
+        assert!(
+            CanonicalSymbol::from_native(&Exchange::Okex, MarketType::Spot, "BTCUSDT").is_err()
+        );
+    }
+
+    #[test]
+    fn test_binance_native() {
+        for (market_type, native, canonical) in [
+            (MarketType::Spot, "BTCUSDT", "BTC/USDT"),
+            (MarketType::Spot, "ETHFDUSD", "ETH/FDUSD"),
+            (MarketType::Spot, "ETHBTC", "ETH/BTC"),
+            (MarketType::Swap, "BTCUSDT", "BTC/USDT-PERP"),
+            (MarketType::Swap, "BTCUSD_PERP", "BTC/USD-PERP"),
+            (MarketType::Futures, "BTCUSDT_250328", "BTC/USDT-250328"),
+            (
+                MarketType::Option,
+                "BTC-250328-50000-C",
+                "BTC/USDT-250328-50000-C",
+            ),
+        ] {
+            let symbol =
+                CanonicalSymbol::from_native(&Exchange::Binance, market_type, native).unwrap();
+            assert_eq!(symbol.to_string(), canonical);
+            assert_eq!(symbol.market_type(), market_type);
+            assert_eq!(symbol.to_native(&Exchange::Binance).unwrap(), native);
+        }
+
+        // One canonical name resolves to each venue's own id
+        let swap: CanonicalSymbol = "BTC/USDT-PERP".parse().unwrap();
+        assert_eq!(swap.to_native(&Exchange::Okex).unwrap(), "BTC-USDT-SWAP");
+        assert_eq!(swap.to_native(&Exchange::Binance).unwrap(), "BTCUSDT");
+
+        let inverse_option: CanonicalSymbol = "BTC/USD-250328-50000-C".parse().unwrap();
+        assert!(inverse_option.to_native(&Exchange::Binance).is_err());
+        assert!(
+            CanonicalSymbol::from_native(&Exchange::Binance, MarketType::Spot, "USDT").is_err()
+        );
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let symbol: CanonicalSymbol = serde_json::from_str("\"ETH/USDT-PERP\"").unwrap();
+        assert_eq!(symbol, CanonicalSymbol::perpetual("ETH", "USDT"));
+        assert_eq!(serde_json::to_string(&symbol).unwrap(), "\"ETH/USDT-PERP\"");
+    }
+}
@@ -0,0 +1,328 @@
+use crate::MarketDataStore;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use quant_schema::{BookUpdate, Candle, Exchange, Interval, Trade};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// 读取文件末尾时每次向前读取的字节数
+const TAIL_CHUNK_SIZE: u64 = 4096;
+
+/// 文件存储，每个产品每种数据一个 JSON Lines 文件，按时间升序排列
+///
+/// 目录结构为 `{root}/{exchange}/{symbol}/candles_{interval}.jsonl`、`{root}/{exchange}/{symbol}/trades.jsonl`
+/// 和 `{root}/{exchange}/{symbol}/books.jsonl`。
+/// 新数据都晚于文件中最后一条记录时直接追加，否则合并后重写整个文件。
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn symbol_dir(&self, exchange: &Exchange, symbol: &str) -> PathBuf {
+        let exchange = match exchange {
+            Exchange::Okex => "okex",
+            Exchange::Binance => "binance",
+        };
+        self.root.join(exchange).join(symbol)
+    }
+
+    fn candle_path(&self, exchange: &Exchange, symbol: &str, interval: Interval) -> PathBuf {
+        self.symbol_dir(exchange, symbol)
+            .join(format!("candles_{interval}.jsonl"))
+    }
+
+    fn trade_path(&self, exchange: &Exchange, symbol: &str) -> PathBuf {
+        self.symbol_dir(exchange, symbol).join("trades.jsonl")
+    }
+
+    fn book_path(&self, exchange: &Exchange, symbol: &str) -> PathBuf {
+        self.symbol_dir(exchange, symbol).join("books.jsonl")
+    }
+}
+
+impl MarketDataStore for FileStore {
+    fn write_candles(&mut self, candles: &[Candle]) -> Result<usize> {
+        let mut groups: HashMap<PathBuf, Vec<&Candle>> = HashMap::new();
+        for candle in candles {
+            let path = self.candle_path(&candle.exchange, &candle.symbol, candle.interval);
+            groups.entry(path).or_default().push(candle);
+        }
+        let mut added = 0;
+        for (path, candles) in groups {
+            added += write_series(&path, candles, |candle| candle.open_time, true)?;
+        }
+        Ok(added)
+    }
+
+    fn write_trades(&mut self, trades: &[Trade]) -> Result<usize> {
+        let mut groups: HashMap<PathBuf, Vec<&Trade>> = HashMap::new();
+        for trade in trades {
+            let path = self.trade_path(&trade.exchange, &trade.symbol);
+            groups.entry(path).or_default().push(trade);
+        }
+        let mut added = 0;
+        for (path, trades) in groups {
+            added += write_series(
+                &path,
+                trades,
+                |trade| (trade.timestamp, trade.trade_id.clone()),
+                false,
+            )?;
+        }
+        Ok(added)
+    }
+
+    fn write_book_updates(&mut self, updates: &[BookUpdate]) -> Result<usize> {
+        let mut groups: HashMap<PathBuf, Vec<&BookUpdate>> = HashMap::new();
+        for update in updates {
+            let path = self.book_path(&update.exchange, &update.symbol);
+            groups.entry(path).or_default().push(update);
+        }
+        let mut added = 0;
+        for (path, updates) in groups {
+            added += write_series(
+                &path,
+                updates,
+                |update| (update.timestamp, update.sequence),
+                false,
+            )?;
+        }
+        Ok(added)
+    }
+
+    fn last_candle_time(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: Interval,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let path = self.candle_path(exchange, symbol, interval);
+        Ok(last_record::<Candle>(&path)?.map(|candle| candle.open_time))
+    }
+
+    fn last_trade_time(&self, exchange: &Exchange, symbol: &str) -> Result<Option<DateTime<Utc>>> {
+        let path = self.trade_path(exchange, symbol);
+        Ok(last_record::<Trade>(&path)?.map(|trade| trade.timestamp))
+    }
+
+    fn last_book_update_time(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let path = self.book_path(exchange, symbol);
+        Ok(last_record::<BookUpdate>(&path)?.map(|update| update.timestamp))
+    }
+
+    fn candles(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>> {
+        let path = self.candle_path(exchange, symbol, interval);
+        Ok(read_records::<Candle>(&path)?
+            .into_iter()
+            .filter(|candle| range.contains(&candle.open_time))
+            .collect())
+    }
+
+    fn trades(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Trade>> {
+        let path = self.trade_path(exchange, symbol);
+        Ok(read_records::<Trade>(&path)?
+            .into_iter()
+            .filter(|trade| range.contains(&trade.timestamp))
+            .collect())
+    }
+
+    fn book_updates(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<BookUpdate>> {
+        let path = self.book_path(exchange, symbol);
+        Ok(read_records::<BookUpdate>(&path)?
+            .into_iter()
+            .filter(|update| range.contains(&update.timestamp))
+            .collect())
+    }
+}
+
+/// 写入一个文件，`overwrite` 为 true 时相同键的新记录覆盖旧记录，否则保留旧记录；返回新增的数量
+fn write_series<T, K, F>(path: &Path, records: Vec<&T>, key: F, overwrite: bool) -> Result<usize>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let mut incoming = BTreeMap::new();
+    for record in records {
+        incoming.insert(key(record), record);
+    }
+    let last_key = last_record::<T>(path)?.map(|record| key(&record));
+    let appendable = match (&last_key, incoming.keys().next()) {
+        (Some(last_key), Some(first_key)) => first_key > last_key,
+        _ => true,
+    };
+    if appendable {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut writer = BufWriter::new(file);
+        for record in incoming.values() {
+            serde_json::to_writer(&mut writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        return Ok(incoming.len());
+    }
+
+    let mut merged = BTreeMap::new();
+    for record in read_records::<T>(path)? {
+        merged.insert(key(&record), record);
+    }
+    let mut added = 0;
+    for (key, record) in incoming {
+        let exists = merged.contains_key(&key);
+        if !exists {
+            added += 1;
+        }
+        if !exists || overwrite {
+            merged.insert(key, record.clone());
+        }
+    }
+    // 先写临时文件再替换，避免中途失败损坏已有数据
+    let tmp = path.with_extension("jsonl.tmp");
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    for record in merged.values() {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    fs::rename(&tmp, path)?;
+    Ok(added)
+}
+
+fn read_records<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(records)
+}
+
+/// 从文件末尾向前读取最后一条记录，不需要读取整个文件
+fn last_record<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let len = file.metadata()?.len();
+    let mut chunk = TAIL_CHUNK_SIZE;
+    loop {
+        let start = len.saturating_sub(chunk);
+        file.seek(SeekFrom::Start(start))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        let trimmed = tail.trim_ascii_end();
+        match trimmed.iter().rposition(|byte| *byte == b'\n') {
+            Some(index) => return Ok(Some(serde_json::from_slice(&trimmed[index + 1..])?)),
+            None if start == 0 => {
+                return Ok((!trimmed.is_empty())
+                    .then(|| serde_json::from_slice(trimmed))
+                    .transpose()?);
+            }
+            None => chunk *= 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quant_schema::Side;
+
+    fn trade(seconds: i64, trade_id: &str) -> Trade {
+        Trade {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: trade_id.to_string(),
+            price: "100".parse().unwrap(),
+            quantity: "1".parse().unwrap(),
+            side: Side::Buy,
+            timestamp: DateTime::from_timestamp(seconds, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_merge() {
+        let root = std::env::temp_dir().join(format!("quant-storage-{}", std::process::id()));
+        let mut store = FileStore::new(&root);
+        let at = |seconds| DateTime::from_timestamp(seconds, 0).unwrap();
+
+        assert_eq!(
+            store.last_trade_time(&Exchange::Okex, "BTC-USDT").unwrap(),
+            None
+        );
+        assert_eq!(
+            store
+                .write_trades(&[trade(20, "2"), trade(10, "1")])
+                .unwrap(),
+            2
+        );
+        assert_eq!(store.write_trades(&[trade(30, "3")]).unwrap(), 1);
+        // 早于最后一条记录，合并重写
+        assert_eq!(
+            store
+                .write_trades(&[trade(15, "15"), trade(20, "2")])
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            store.last_trade_time(&Exchange::Okex, "BTC-USDT").unwrap(),
+            Some(at(30))
+        );
+        let trades = store
+            .trades(&Exchange::Okex, "BTC-USDT", at(0)..at(30))
+            .unwrap();
+        assert_eq!(
+            trades
+                .iter()
+                .map(|trade| trade.trade_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1", "15", "2"]
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
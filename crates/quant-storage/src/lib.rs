@@ -0,0 +1,68 @@
+pub mod error;
+pub mod file;
+pub mod memory;
+
+pub use file::FileStore;
+pub use memory::MemoryStore;
+
+use chrono::{DateTime, Utc};
+use error::Result;
+use quant_schema::{BookUpdate, Candle, Exchange, Interval, Trade};
+use std::ops::Range;
+
+/// 行情数据存储，用于历史数据回补和回测
+///
+/// K 线以开始时间去重，成交以成交ID去重，深度以时间和序列号去重；写入的数据不要求有序，读取时按时间升序返回。
+pub trait MarketDataStore {
+    /// 写入 K 线，已存在的 K 线会被覆盖；返回新增的数量
+    fn write_candles(&mut self, candles: &[Candle]) -> Result<usize>;
+
+    /// 写入成交，已存在的成交会被忽略；返回新增的数量
+    fn write_trades(&mut self, trades: &[Trade]) -> Result<usize>;
+
+    /// 写入深度变化，已存在的会被忽略；返回新增的数量
+    fn write_book_updates(&mut self, updates: &[BookUpdate]) -> Result<usize>;
+
+    /// 最后一根 K 线的开始时间
+    fn last_candle_time(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: Interval,
+    ) -> Result<Option<DateTime<Utc>>>;
+
+    /// 最后一笔成交的时间
+    fn last_trade_time(&self, exchange: &Exchange, symbol: &str) -> Result<Option<DateTime<Utc>>>;
+
+    /// 最后一次深度变化的时间
+    fn last_book_update_time(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+    ) -> Result<Option<DateTime<Utc>>>;
+
+    /// 开始时间在 `range` 内的 K 线
+    fn candles(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>>;
+
+    /// 成交时间在 `range` 内的成交
+    fn trades(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Trade>>;
+
+    /// 时间在 `range` 内的深度变化
+    fn book_updates(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<BookUpdate>>;
+}
@@ -0,0 +1,158 @@
+use crate::MarketDataStore;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use quant_schema::{BookUpdate, Candle, Exchange, Interval, Trade};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+type CandleKey = (Exchange, String, Interval);
+type SymbolKey = (Exchange, String);
+
+/// 内存存储，用于测试和短时间的回测
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    candles: HashMap<CandleKey, BTreeMap<DateTime<Utc>, Candle>>,
+    /// 以成交时间和成交ID为键
+    trades: HashMap<SymbolKey, BTreeMap<(DateTime<Utc>, String), Trade>>,
+    /// 以时间和序列号为键
+    books: HashMap<SymbolKey, BTreeMap<(DateTime<Utc>, i64), BookUpdate>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MarketDataStore for MemoryStore {
+    fn write_candles(&mut self, candles: &[Candle]) -> Result<usize> {
+        let mut added = 0;
+        for candle in candles {
+            let key = (
+                candle.exchange.clone(),
+                candle.symbol.clone(),
+                candle.interval,
+            );
+            let series = self.candles.entry(key).or_default();
+            if series.insert(candle.open_time, candle.clone()).is_none() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    fn write_trades(&mut self, trades: &[Trade]) -> Result<usize> {
+        let mut added = 0;
+        for trade in trades {
+            let key = (trade.exchange.clone(), trade.symbol.clone());
+            let series = self.trades.entry(key).or_default();
+            let trade_key = (trade.timestamp, trade.trade_id.clone());
+            if let Entry::Vacant(entry) = series.entry(trade_key) {
+                entry.insert(trade.clone());
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    fn write_book_updates(&mut self, updates: &[BookUpdate]) -> Result<usize> {
+        let mut added = 0;
+        for update in updates {
+            let key = (update.exchange.clone(), update.symbol.clone());
+            let series = self.books.entry(key).or_default();
+            if let Entry::Vacant(entry) = series.entry((update.timestamp, update.sequence)) {
+                entry.insert(update.clone());
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    fn last_candle_time(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: Interval,
+    ) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .candles
+            .get(&(exchange.clone(), symbol.to_string(), interval))
+            .and_then(|series| series.keys().next_back().copied()))
+    }
+
+    fn last_trade_time(&self, exchange: &Exchange, symbol: &str) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .trades
+            .get(&(exchange.clone(), symbol.to_string()))
+            .and_then(|series| series.keys().next_back().map(|(timestamp, _)| *timestamp)))
+    }
+
+    fn last_book_update_time(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .books
+            .get(&(exchange.clone(), symbol.to_string()))
+            .and_then(|series| series.keys().next_back().map(|(timestamp, _)| *timestamp)))
+    }
+
+    fn candles(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Candle>> {
+        Ok(self
+            .candles
+            .get(&(exchange.clone(), symbol.to_string(), interval))
+            .map(|series| {
+                series
+                    .range(range)
+                    .map(|(_, candle)| candle.clone())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn trades(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Trade>> {
+        Ok(self
+            .trades
+            .get(&(exchange.clone(), symbol.to_string()))
+            .map(|series| {
+                series
+                    .values()
+                    .filter(|trade| range.contains(&trade.timestamp))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn book_updates(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<BookUpdate>> {
+        Ok(self
+            .books
+            .get(&(exchange.clone(), symbol.to_string()))
+            .map(|series| {
+                series
+                    .values()
+                    .filter(|update| range.contains(&update.timestamp))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,192 @@
+use crate::types::Action;
+use chrono::{DateTime, Utc};
+use quant_order::OrderTracker;
+use quant_order::router::new_client_order_id;
+use quant_portfolio::Portfolio;
+use quant_schema::{Bar, BarType, Exchange, Order, OrderId, OrderRequest, Price, Ticker, Trade};
+use std::collections::{HashMap, VecDeque};
+
+type SymbolKey = (Exchange, String);
+
+/// 运行时缓存的最新行情，所有策略共享
+#[derive(Debug)]
+pub struct MarketState {
+    tickers: HashMap<SymbolKey, Ticker>,
+    trades: HashMap<SymbolKey, Trade>,
+    /// 每个产品每种 K 线保留最近 `bar_capacity` 根，按时间升序
+    bars: HashMap<(SymbolKey, BarType), VecDeque<Bar>>,
+    bar_capacity: usize,
+}
+
+impl Default for MarketState {
+    fn default() -> Self {
+        Self::new(500)
+    }
+}
+
+impl MarketState {
+    pub fn new(bar_capacity: usize) -> Self {
+        Self {
+            tickers: HashMap::new(),
+            trades: HashMap::new(),
+            bars: HashMap::new(),
+            bar_capacity,
+        }
+    }
+
+    pub fn ticker(&self, exchange: &Exchange, symbol: &str) -> Option<&Ticker> {
+        self.tickers.get(&(exchange.clone(), symbol.to_string()))
+    }
+
+    pub fn last_trade(&self, exchange: &Exchange, symbol: &str) -> Option<&Trade> {
+        self.trades.get(&(exchange.clone(), symbol.to_string()))
+    }
+
+    /// 最新成交价，优先使用逐笔成交，其次 ticker，最后 K 线收盘价
+    pub fn last_price(&self, exchange: &Exchange, symbol: &str) -> Option<Price> {
+        let key = (exchange.clone(), symbol.to_string());
+        self.trades
+            .get(&key)
+            .map(|trade| trade.price)
+            .or_else(|| self.tickers.get(&key).map(|ticker| ticker.last_price))
+            .or_else(|| {
+                self.bars
+                    .iter()
+                    .filter(|((bar_key, _), _)| *bar_key == key)
+                    .filter_map(|(_, bars)| bars.back())
+                    .max_by_key(|bar| bar.close_time)
+                    .map(|bar| bar.close)
+            })
+    }
+
+    /// 最近的 `count` 根 K 线，按时间升序
+    pub fn bars(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        bar_type: BarType,
+        count: usize,
+    ) -> Vec<&Bar> {
+        self.bars
+            .get(&((exchange.clone(), symbol.to_string()), bar_type))
+            .map(|bars| bars.iter().skip(bars.len().saturating_sub(count)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn update_ticker(&mut self, ticker: &Ticker) {
+        self.tickers.insert(
+            (
+                ticker.symbol.exchange.clone(),
+                ticker.symbol.exchange_symbol.clone(),
+            ),
+            ticker.clone(),
+        );
+    }
+
+    pub fn update_trade(&mut self, trade: &Trade) {
+        self.trades.insert(
+            (trade.exchange.clone(), trade.symbol.clone()),
+            trade.clone(),
+        );
+    }
+
+    pub fn update_bar(&mut self, bar: &Bar) {
+        let bars = self
+            .bars
+            .entry(((bar.exchange.clone(), bar.symbol.clone()), bar.bar_type))
+            .or_default();
+        bars.push_back(bar.clone());
+        while bars.len() > self.bar_capacity {
+            bars.pop_front();
+        }
+    }
+}
+
+/// 策略回调的上下文：行情、持仓、本策略的订单、时钟，以及发出交易指令
+///
+/// 交易指令不会立即发送，回调返回后由运行时依次发送，结果通过 `Strategy::on_order` 通知。
+pub struct Context<'a> {
+    strategy: usize,
+    now: DateTime<Utc>,
+    market: &'a MarketState,
+    portfolio: &'a Portfolio,
+    orders: &'a OrderTracker,
+    /// 客户端订单ID到策略的下标
+    owners: &'a HashMap<String, usize>,
+    actions: Vec<Action>,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn new(
+        strategy: usize,
+        now: DateTime<Utc>,
+        market: &'a MarketState,
+        portfolio: &'a Portfolio,
+        orders: &'a OrderTracker,
+        owners: &'a HashMap<String, usize>,
+    ) -> Self {
+        Self {
+            strategy,
+            now,
+            market,
+            portfolio,
+            orders,
+            owners,
+            actions: Vec::new(),
+        }
+    }
+
+    /// 当前时间：实盘为最新事件的时间，回测为回放到的时间
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    pub fn market(&self) -> &MarketState {
+        self.market
+    }
+
+    pub fn portfolio(&self) -> &Portfolio {
+        self.portfolio
+    }
+
+    /// 本策略发出的订单
+    pub fn order(&self, client_order_id: &str) -> Option<&Order> {
+        if self.owners.get(client_order_id) != Some(&self.strategy) {
+            return None;
+        }
+        self.orders.order(client_order_id)
+    }
+
+    /// 本策略未完成的订单
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.open_orders().filter(|order| {
+            order
+                .client_order_id
+                .as_ref()
+                .and_then(|id| self.owners.get(id))
+                == Some(&self.strategy)
+        })
+    }
+
+    /// 下单，返回客户端订单ID
+    pub fn submit(&mut self, exchange: Exchange, mut request: OrderRequest) -> String {
+        let client_order_id = request
+            .client_order_id
+            .get_or_insert_with(new_client_order_id)
+            .clone();
+        self.actions.push(Action::Submit { exchange, request });
+        client_order_id
+    }
+
+    pub fn cancel(&mut self, exchange: Exchange, symbol: impl Into<String>, order_id: OrderId) {
+        self.actions.push(Action::Cancel {
+            exchange,
+            symbol: symbol.into(),
+            order_id,
+        });
+    }
+
+    pub(crate) fn into_actions(self) -> Vec<Action> {
+        self.actions
+    }
+}
@@ -0,0 +1,19 @@
+use quant_schema::Exchange;
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Order(#[from] quant_order::error::Error),
+    /// 策略自定义的错误，会终止运行时
+    #[error("Strategy {strategy} failed: {message}")]
+    Strategy { strategy: String, message: String },
+    #[error("Symbol {1} on {0:?} is not registered")]
+    UnknownSymbol(Exchange, String),
+    #[error("No price for {1} on {0:?}")]
+    NoPrice(Exchange, String),
+    #[error("No {1} balance on {0:?}")]
+    NoBalance(Exchange, String),
+}
@@ -0,0 +1,615 @@
+use crate::context::Context;
+use crate::error::{Error, Result};
+use quant_schema::{
+    Decimal, Exchange, Fill, OrderId, OrderRequest, Price, Quantity, Rounding, Side, Symbol,
+    TimeInForce,
+};
+use std::collections::HashMap;
+
+/// 调整持仓时的下单方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Execution {
+    Market,
+    Limit {
+        price: Price,
+        time_in_force: TimeInForce,
+    },
+}
+
+/// 策略的交易意图，由 [`IntentTranslator`] 结合当前持仓换算为具体的订单
+///
+/// 数量的单位与订单一致：现货为交易货币，合约为张数。
+#[derive(Debug, Clone)]
+pub enum Intent {
+    /// 把持仓调整到目标数量，多头为正、空头为负，0 表示平仓
+    TargetPosition {
+        exchange: Exchange,
+        symbol: String,
+        quantity: Quantity,
+        execution: Execution,
+    },
+
+    /// 按账户权益的比例调整持仓，负数为空头；需要先用 `IntentTranslator::add_symbol` 登记产品
+    TargetWeight {
+        exchange: Exchange,
+        symbol: String,
+        weight: Decimal,
+        execution: Execution,
+    },
+
+    /// 直接下单，限价单和市价单见 `OrderRequest::limit`、`OrderRequest::market`
+    Order {
+        exchange: Exchange,
+        request: OrderRequest,
+    },
+
+    Cancel {
+        exchange: Exchange,
+        symbol: String,
+        order_id: OrderId,
+    },
+
+    /// 撤掉旧订单并按新请求下单，撤单和下单依次发送，不等待撤单结果
+    Replace {
+        exchange: Exchange,
+        order_id: OrderId,
+        request: OrderRequest,
+    },
+
+    /// 入场单加止盈止损：入场单每次成交后按成交数量挂只减仓的止盈限价单，
+    /// 最新价触及止损价时撤销止盈单并市价平掉已成交的部分
+    Bracket {
+        exchange: Exchange,
+        entry: OrderRequest,
+        take_profit: Price,
+        stop_loss: Price,
+    },
+}
+
+/// 进行中的止盈止损
+#[derive(Debug, Clone)]
+struct Bracket {
+    exchange: Exchange,
+    symbol: String,
+    /// 入场方向，止盈止损单方向相反
+    side: Side,
+    entry: String,
+    take_profit: Price,
+    stop_loss: Price,
+    take_profit_orders: Vec<String>,
+    /// 已入场但尚未止盈或止损的数量
+    open_quantity: Quantity,
+}
+
+impl Bracket {
+    fn stopped(&self, price: Price) -> bool {
+        match self.side {
+            Side::Buy => price <= self.stop_loss,
+            Side::Sell => price >= self.stop_loss,
+        }
+    }
+}
+
+/// 把 [`Intent`] 换算为订单并通过 `Context` 发出，同时管理止盈止损
+///
+/// 每个策略持有自己的转换器。目标持仓类意图以 quant-portfolio 的持仓加上本策略未完成的订单为当前数量，
+/// 只下差额部分；需要改价时先撤单或使用 `Intent::Replace`。止盈止损订单不计入当前数量。
+/// 策略需要在 `on_fill` 中调用 [`IntentTranslator::on_fill`]，在行情回调中调用 [`IntentTranslator::check_stops`]。
+#[derive(Debug, Default, Clone)]
+pub struct IntentTranslator {
+    symbols: HashMap<(Exchange, String), Symbol>,
+    brackets: Vec<Bracket>,
+}
+
+impl IntentTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记产品信息，用于数量和价格取整、按权重计算数量以及判断是否可以只减仓
+    pub fn add_symbol(&mut self, symbol: Symbol) {
+        self.symbols.insert(
+            (symbol.exchange.clone(), symbol.exchange_symbol.clone()),
+            symbol,
+        );
+    }
+
+    /// 发出意图对应的订单，返回新订单的客户端订单ID；持仓已经达到目标时不下单
+    pub fn submit(&mut self, ctx: &mut Context<'_>, intent: Intent) -> Result<Vec<String>> {
+        match intent {
+            Intent::TargetPosition {
+                exchange,
+                symbol,
+                quantity,
+                execution,
+            } => Ok(self
+                .rebalance(ctx, exchange, &symbol, quantity, execution)
+                .into_iter()
+                .collect()),
+            Intent::TargetWeight {
+                exchange,
+                symbol,
+                weight,
+                execution,
+            } => {
+                let quantity = self.weight_quantity(ctx, &exchange, &symbol, weight, execution)?;
+                Ok(self
+                    .rebalance(ctx, exchange, &symbol, quantity, execution)
+                    .into_iter()
+                    .collect())
+            }
+            Intent::Order { exchange, request } => Ok(vec![ctx.submit(exchange, request)]),
+            Intent::Cancel {
+                exchange,
+                symbol,
+                order_id,
+            } => {
+                ctx.cancel(exchange, symbol, order_id);
+                Ok(Vec::new())
+            }
+            Intent::Replace {
+                exchange,
+                order_id,
+                request,
+            } => {
+                ctx.cancel(exchange.clone(), request.symbol.clone(), order_id);
+                Ok(vec![ctx.submit(exchange, request)])
+            }
+            Intent::Bracket {
+                exchange,
+                entry,
+                take_profit,
+                stop_loss,
+            } => {
+                let symbol = entry.symbol.clone();
+                let side = entry.side;
+                let entry = ctx.submit(exchange.clone(), entry);
+                self.brackets.push(Bracket {
+                    exchange,
+                    symbol,
+                    side,
+                    entry: entry.clone(),
+                    take_profit,
+                    stop_loss,
+                    take_profit_orders: Vec::new(),
+                    open_quantity: Quantity::ZERO,
+                });
+                Ok(vec![entry])
+            }
+        }
+    }
+
+    /// 处理本策略的成交：入场单成交后挂止盈单，止盈单成交后减少待保护的数量
+    pub fn on_fill(&mut self, ctx: &mut Context<'_>, fill: &Fill) {
+        let Some(client_order_id) = fill.client_order_id.as_deref() else {
+            return;
+        };
+        let Some(index) = self.brackets.iter().position(|bracket| {
+            bracket.entry == client_order_id
+                || bracket
+                    .take_profit_orders
+                    .iter()
+                    .any(|id| id == client_order_id)
+        }) else {
+            return;
+        };
+
+        let bracket = &mut self.brackets[index];
+        if bracket.entry == client_order_id {
+            bracket.open_quantity += fill.quantity;
+            let request = OrderRequest::limit(
+                &bracket.symbol,
+                bracket.side.opposite(),
+                fill.quantity,
+                bracket.take_profit,
+            )
+            .reduce_only(true);
+            let id = ctx.submit(bracket.exchange.clone(), request);
+            bracket.take_profit_orders.push(id);
+        } else {
+            bracket.open_quantity = (bracket.open_quantity - fill.quantity).max(Quantity::ZERO);
+        }
+        self.remove_finished(ctx);
+    }
+
+    /// 用最新价检查止损，触发时撤销入场单和止盈单，并市价平掉已入场的数量
+    pub fn check_stops(&mut self, ctx: &mut Context<'_>) {
+        self.remove_finished(ctx);
+        let mut stopped = Vec::new();
+        for (index, bracket) in self.brackets.iter().enumerate() {
+            if bracket.open_quantity.is_zero() {
+                continue;
+            }
+            if let Some(price) = ctx.market().last_price(&bracket.exchange, &bracket.symbol)
+                && bracket.stopped(price)
+            {
+                stopped.push(index);
+            }
+        }
+
+        for index in stopped.into_iter().rev() {
+            let bracket = self.brackets.remove(index);
+            for id in std::iter::once(&bracket.entry).chain(&bracket.take_profit_orders) {
+                if ctx.order(id).is_some_and(|order| order.status.is_open()) {
+                    ctx.cancel(
+                        bracket.exchange.clone(),
+                        &bracket.symbol,
+                        OrderId::Client(id.clone()),
+                    );
+                }
+            }
+            let request = OrderRequest::market(
+                &bracket.symbol,
+                bracket.side.opposite(),
+                bracket.open_quantity,
+            )
+            .reduce_only(true);
+            ctx.submit(bracket.exchange, request);
+        }
+    }
+
+    /// 进行中的止盈止损数量
+    pub fn active_brackets(&self) -> usize {
+        self.brackets.len()
+    }
+
+    /// 入场单已结束、已入场的数量全部止盈，或入场单未成交就结束的止盈止损不再需要跟踪
+    fn remove_finished(&mut self, ctx: &Context<'_>) {
+        self.brackets.retain(|bracket| {
+            let entry_open = ctx
+                .order(&bracket.entry)
+                .is_none_or(|order| order.status.is_open());
+            entry_open || !bracket.open_quantity.is_zero()
+        });
+    }
+
+    /// 下单补足目标数量与当前数量的差额，差额取整后为 0 或不满足最小下单量时不下单
+    fn rebalance(
+        &self,
+        ctx: &mut Context<'_>,
+        exchange: Exchange,
+        symbol: &str,
+        target: Quantity,
+        execution: Execution,
+    ) -> Option<String> {
+        let position = ctx.portfolio().net_quantity(&exchange, symbol);
+        let pending: Quantity = ctx
+            .open_orders()
+            .filter(|order| order.exchange == exchange && order.symbol == symbol)
+            .filter(|order| !self.is_bracket_order(order.client_order_id.as_deref()))
+            .map(|order| match order.side {
+                Side::Buy => order.remaining_quantity(),
+                Side::Sell => -order.remaining_quantity(),
+            })
+            .sum();
+        let current = position + pending;
+        let delta = target - current;
+        let side = if delta > Quantity::ZERO {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+
+        let info = self.symbols.get(&(exchange.clone(), symbol.to_string()));
+        let quantity = match info {
+            Some(info) => info.round_quantity(delta.abs()),
+            None => delta.abs(),
+        };
+        if quantity.is_zero() || info.is_some_and(|info| quantity < info.min_qty) {
+            return None;
+        }
+        // 只减少持仓、不反手时设置只减仓，现货不支持只减仓
+        let reduce_only = info.is_some_and(|info| info.is_derivative())
+            && !position.is_zero()
+            && (position > Quantity::ZERO) != (side == Side::Buy)
+            && quantity <= position.abs();
+
+        let request = match execution {
+            Execution::Market => OrderRequest::market(symbol, side, quantity),
+            Execution::Limit {
+                price,
+                time_in_force,
+            } => {
+                let price = match info {
+                    Some(info) => info.round_price(
+                        price,
+                        match side {
+                            Side::Buy => Rounding::Down,
+                            Side::Sell => Rounding::Up,
+                        },
+                    ),
+                    None => price,
+                };
+                OrderRequest::limit(symbol, side, quantity, price).time_in_force(time_in_force)
+            }
+        };
+        Some(ctx.submit(exchange, request.reduce_only(reduce_only)))
+    }
+
+    /// 按权重换算的目标数量
+    ///
+    /// 权益取结算货币（现货为计价货币）的余额，价格取限价或最新价。U 本位合约面值以交易货币计，
+    /// 币本位合约权益以交易货币计、面值以计价货币计。
+    fn weight_quantity(
+        &self,
+        ctx: &Context<'_>,
+        exchange: &Exchange,
+        symbol: &str,
+        weight: Decimal,
+        execution: Execution,
+    ) -> Result<Quantity> {
+        let info = self
+            .symbols
+            .get(&(exchange.clone(), symbol.to_string()))
+            .ok_or_else(|| Error::UnknownSymbol(exchange.clone(), symbol.to_string()))?;
+        let price = match execution {
+            Execution::Limit { price, .. } => Some(price),
+            Execution::Market => ctx.market().last_price(exchange, symbol),
+        }
+        .filter(|price| !price.is_zero())
+        .ok_or_else(|| Error::NoPrice(exchange.clone(), symbol.to_string()))?;
+        let asset = info.settle_asset.as_deref().unwrap_or(&info.quote_asset);
+        let equity = ctx
+            .portfolio()
+            .balance(exchange, asset)
+            .map(|balance| balance.total())
+            .ok_or_else(|| Error::NoBalance(exchange.clone(), asset.to_string()))?;
+
+        let quantity = match info.contract_value {
+            Some(contract_value) if info.is_derivative() && !contract_value.is_zero() => {
+                if info.settle_asset.as_deref() == Some(info.base_asset.as_str()) {
+                    equity * weight * price.value() / contract_value
+                } else {
+                    equity * weight / (price.value() * contract_value)
+                }
+            }
+            _ => equity * weight / price.value(),
+        };
+        Ok(Quantity::new(quantity))
+    }
+
+    fn is_bracket_order(&self, client_order_id: Option<&str>) -> bool {
+        let Some(id) = client_order_id else {
+            return false;
+        };
+        self.brackets
+            .iter()
+            .any(|bracket| bracket.take_profit_orders.iter().any(|order| order == id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::MarketState;
+    use crate::types::Action;
+    use chrono::DateTime;
+    use quant_order::OrderTracker;
+    use quant_portfolio::Portfolio;
+    use quant_schema::{Balance, MarketType, Order, OrderAck, Trade};
+
+    fn swap() -> Symbol {
+        Symbol {
+            name: "BTC-USDT-SWAP".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USDT".to_string(),
+            exchange: Exchange::Okex,
+            exchange_symbol: "BTC-USDT-SWAP".to_string(),
+            market_type: MarketType::Swap,
+            status: "live".to_string(),
+            min_qty: "0.1".parse().unwrap(),
+            lot_size: "0.1".parse().unwrap(),
+            tick_size: "0.1".parse().unwrap(),
+            min_notional: Decimal::ZERO,
+            contract_value: Some("0.01".parse().unwrap()),
+            settle_asset: Some("USDT".to_string()),
+            expiry: None,
+        }
+    }
+
+    fn trade(price: &str) -> Trade {
+        Trade {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT-SWAP".to_string(),
+            trade_id: "1".to_string(),
+            price: price.parse().unwrap(),
+            quantity: "1".parse().unwrap(),
+            side: Side::Buy,
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+        }
+    }
+
+    fn fill(client_order_id: &str, side: Side, quantity: &str) -> Fill {
+        Fill {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT-SWAP".to_string(),
+            trade_id: client_order_id.to_string(),
+            order_id: client_order_id.to_string(),
+            client_order_id: Some(client_order_id.to_string()),
+            side,
+            price: "100".parse().unwrap(),
+            quantity: quantity.parse().unwrap(),
+            fee: Decimal::ZERO,
+            fee_asset: "USDT".to_string(),
+            is_maker: false,
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+        }
+    }
+
+    /// 按交易所接受处理发出的订单，返回新订单的请求
+    fn accept(
+        actions: Vec<Action>,
+        tracker: &mut OrderTracker,
+        owners: &mut HashMap<String, usize>,
+    ) -> Vec<Action> {
+        for action in &actions {
+            if let Action::Submit { exchange, request } = action {
+                let id = request.client_order_id.clone().unwrap();
+                let ack = OrderAck {
+                    exchange: exchange.clone(),
+                    symbol: request.symbol.clone(),
+                    order_id: id.clone(),
+                    client_order_id: Some(id.clone()),
+                    timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+                };
+                tracker.insert(Order::new(request, &ack));
+                owners.insert(id, 0);
+            }
+        }
+        actions
+    }
+
+    fn request(action: &Action) -> &OrderRequest {
+        match action {
+            Action::Submit { request, .. } => request,
+            Action::Cancel { .. } => panic!("expected a new order, got {action:?}"),
+        }
+    }
+
+    #[test]
+    fn test_target_position_and_weight() {
+        let exchange = Exchange::Okex;
+        let mut market = MarketState::default();
+        market.update_trade(&trade("100"));
+        let mut portfolio = Portfolio::new();
+        portfolio.set_balances(
+            &exchange,
+            vec![Balance {
+                asset: "USDT".to_string(),
+                free: "1000".parse().unwrap(),
+                locked: Decimal::ZERO,
+            }],
+        );
+        portfolio.apply_fill(&fill("held", Side::Buy, "3"));
+        let mut tracker = OrderTracker::new();
+        let mut owners = HashMap::new();
+        let mut translator = IntentTranslator::new();
+        translator.add_symbol(swap());
+        let now = DateTime::from_timestamp(0, 0).unwrap();
+
+        // 持仓 3 张，目标 1 张：只减仓卖出 2 张
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        let target = Intent::TargetPosition {
+            exchange: exchange.clone(),
+            symbol: "BTC-USDT-SWAP".to_string(),
+            quantity: "1".parse().unwrap(),
+            execution: Execution::Limit {
+                price: "100.05".parse().unwrap(),
+                time_in_force: TimeInForce::PostOnly,
+            },
+        };
+        translator.submit(&mut ctx, target.clone()).unwrap();
+        let actions = accept(ctx.into_actions(), &mut tracker, &mut owners);
+        let sell = request(&actions[0]);
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!(sell.quantity, "2".parse().unwrap());
+        assert_eq!(sell.price, Some("100.1".parse().unwrap()));
+        assert!(sell.reduce_only);
+
+        // 挂单计入当前数量，重复的意图不再下单
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        assert!(translator.submit(&mut ctx, target).unwrap().is_empty());
+
+        // 1000 USDT 的 50% 做空，100 USDT 每个 BTC、每张 0.01 BTC：目标 -500 张
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        translator
+            .submit(
+                &mut ctx,
+                Intent::TargetWeight {
+                    exchange: exchange.clone(),
+                    symbol: "BTC-USDT-SWAP".to_string(),
+                    weight: "-0.5".parse().unwrap(),
+                    execution: Execution::Market,
+                },
+            )
+            .unwrap();
+        let actions = ctx.into_actions();
+        let sell = request(&actions[0]);
+        assert_eq!(sell.quantity, "501".parse().unwrap());
+        assert!(!sell.reduce_only);
+
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        let result = translator.submit(
+            &mut ctx,
+            Intent::TargetWeight {
+                exchange: exchange.clone(),
+                symbol: "ETH-USDT-SWAP".to_string(),
+                weight: Decimal::ONE,
+                execution: Execution::Market,
+            },
+        );
+        assert!(matches!(result, Err(Error::UnknownSymbol(..))));
+    }
+
+    #[test]
+    fn test_bracket() {
+        let exchange = Exchange::Okex;
+        let mut market = MarketState::default();
+        let portfolio = Portfolio::new();
+        let mut tracker = OrderTracker::new();
+        let mut owners = HashMap::new();
+        let mut translator = IntentTranslator::new();
+        let now = DateTime::from_timestamp(0, 0).unwrap();
+
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        let entry = translator
+            .submit(
+                &mut ctx,
+                Intent::Bracket {
+                    exchange: exchange.clone(),
+                    entry: OrderRequest::limit(
+                        "BTC-USDT-SWAP",
+                        Side::Buy,
+                        "2".parse().unwrap(),
+                        "100".parse().unwrap(),
+                    ),
+                    take_profit: "110".parse().unwrap(),
+                    stop_loss: "95".parse().unwrap(),
+                },
+            )
+            .unwrap()
+            .remove(0);
+        accept(ctx.into_actions(), &mut tracker, &mut owners);
+
+        // 入场单部分成交，按成交数量挂止盈单
+        let entry_fill = fill(&entry, Side::Buy, "1");
+        tracker.apply_fill(&entry_fill).unwrap();
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        translator.on_fill(&mut ctx, &entry_fill);
+        let actions = accept(ctx.into_actions(), &mut tracker, &mut owners);
+        let take_profit = request(&actions[0]);
+        assert_eq!(take_profit.side, Side::Sell);
+        assert_eq!(take_profit.quantity, "1".parse().unwrap());
+        assert_eq!(take_profit.price, Some("110".parse().unwrap()));
+        assert!(take_profit.reduce_only);
+        let take_profit = take_profit.client_order_id.clone().unwrap();
+
+        // 未触及止损
+        market.update_trade(&trade("96"));
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        translator.check_stops(&mut ctx);
+        assert!(ctx.into_actions().is_empty());
+
+        // 触及止损：撤销入场单和止盈单，市价平掉 1 张
+        market.update_trade(&trade("95"));
+        let mut ctx = Context::new(0, now, &market, &portfolio, &tracker, &owners);
+        translator.check_stops(&mut ctx);
+        let actions = ctx.into_actions();
+        let canceled: Vec<_> = actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Cancel {
+                    order_id: OrderId::Client(id),
+                    ..
+                } => Some(id.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(canceled, vec![entry, take_profit]);
+        let stop = request(&actions[2]);
+        assert_eq!(stop.side, Side::Sell);
+        assert_eq!(stop.quantity, "1".parse().unwrap());
+        assert!(stop.reduce_only);
+        assert_eq!(translator.active_brackets(), 0);
+    }
+}
@@ -1,17 +1,83 @@
-// pub use crate::ss::{MarketData, Signal};
-// pub trait Strategy{
-//     /// Init Strategy
-//     fn on_init(&mut self);
+pub mod context;
+pub mod error;
+pub mod intent;
+pub mod runtime;
+pub mod types;
 
-//     /// Receive market updates.
-//     fn on_tick(&mut self, data: &MarketData) -> Optional<Signal>;
+pub use context::{Context, MarketState};
+pub use intent::{Execution, Intent, IntentTranslator};
+pub use runtime::{Feeds, StrategyRuntime};
+pub use types::Action;
 
-//     /// Received K-line bars (such as 1 min, 5 min)
-//     fn on_bar(&mut self, data: &MarketData) -> Optional<Signal>;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use error::Result;
+use quant_schema::{Bar, Fill, MarketEvent, Order, Ticker, Trade};
 
-//     /// Optional: Periodic checks(such as periodic clearing) receive candlestick bars(such as 1 minute, 5 minutes)
-//     fn on_timer(&mut self);
+/// 交易策略，由 `StrategyRuntime` 按事件驱动
+///
+/// 所有回调都有空的默认实现，策略只需实现关心的事件。回调中通过 `Context` 读取行情和持仓、发出交易指令，
+/// 返回错误会终止运行时。
+#[async_trait]
+pub trait Strategy: Send {
+    /// 策略名称，用于日志和错误信息
+    fn name(&self) -> &str;
 
-//     /// Optional: Strategy ended, clean up
-//     fn on_exit(&mut self);
-// }
+    /// 开始运行前调用一次
+    async fn on_init(&mut self, _ctx: &mut Context<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// 收到 ticker
+    async fn on_tick(
+        &mut self,
+        _ctx: &mut Context<'_>,
+        _ticker: &MarketEvent<Ticker>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// 收到逐笔成交
+    async fn on_trade(
+        &mut self,
+        _ctx: &mut Context<'_>,
+        _trade: &MarketEvent<Trade>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// 收到已完结的 K 线（如 1 分钟、5 分钟）
+    async fn on_bar(&mut self, _ctx: &mut Context<'_>, _bar: &Bar) -> Result<()> {
+        Ok(())
+    }
+
+    /// 本策略的订单状态变化：交易所接受、被拒绝、成交、撤销
+    async fn on_order(&mut self, _ctx: &mut Context<'_>, _order: &Order) -> Result<()> {
+        Ok(())
+    }
+
+    /// 本策略的订单成交，在 `on_order` 之前调用
+    async fn on_fill(&mut self, _ctx: &mut Context<'_>, _fill: &Fill) -> Result<()> {
+        Ok(())
+    }
+
+    /// 交易指令发送失败，下单失败时还会收到状态为 `Rejected` 的 `on_order`
+    async fn on_action_error(
+        &mut self,
+        _ctx: &mut Context<'_>,
+        _action: &Action,
+        _error: &quant_order::error::Error,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// 定时回调，用于定期检查（如定时平仓）
+    async fn on_timer(&mut self, _ctx: &mut Context<'_>, _now: DateTime<Utc>) -> Result<()> {
+        Ok(())
+    }
+
+    /// 运行结束前调用一次，用于清理
+    async fn on_exit(&mut self, _ctx: &mut Context<'_>) -> Result<()> {
+        Ok(())
+    }
+}
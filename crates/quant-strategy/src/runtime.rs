@@ -0,0 +1,538 @@
+use crate::Strategy;
+use crate::context::{Context, MarketState};
+use crate::error::Result;
+use crate::types::Action;
+use chrono::{DateTime, Utc};
+use quant_order::OrderRouter;
+use quant_order::router::new_client_order_id;
+use quant_portfolio::Portfolio;
+use quant_schema::{Bar, Exchange, Fill, MarketEvent, OrderId, OrderStatus, Ticker, Trade};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+/// 在一次回调中调用某个策略，返回它发出的交易指令
+macro_rules! dispatch {
+    ($runtime:ident, $index:expr, |$strategy:ident, $ctx:ident| $call:expr) => {{
+        let index = $index;
+        let mut $ctx = Context::new(
+            index,
+            $runtime.now,
+            &$runtime.market,
+            &$runtime.portfolio,
+            $runtime.router.tracker(),
+            &$runtime.owners,
+        );
+        let $strategy = &mut $runtime.strategies[index];
+        $call.await?;
+        $ctx.into_actions()
+    }};
+}
+
+/// `StrategyRuntime::run` 的输入，未设置的数据源不会被读取
+#[derive(Default)]
+pub struct Feeds {
+    /// `MarketDataService::subscribe_tickers`
+    pub tickers: Option<broadcast::Receiver<MarketEvent<Ticker>>>,
+    /// `MarketDataService::subscribe_trades`
+    pub trades: Option<broadcast::Receiver<MarketEvent<Trade>>>,
+    /// `BarAggregator::run` 广播的 K 线
+    pub bars: Option<broadcast::Receiver<Bar>>,
+    /// 私有频道的成交回报
+    pub fills: Option<mpsc::UnboundedReceiver<Fill>>,
+}
+
+impl Feeds {
+    fn is_closed(&self) -> bool {
+        self.tickers.is_none()
+            && self.trades.is_none()
+            && self.bars.is_none()
+            && self.fills.is_none()
+    }
+}
+
+/// 策略运行时：把行情和成交回报分发给已注册的策略，并把策略的交易指令发送到 `OrderRouter`
+///
+/// 所有策略共享行情缓存和持仓，订单按客户端订单ID归属到发出它的策略。
+/// 时钟取最新事件的时间（交易所时间优先），回放历史数据时与实盘行为一致。
+pub struct StrategyRuntime<'a> {
+    strategies: Vec<Box<dyn Strategy + 'a>>,
+    router: OrderRouter<'a>,
+    market: MarketState,
+    portfolio: Portfolio,
+    /// 客户端订单ID到策略的下标
+    owners: HashMap<String, usize>,
+    now: DateTime<Utc>,
+    timer_interval: Duration,
+}
+
+impl<'a> StrategyRuntime<'a> {
+    pub fn new(router: OrderRouter<'a>) -> Self {
+        Self {
+            strategies: Vec::new(),
+            router,
+            market: MarketState::default(),
+            portfolio: Portfolio::new(),
+            owners: HashMap::new(),
+            now: DateTime::UNIX_EPOCH,
+            timer_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// `run` 中 `on_timer` 的间隔，默认 1 秒
+    pub fn timer_interval(mut self, timer_interval: Duration) -> Self {
+        self.timer_interval = timer_interval;
+        self
+    }
+
+    /// 行情缓存，默认每种 K 线保留 500 根
+    pub fn market_state(mut self, market: MarketState) -> Self {
+        self.market = market;
+        self
+    }
+
+    pub fn add_strategy(&mut self, strategy: impl Strategy + 'a) {
+        self.strategies.push(Box::new(strategy));
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    pub fn market(&self) -> &MarketState {
+        &self.market
+    }
+
+    pub fn portfolio(&self) -> &Portfolio {
+        &self.portfolio
+    }
+
+    pub fn portfolio_mut(&mut self) -> &mut Portfolio {
+        &mut self.portfolio
+    }
+
+    pub fn router(&self) -> &OrderRouter<'a> {
+        &self.router
+    }
+
+    pub fn router_mut(&mut self) -> &mut OrderRouter<'a> {
+        &mut self.router
+    }
+
+    /// 从各交易所加载余额和持仓，覆盖本地的持仓
+    pub async fn sync_portfolio(&mut self) -> Result<()> {
+        let exchanges: Vec<_> = self.router.exchanges().cloned().collect();
+        for exchange in exchanges {
+            let venue = self.router.venue_mut(&exchange)?;
+            let balances = venue
+                .balances()
+                .await
+                .map_err(quant_order::error::Error::from)?;
+            let positions = venue
+                .positions()
+                .await
+                .map_err(quant_order::error::Error::from)?;
+            self.portfolio.set_balances(&exchange, balances);
+            self.portfolio.set_positions(&exchange, positions);
+        }
+        Ok(())
+    }
+
+    /// 调用所有策略的 `on_init`
+    pub async fn init(&mut self) -> Result<()> {
+        for index in 0..self.strategies.len() {
+            let actions = dispatch!(self, index, |strategy, ctx| strategy.on_init(&mut ctx));
+            self.execute(index, actions).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn on_ticker(&mut self, event: &MarketEvent<Ticker>) -> Result<()> {
+        self.advance(event.exchange_time.unwrap_or(event.received.wall));
+        self.market.update_ticker(&event.data);
+        for index in 0..self.strategies.len() {
+            let actions = dispatch!(self, index, |strategy, ctx| strategy
+                .on_tick(&mut ctx, event));
+            self.execute(index, actions).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn on_trade(&mut self, event: &MarketEvent<Trade>) -> Result<()> {
+        self.advance(event.exchange_time.unwrap_or(event.received.wall));
+        self.market.update_trade(&event.data);
+        for index in 0..self.strategies.len() {
+            let actions = dispatch!(self, index, |strategy, ctx| strategy
+                .on_trade(&mut ctx, event));
+            self.execute(index, actions).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn on_bar(&mut self, bar: &Bar) -> Result<()> {
+        self.advance(bar.close_time);
+        self.market.update_bar(bar);
+        for index in 0..self.strategies.len() {
+            let actions = dispatch!(self, index, |strategy, ctx| strategy.on_bar(&mut ctx, bar));
+            self.execute(index, actions).await?;
+        }
+        Ok(())
+    }
+
+    /// 更新订单和持仓，并通知发出订单的策略；不属于任何策略的成交只更新持仓，重复的成交被忽略
+    pub async fn on_fill(&mut self, fill: &Fill) -> Result<()> {
+        self.advance(fill.timestamp);
+        // 先由订单跟踪按成交ID去重，重复推送的成交不再计入持仓
+        let order = match self.router.apply_fill(fill) {
+            Ok(order) => Some(order.clone()),
+            Err(quant_order::error::Error::UnknownOrder(_)) => None,
+            Err(quant_order::error::Error::DuplicateFill(_)) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        self.portfolio.apply_fill(fill);
+        let Some(order) = order else {
+            return Ok(());
+        };
+        let Some(index) = order
+            .client_order_id
+            .as_ref()
+            .and_then(|id| self.owners.get(id))
+            .copied()
+        else {
+            return Ok(());
+        };
+        let mut actions = dispatch!(self, index, |strategy, ctx| strategy
+            .on_fill(&mut ctx, fill));
+        actions.extend(dispatch!(self, index, |strategy, ctx| strategy
+            .on_order(&mut ctx, &order)));
+        self.execute(index, actions).await
+    }
+
+    /// 交易所推送的订单状态变化（如撤单、过期），通知发出订单的策略
+    pub async fn on_order_status(
+        &mut self,
+        exchange: &Exchange,
+        order_id: &OrderId,
+        status: OrderStatus,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.advance(at);
+        let Some(order) = self
+            .router
+            .on_status(exchange, order_id, status, at)
+            .cloned()
+        else {
+            return Ok(());
+        };
+        let Some(index) = order
+            .client_order_id
+            .as_ref()
+            .and_then(|id| self.owners.get(id))
+            .copied()
+        else {
+            return Ok(());
+        };
+        let actions = dispatch!(self, index, |strategy, ctx| strategy
+            .on_order(&mut ctx, &order));
+        self.execute(index, actions).await
+    }
+
+    pub async fn on_timer(&mut self, now: DateTime<Utc>) -> Result<()> {
+        self.advance(now);
+        for index in 0..self.strategies.len() {
+            let actions = dispatch!(self, index, |strategy, ctx| strategy
+                .on_timer(&mut ctx, now));
+            self.execute(index, actions).await?;
+        }
+        Ok(())
+    }
+
+    /// 调用所有策略的 `on_exit`
+    pub async fn shutdown(&mut self) -> Result<()> {
+        for index in 0..self.strategies.len() {
+            let actions = dispatch!(self, index, |strategy, ctx| strategy.on_exit(&mut ctx));
+            self.execute(index, actions).await?;
+        }
+        Ok(())
+    }
+
+    /// 初始化策略后持续处理 `feeds` 中的事件，并按 `timer_interval` 调用 `on_timer`；
+    /// 全部数据源关闭后调用 `on_exit` 并返回
+    pub async fn run(&mut self, mut feeds: Feeds) -> Result<()> {
+        self.init().await?;
+        let mut timer = tokio::time::interval(self.timer_interval);
+        while !feeds.is_closed() {
+            tokio::select! {
+                Some(event) = next(&mut feeds.tickers) => self.on_ticker(&event).await?,
+                Some(event) = next(&mut feeds.trades) => self.on_trade(&event).await?,
+                Some(bar) = next(&mut feeds.bars) => self.on_bar(&bar).await?,
+                Some(fill) = next_fill(&mut feeds.fills) => self.on_fill(&fill).await?,
+                _ = timer.tick() => self.on_timer(Utc::now()).await?,
+            }
+        }
+        self.shutdown().await
+    }
+
+    /// 时钟只前进不后退
+    fn advance(&mut self, time: DateTime<Utc>) {
+        self.now = self.now.max(time);
+    }
+
+    /// 依次发送交易指令，并把结果通知发出指令的策略；通知中产生的新指令排在队尾继续发送
+    async fn execute(&mut self, index: usize, actions: Vec<Action>) -> Result<()> {
+        let mut queue: VecDeque<_> = actions.into_iter().map(|action| (index, action)).collect();
+        while let Some((index, action)) = queue.pop_front() {
+            let (order, error) = match &action {
+                Action::Submit { exchange, request } => {
+                    // 先生成客户端订单号再登记归属，成交和状态推送才能找到发出指令的策略
+                    let mut request = request.clone();
+                    let client_order_id = request
+                        .client_order_id
+                        .get_or_insert_with(new_client_order_id)
+                        .clone();
+                    self.owners.insert(client_order_id.clone(), index);
+                    let error = self.router.submit(exchange, request).await.err();
+                    (
+                        self.router.tracker().order(&client_order_id).cloned(),
+                        error,
+                    )
+                }
+                Action::Cancel {
+                    exchange,
+                    symbol,
+                    order_id,
+                } => match self.router.cancel(exchange, symbol, order_id).await {
+                    Ok(_) => (
+                        self.router.tracker().find(exchange, order_id).cloned(),
+                        None,
+                    ),
+                    Err(err) => (None, Some(err)),
+                },
+            };
+            if let Some(error) = error {
+                let actions = dispatch!(self, index, |strategy, ctx| strategy
+                    .on_action_error(&mut ctx, &action, &error));
+                queue.extend(actions.into_iter().map(|action| (index, action)));
+            }
+            if let Some(order) = order {
+                let actions = dispatch!(self, index, |strategy, ctx| strategy
+                    .on_order(&mut ctx, &order));
+                queue.extend(actions.into_iter().map(|action| (index, action)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取下一条数据，跳过处理不及时丢失的数据；通道关闭后把数据源置为 `None`，之后不再返回
+async fn next<T: Clone>(feed: &mut Option<broadcast::Receiver<T>>) -> Option<T> {
+    loop {
+        let Some(receiver) = feed else {
+            return std::future::pending().await;
+        };
+        match receiver.recv().await {
+            Ok(item) => return Some(item),
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => {
+                *feed = None;
+                return None;
+            }
+        }
+    }
+}
+
+async fn next_fill(feed: &mut Option<mpsc::UnboundedReceiver<Fill>>) -> Option<Fill> {
+    let Some(receiver) = feed else {
+        return std::future::pending().await;
+    };
+    let fill = receiver.recv().await;
+    if fill.is_none() {
+        *feed = None;
+    }
+    fill
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use quant_exchange::TradingVenue;
+    use quant_schema::{Balance, BarType, Interval, Order, OrderAck, OrderRequest, Position, Side};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct MockVenue;
+
+    #[async_trait]
+    impl TradingVenue for MockVenue {
+        fn exchange(&self) -> Exchange {
+            Exchange::Okex
+        }
+
+        async fn submit_order(
+            &mut self,
+            order: &OrderRequest,
+        ) -> quant_exchange::error::Result<OrderAck> {
+            Ok(OrderAck {
+                exchange: Exchange::Okex,
+                symbol: order.symbol.clone(),
+                order_id: "1".to_string(),
+                client_order_id: order.client_order_id.clone(),
+                timestamp: Utc::now(),
+            })
+        }
+
+        async fn cancel(
+            &mut self,
+            _symbol: &str,
+            _order_id: &OrderId,
+        ) -> quant_exchange::error::Result<OrderAck> {
+            Err(quant_exchange::error::Error::Other("Order does not exist"))
+        }
+
+        async fn balances(&mut self) -> quant_exchange::error::Result<Vec<Balance>> {
+            Ok(Vec::new())
+        }
+
+        async fn positions(&mut self) -> quant_exchange::error::Result<Vec<Position>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe_book(&mut self, _symbol: &str) -> quant_exchange::error::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 没有持仓和挂单时按 K 线收盘价买入 1 个，同时向没有注册的交易所下单
+    struct BuyOnBar {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Strategy for BuyOnBar {
+        fn name(&self) -> &str {
+            "buy-on-bar"
+        }
+
+        async fn on_bar(&mut self, ctx: &mut Context<'_>, bar: &Bar) -> Result<()> {
+            let position = ctx.portfolio().net_quantity(&bar.exchange, &bar.symbol);
+            if position.is_zero() && ctx.open_orders().next().is_none() {
+                let request = OrderRequest::market(&bar.symbol, Side::Buy, "1".parse().unwrap());
+                ctx.submit(Exchange::Okex, request.clone());
+                ctx.submit(Exchange::Binance, request);
+            }
+            Ok(())
+        }
+
+        async fn on_order(&mut self, ctx: &mut Context<'_>, order: &Order) -> Result<()> {
+            self.log.lock().unwrap().push(format!(
+                "order {:?} {}",
+                order.status,
+                ctx.now().timestamp()
+            ));
+            Ok(())
+        }
+
+        async fn on_action_error(
+            &mut self,
+            _ctx: &mut Context<'_>,
+            _action: &Action,
+            error: &quant_order::error::Error,
+        ) -> Result<()> {
+            self.log.lock().unwrap().push(format!("error {error}"));
+            Ok(())
+        }
+
+        async fn on_fill(&mut self, _ctx: &mut Context<'_>, fill: &Fill) -> Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("fill {}", fill.quantity));
+            Ok(())
+        }
+    }
+
+    fn bar(seconds: i64) -> Bar {
+        let time = DateTime::from_timestamp(seconds, 0).unwrap();
+        Bar {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            bar_type: BarType::Time(Interval::minutes(1)),
+            open_time: time - Interval::minutes(1).duration(),
+            close_time: time,
+            open: "100".parse().unwrap(),
+            high: "100".parse().unwrap(),
+            low: "100".parse().unwrap(),
+            close: "100".parse().unwrap(),
+            volume: "1".parse().unwrap(),
+            quote_volume: "100".parse().unwrap(),
+            vwap: "100".parse().unwrap(),
+            trade_count: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_route_actions() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut router = OrderRouter::new();
+        router.add_venue(MockVenue);
+        let mut runtime = StrategyRuntime::new(router);
+        runtime.add_strategy(BuyOnBar { log: log.clone() });
+        runtime.init().await.unwrap();
+
+        runtime.on_bar(&bar(60)).await.unwrap();
+        let client_order_id = runtime
+            .router()
+            .tracker()
+            .open_orders()
+            .next()
+            .and_then(|order| order.client_order_id.clone())
+            .unwrap();
+        assert_eq!(
+            runtime
+                .market()
+                .bars(&Exchange::Okex, "BTC-USDT", bar(60).bar_type, 10)
+                .len(),
+            1
+        );
+
+        let fill = Fill {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".to_string(),
+            trade_id: "t1".to_string(),
+            order_id: "1".to_string(),
+            client_order_id: Some(client_order_id),
+            side: Side::Buy,
+            price: "100".parse().unwrap(),
+            quantity: "1".parse().unwrap(),
+            fee: Default::default(),
+            fee_asset: "BTC".to_string(),
+            is_maker: false,
+            timestamp: DateTime::from_timestamp(61, 0).unwrap(),
+        };
+        runtime.on_fill(&fill).await.unwrap();
+        // 重复推送的成交不再计入持仓，也不通知策略
+        runtime.on_fill(&fill).await.unwrap();
+        assert_eq!(
+            runtime
+                .portfolio()
+                .net_quantity(&Exchange::Okex, "BTC-USDT"),
+            "1".parse().unwrap()
+        );
+
+        // 已有持仓，不再下单
+        runtime.on_bar(&bar(120)).await.unwrap();
+        assert_eq!(runtime.router().tracker().open_orders().count(), 0);
+        assert!(!runtime.owners.contains_key(""));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "order New 60".to_string(),
+                "error No trading venue registered for Binance".to_string(),
+                format!("order {:?} 60", OrderStatus::Rejected),
+                "fill 1".to_string(),
+                "order Filled 61".to_string(),
+            ]
+        );
+    }
+}
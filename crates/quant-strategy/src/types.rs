@@ -1,15 +1,17 @@
-// pub struct MarketData{
-//     pub timestamp: u64,
-//     pub 
-// }
+use quant_schema::{Exchange, OrderId, OrderRequest};
 
-// pub enum Signal{
-//     Buy,
-//     Sell,
-//     Hold,
-//     ClosePosition
-// }
+/// 策略通过 `Context` 发出的交易指令，由运行时在回调返回后依次发送到 quant-order
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// 下单，`request.client_order_id` 已由 `Context::submit` 分配
+    Submit {
+        exchange: Exchange,
+        request: OrderRequest,
+    },
 
-// pub enum Contact{
-
-// }
\ No newline at end of file
+    Cancel {
+        exchange: Exchange,
+        symbol: String,
+        order_id: OrderId,
+    },
+}